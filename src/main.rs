@@ -12,11 +12,13 @@ use gtk::{Application, ApplicationWindow, Box, Button, Entry, Label, ListBox, Sc
           Align};
 use gtk::gdk_pixbuf::Pixbuf;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::{File, read_dir, create_dir_all};
-use std::io::{BufRead, BufReader, Read};
+use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::process::{Command, Stdio};
 use serde::{Deserialize, Serialize};
 use gtk::glib;
@@ -44,8 +46,64 @@ fn get_config_file() -> PathBuf {
     path
 }
 
+// Data directory in the home folder (movies.db, posters, cast photos, log)
+fn get_data_dir() -> PathBuf {
+    dirs::home_dir()
+        .expect("Could not find home directory")
+        .join(".movie_database")
+}
+
+fn get_log_file() -> PathBuf {
+    get_data_dir().join("movie_db.log")
+}
+
+// Append a timestamped line to the log file (best effort)
+fn append_to_log(message: &str) {
+    use std::io::Write;
+    let line = format!("[{}] {}\n", chrono::Local::now().format("%Y-%m-%d %H:%M:%S"), message);
+    if let Ok(mut file) = std::fs::OpenOptions::new().create(true).append(true).open(get_log_file()) {
+        let _ = file.write_all(line.as_bytes());
+    }
+}
+
+// Print to stderr and keep a copy in the log file for the Diagnostics dialog
+macro_rules! log_warn {
+    ($($arg:tt)*) => {{
+        let message = format!($($arg)*);
+        eprintln!("{}", message);
+        append_to_log(&message);
+    }};
+}
+
+// Last `max_lines` lines of the log file
+fn read_log_tail(max_lines: usize) -> String {
+    let contents = std::fs::read_to_string(get_log_file()).unwrap_or_default();
+    let lines: Vec<&str> = contents.lines().collect();
+    lines[lines.len().saturating_sub(max_lines)..].join("\n")
+}
+
+// Locate an executable on PATH
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    let path_var = std::env::var_os("PATH")?;
+    std::env::split_paths(&path_var)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+// Number of files and total bytes directly inside a directory
+fn dir_usage(dir: &Path) -> (usize, u64) {
+    read_dir(dir)
+        .map(|entries| {
+            entries.flatten()
+                .filter_map(|entry| entry.metadata().ok())
+                .filter(|meta| meta.is_file())
+                .fold((0, 0), |(count, bytes), meta| (count + 1, bytes + meta.len()))
+        })
+        .unwrap_or((0, 0))
+}
+
 // Configuration structure
-#[derive(Serialize, Deserialize, Default, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 struct Config {
     tmdb_api_key: String,
     #[serde(default)]
@@ -54,6 +112,154 @@ struct Config {
     auto_scan_on_startup: bool,
     #[serde(default = "default_year_cutoff")]
     year_cutoff: i32,
+    #[serde(default = "default_min_vote_count")]
+    min_vote_count: u32,  // TMDB votes a search result needs to be preferred when auto-matching
+    #[serde(default = "default_theme")]
+    theme: String,  // "system", "light" or "dark"
+    #[serde(default = "default_list_thumb_width")]
+    list_thumb_width: i32,
+    #[serde(default = "default_list_thumb_height")]
+    list_thumb_height: i32,
+    #[serde(default)]
+    last_scanned: HashMap<String, String>,  // directory -> ISO timestamp of last completed scan
+    #[serde(default = "default_storage_format")]
+    storage_format: String,  // "sqlite", "database", "array" or "lines" - format used when saving movies.db
+    #[serde(default = "default_link_templates")]
+    link_templates: Vec<LinkTemplate>,  // External site buttons in the details pane and dialog
+    #[serde(default = "default_min_file_size_mb", alias = "min_video_size_mb")]
+    min_file_size_mb: u64,  // Scans skip smaller video files (samples, trailers); 0 disables
+    #[serde(default = "default_scan_ignore_patterns")]
+    scan_ignore_patterns: Vec<String>,  // Scans skip files whose names contain any of these
+    #[serde(default = "default_scan_exclude_patterns")]
+    scan_exclude_patterns: Vec<String>,  // Folder names scans don't descend into; * and ? wildcards
+    #[serde(default = "default_video_extensions")]
+    video_extensions: Vec<String>,  // Lowercase, without the dot; files with other extensions aren't scanned
+    #[serde(default = "default_player_command")]
+    player_command: String,  // Program and arguments; %f is replaced by the file path, else it's appended
+    #[serde(default = "default_view_mode")]
+    view_mode: String,  // "list" or "grid" - last view chosen with the view toggle
+    #[serde(default = "default_max_concurrent_requests")]
+    max_concurrent_requests: usize,  // TMDB requests a scan may have in flight at once
+    #[serde(default = "default_max_cast_members")]
+    max_cast_members: usize,  // Cast stored per movie from TMDB credits; 0 keeps everyone
+    #[serde(default = "default_window_width")]
+    window_width: i32,  // Unmaximized window size when the app was last closed
+    #[serde(default = "default_window_height")]
+    window_height: i32,
+    #[serde(default = "default_window_maximized")]
+    window_maximized: bool,
+    #[serde(default)]
+    use_ffprobe: bool,  // Read resolution, codecs and duration from files with ffprobe when scanning or associating
+    #[serde(default)]
+    omdb_api_key: String,  // Optional; scans try OMDb for titles TMDB can't find
+    #[serde(default = "default_tmdb_language")]
+    tmdb_language: String,  // TMDB locale for titles, overviews and genre names, e.g. "de-DE"
+    #[serde(default = "default_last_sort")]
+    last_sort: String,  // Sort dropdown choice when the app was last used, one of SORT_OPTIONS
+    #[serde(default)]
+    last_genres: Vec<String>,  // Genre filter selection when the app was last used
+    #[serde(default)]
+    last_match_all_genres: bool,
+}
+
+// An external site link; `url` may use {tmdb_id}, {imdb_id} and {title} placeholders
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct LinkTemplate {
+    name: String,
+    url: String,
+}
+
+impl LinkTemplate {
+    // The URL for a movie, or None when the movie lacks an ID the template needs
+    fn resolve(&self, movie: &Movie) -> Option<String> {
+        if self.url.contains("{tmdb_id}") && movie.tmdb_id == 0 {
+            return None;
+        }
+        if self.url.contains("{imdb_id}") && movie.imdb_id.is_empty() {
+            return None;
+        }
+        Some(self.url
+            .replace("{tmdb_id}", &movie.tmdb_id.to_string())
+            .replace("{imdb_id}", &movie.imdb_id)
+            .replace("{title}", &urlencoding::encode(&movie.title)))
+    }
+}
+
+// Parse "Name = URL" lines from Settings, skipping blank or malformed lines
+fn parse_link_templates(text: &str) -> Vec<LinkTemplate> {
+    text.lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(name, url)| LinkTemplate { name: name.trim().to_string(), url: url.trim().to_string() })
+        .filter(|link| !link.name.is_empty() && link.url.starts_with("http"))
+        .collect()
+}
+
+fn format_link_templates(links: &[LinkTemplate]) -> String {
+    links.iter()
+        .map(|link| format!("{} = {}", link.name, link.url))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            tmdb_api_key: String::new(),
+            scan_directories: Vec::new(),
+            auto_scan_on_startup: default_auto_scan(),
+            year_cutoff: default_year_cutoff(),
+            min_vote_count: default_min_vote_count(),
+            theme: default_theme(),
+            list_thumb_width: default_list_thumb_width(),
+            list_thumb_height: default_list_thumb_height(),
+            last_scanned: HashMap::new(),
+            storage_format: default_storage_format(),
+            link_templates: default_link_templates(),
+            min_file_size_mb: default_min_file_size_mb(),
+            scan_ignore_patterns: default_scan_ignore_patterns(),
+            scan_exclude_patterns: default_scan_exclude_patterns(),
+            video_extensions: default_video_extensions(),
+            player_command: default_player_command(),
+            view_mode: default_view_mode(),
+            max_concurrent_requests: default_max_concurrent_requests(),
+            max_cast_members: default_max_cast_members(),
+            window_width: default_window_width(),
+            window_height: default_window_height(),
+            window_maximized: default_window_maximized(),
+            use_ffprobe: false,
+            omdb_api_key: String::new(),
+            tmdb_language: default_tmdb_language(),
+            last_sort: default_last_sort(),
+            last_genres: Vec::new(),
+            last_match_all_genres: false,
+        }
+    }
+}
+
+impl Config {
+    // List thumbnail box size; validated once by validate_list_thumb_size at load
+    fn list_thumb_size(&self) -> (i32, i32) {
+        (self.list_thumb_width, self.list_thumb_height)
+    }
+
+    // Clamp both thumbnail dimensions to a sane range. A box that is not 2:3 is kept
+    // as configured; posters are fitted inside it without stretching.
+    fn validate_list_thumb_size(&mut self) {
+        let width = self.list_thumb_width.clamp(MIN_LIST_THUMB_WIDTH, MAX_LIST_THUMB_WIDTH);
+        let height = self.list_thumb_height.clamp(MIN_LIST_THUMB_WIDTH * 3 / 2, MAX_LIST_THUMB_WIDTH * 3 / 2);
+        if (width, height) != (self.list_thumb_width, self.list_thumb_height) {
+            log_warn!("Warning: list thumbnail {}x{} is out of range, using {}x{}",
+                self.list_thumb_width, self.list_thumb_height, width, height);
+        } else if width * 3 != height * 2 {
+            log_warn!("Warning: list thumbnail {}x{} is not 2:3, posters will be fitted inside it", width, height);
+        }
+        self.list_thumb_width = width;
+        self.list_thumb_height = height;
+    }
+}
+
+fn default_tmdb_language() -> String {
+    String::from("en-US")
 }
 
 fn default_auto_scan() -> bool {
@@ -64,6 +270,444 @@ fn default_year_cutoff() -> i32 {
     1966  // Default to pre-1966 movies
 }
 
+fn default_min_vote_count() -> u32 {
+    20  // Skips obscure same-named films with only a handful of votes
+}
+
+fn default_max_concurrent_requests() -> usize {
+    4  // Keeps parallel scans under TMDB's rate limit
+}
+
+fn default_max_cast_members() -> usize {
+    10
+}
+
+fn default_window_width() -> i32 {
+    1000
+}
+
+fn default_window_height() -> i32 {
+    700
+}
+
+fn default_window_maximized() -> bool {
+    true
+}
+
+fn default_theme() -> String {
+    "system".to_string()  // Follow the desktop theme
+}
+
+const THEME_OPTIONS: [&str; 3] = ["system", "light", "dark"];
+
+fn default_list_thumb_width() -> i32 {
+    60
+}
+
+fn default_list_thumb_height() -> i32 {
+    90
+}
+
+// One row per movie, so edits don't rewrite the whole file
+const DEFAULT_STORAGE_FORMAT: &str = "sqlite";
+
+fn default_storage_format() -> String {
+    DEFAULT_STORAGE_FORMAT.to_string()
+}
+
+fn default_min_file_size_mb() -> u64 {
+    50
+}
+
+fn default_scan_ignore_patterns() -> Vec<String> {
+    vec!["sample".to_string(), "trailer".to_string()]
+}
+
+fn default_scan_exclude_patterns() -> Vec<String> {
+    ["sample", "samples", "extras", "featurettes", "behind the scenes", ".*"]
+        .iter()
+        .map(|pattern| pattern.to_string())
+        .collect()
+}
+
+fn default_video_extensions() -> Vec<String> {
+    ["mp4", "mkv", "avi", "mov", "wmv", "flv", "webm", "m4v", "mpg", "mpeg", "ts", "m2ts", "ogv", "divx"]
+        .iter()
+        .map(|ext| ext.to_string())
+        .collect()
+}
+
+// Parse the comma-separated extension list from Settings: lowercase, no leading dots, no repeats
+fn parse_video_extensions(text: &str) -> Vec<String> {
+    let mut extensions: Vec<String> = Vec::new();
+    for ext in text.split(',').map(|ext| ext.trim().trim_start_matches('.').to_lowercase()) {
+        if !ext.is_empty() && !extensions.contains(&ext) {
+            extensions.push(ext);
+        }
+    }
+    extensions
+}
+
+fn default_player_command() -> String {
+    "vlc".to_string()
+}
+
+fn default_view_mode() -> String {
+    "list".to_string()
+}
+
+fn default_last_sort() -> String {
+    SORT_OPTIONS[0].to_string()
+}
+
+fn default_link_templates() -> Vec<LinkTemplate> {
+    [
+        ("IMDb", "https://www.imdb.com/title/{imdb_id}"),
+        ("TMDB", "https://www.themoviedb.org/movie/{tmdb_id}"),
+        ("Letterboxd", "https://letterboxd.com/tmdb/{tmdb_id}"),
+    ]
+    .into_iter()
+    .map(|(name, url)| LinkTemplate { name: name.to_string(), url: url.to_string() })
+    .collect()
+}
+
+// Storage formats for movies.db: config value and Settings label
+// TMDB locales offered in Settings: (language code, label)
+const TMDB_LANGUAGES: [(&str, &str); 20] = [
+    ("en-US", "English (US)"),
+    ("en-GB", "English (UK)"),
+    ("de-DE", "German"),
+    ("fr-FR", "French"),
+    ("es-ES", "Spanish (Spain)"),
+    ("es-MX", "Spanish (Latin America)"),
+    ("it-IT", "Italian"),
+    ("pt-BR", "Portuguese (Brazil)"),
+    ("pt-PT", "Portuguese (Portugal)"),
+    ("nl-NL", "Dutch"),
+    ("sv-SE", "Swedish"),
+    ("da-DK", "Danish"),
+    ("no-NO", "Norwegian"),
+    ("fi-FI", "Finnish"),
+    ("pl-PL", "Polish"),
+    ("cs-CZ", "Czech"),
+    ("ru-RU", "Russian"),
+    ("ja-JP", "Japanese"),
+    ("ko-KR", "Korean"),
+    ("zh-CN", "Chinese (Simplified)"),
+];
+
+const STORAGE_FORMATS: [(&str, &str); 4] = [
+    ("sqlite", "SQLite (saves only changed movies)"),
+    ("database", "Database object (includes TMDB cache)"),
+    ("array", "JSON array of movies"),
+    ("lines", "Line-delimited JSON"),
+];
+
+const MIN_LIST_THUMB_WIDTH: i32 = 40;
+const MAX_LIST_THUMB_WIDTH: i32 = 300;
+
+// List thumbnail presets: label and (width, height)
+const LIST_THUMB_PRESETS: [(&str, (i32, i32)); 3] = [
+    ("Small (60×90)", (60, 90)),
+    ("Medium (100×150)", (100, 150)),
+    ("Large (160×240)", (160, 240)),
+];
+
+thread_local! {
+    // Thumbnail size used by create_movie_row, loaded from Config at startup and updated from Settings
+    static LIST_THUMB_SIZE: std::cell::Cell<(i32, i32)> = const { std::cell::Cell::new((60, 90)) };
+    // Movies whose video file was gone at the last check_missing_files run
+    static MISSING_FILES: RefCell<HashSet<u32>> = RefCell::new(HashSet::new());
+    // Status bar button that restores the last deleted movie; shown after a delete
+    static UNDO_DELETE_BUTTON: RefCell<Option<Button>> = const { RefCell::new(None) };
+    // Status bar button that retries a failed save; shown until a save succeeds
+    static SAVE_RETRY_BUTTON: RefCell<Option<Button>> = const { RefCell::new(None) };
+}
+
+// The `limit` most common non-empty names with their counts, most common first and ties
+// in alphabetical order
+fn top_counts<'a>(names: impl Iterator<Item = &'a str>, limit: usize) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for name in names.filter(|name| !name.is_empty()) {
+        *counts.entry(name).or_insert(0) += 1;
+    }
+    let mut counts: Vec<(String, usize)> = counts.into_iter()
+        .map(|(name, count)| (name.to_string(), count))
+        .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts.truncate(limit);
+    counts
+}
+
+// Library figures shown in the Statistics dialog and written by its Export Report button
+#[derive(Clone)]
+struct Stats {
+    total_movies: usize,
+    avg_rating: f32,
+    total_runtime: u32,  // Minutes
+    avg_runtime: u32,
+    oldest_year: u16,
+    newest_year: u16,
+    top_rated: Vec<(String, u16, f32)>,  // Up to 100 (title, year, rating), best first
+    genres: Vec<(String, usize)>,  // Most common first
+    top_actors: Vec<(String, usize)>,
+    top_directors: Vec<(String, usize)>,
+    decades: Vec<(u16, usize)>,  // Oldest first
+}
+
+impl Stats {
+    fn to_text(&self) -> String {
+        let mut out = format!(
+            "Movie Database Statistics\n\
+            Generated {}\n\n\
+            OVERVIEW\n\
+            Total Movies: {}\n\
+            Average Rating: {:.2}/10\n\
+            Total Runtime: {} hours ({} minutes)\n\
+            Average Runtime: {} minutes\n\
+            Year Range: {} - {}\n",
+            chrono::Local::now().format("%Y-%m-%d %H:%M"),
+            self.total_movies,
+            self.avg_rating,
+            self.total_runtime / 60,
+            self.total_runtime,
+            self.avg_runtime,
+            self.oldest_year,
+            self.newest_year
+        );
+        out.push_str(&format!("\nTOP {} RATED MOVIES\n", self.top_rated.len()));
+        for (rank, (title, year, rating)) in self.top_rated.iter().enumerate() {
+            out.push_str(&format!("{:>3}. {} ({}) - {:.1}\n", rank + 1, title, year, rating));
+        }
+        let counted = [
+            ("GENRES", &self.genres),
+            ("TOP 10 ACTORS", &self.top_actors),
+            ("TOP 10 DIRECTORS", &self.top_directors),
+        ];
+        for (heading, counts) in counted {
+            out.push_str(&format!("\n{}\n", heading));
+            for (name, count) in counts {
+                out.push_str(&format!("{}: {} movies\n", name, count));
+            }
+        }
+        out.push_str("\nBY DECADE\n");
+        for (decade, count) in &self.decades {
+            out.push_str(&format!("{}s: {} movies\n", decade, count));
+        }
+        out
+    }
+    
+    // Standalone page with inline styling so it opens the same anywhere
+    fn to_html(&self) -> String {
+        let count_table = |header: &str, counts: Vec<(String, usize)>| {
+            let rows: String = counts.iter()
+                .map(|(name, count)| format!("<tr><td>{}</td><td>{}</td></tr>\n", escape_markup(name), count))
+                .collect();
+            format!("<table>\n<tr><th>{}</th><th>Movies</th></tr>\n{}</table>\n", header, rows)
+        };
+        let top_rated_rows: String = self.top_rated.iter().enumerate()
+            .map(|(rank, (title, year, rating))| format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:.1}</td></tr>\n",
+                rank + 1, escape_markup(title), year, rating
+            ))
+            .collect();
+        let decades = self.decades.iter().map(|(decade, count)| (format!("{}s", decade), *count)).collect();
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+            <title>Movie Database Statistics</title>\n\
+            <style>\n\
+            body {{ font-family: sans-serif; margin: 2em; }}\n\
+            table {{ border-collapse: collapse; margin-bottom: 1.5em; }}\n\
+            th, td {{ border: 1px solid #ccc; padding: 4px 10px; text-align: left; }}\n\
+            th {{ background: #eee; }}\n\
+            </style>\n</head>\n<body>\n\
+            <h1>Movie Database Statistics</h1>\n\
+            <p>Generated {}</p>\n\
+            <h2>Overview</h2>\n<table>\n\
+            <tr><th>Total Movies</th><td>{}</td></tr>\n\
+            <tr><th>Average Rating</th><td>{:.2}/10</td></tr>\n\
+            <tr><th>Total Runtime</th><td>{} hours ({} minutes)</td></tr>\n\
+            <tr><th>Average Runtime</th><td>{} minutes</td></tr>\n\
+            <tr><th>Year Range</th><td>{} - {}</td></tr>\n\
+            </table>\n\
+            <h2>Top {} Rated Movies</h2>\n<table>\n\
+            <tr><th>#</th><th>Title</th><th>Year</th><th>Rating</th></tr>\n{}</table>\n\
+            <h2>Genres</h2>\n{}\
+            <h2>Top 10 Actors</h2>\n{}\
+            <h2>Top 10 Directors</h2>\n{}\
+            <h2>By Decade</h2>\n{}\
+            </body>\n</html>\n",
+            chrono::Local::now().format("%Y-%m-%d %H:%M"),
+            self.total_movies,
+            self.avg_rating,
+            self.total_runtime / 60,
+            self.total_runtime,
+            self.avg_runtime,
+            self.oldest_year,
+            self.newest_year,
+            self.top_rated.len(),
+            top_rated_rows,
+            count_table("Genre", self.genres.clone()),
+            count_table("Actor", self.top_actors.clone()),
+            count_table("Director", self.top_directors.clone()),
+            count_table("Decade", decades)
+        )
+    }
+}
+
+// File sizes for the Statistics dialog
+struct DiskUsage {
+    total_bytes: u64,
+    movies_measured: usize,  // Movies with at least one file on disk
+    missing: usize,  // Movies with a file that no longer exists
+    unassociated: usize,  // Movies without any file
+    largest: Vec<(String, u64)>,  // Ten biggest files as (movie title, bytes), largest first
+}
+
+// Stat every file of the given (title, files) movies. Blocking, so call it off the main thread.
+fn measure_disk_usage(movies: Vec<(String, Vec<String>)>) -> DiskUsage {
+    let mut usage = DiskUsage { total_bytes: 0, movies_measured: 0, missing: 0, unassociated: 0, largest: Vec::new() };
+    let mut files = Vec::new();
+    for (title, paths) in movies {
+        if paths.is_empty() {
+            usage.unassociated += 1;
+            continue;
+        }
+        let sizes: Vec<u64> = paths.iter()
+            .filter_map(|path| std::fs::metadata(path).ok())
+            .map(|meta| meta.len())
+            .collect();
+        if sizes.len() < paths.len() {
+            usage.missing += 1;
+        }
+        if !sizes.is_empty() {
+            usage.movies_measured += 1;
+        }
+        for size in sizes {
+            usage.total_bytes += size;
+            files.push((title.clone(), size));
+        }
+    }
+    files.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+    files.truncate(10);
+    usage.largest = files;
+    usage
+}
+
+// Bytes as GB, or TB from 1 TB up
+fn format_disk_size(bytes: u64) -> String {
+    const GB: f64 = 1_073_741_824.0;
+    let gb = bytes as f64 / GB;
+    if gb >= 1024.0 {
+        format!("{:.2} TB", gb / 1024.0)
+    } else {
+        format!("{:.2} GB", gb)
+    }
+}
+
+// Show the Undo button for a movie just removed with MovieDatabase::delete_movie
+fn offer_undo_delete(title: &str) {
+    UNDO_DELETE_BUTTON.with(|button| {
+        if let Some(button) = button.borrow().as_ref() {
+            button.set_tooltip_text(Some(&format!("Restore \"{}\" (Ctrl+Z)", title)));
+            button.set_visible(true);
+        }
+    });
+}
+
+// Hide the Undo button once the deleted movie can no longer be restored
+fn hide_undo_delete() {
+    UNDO_DELETE_BUTTON.with(|button| {
+        if let Some(button) = button.borrow().as_ref() {
+            button.set_visible(false);
+        }
+    });
+}
+
+// Show or hide the save retry button after MovieDatabase::save_to_file. Headless runs have
+// no button and just get the error back.
+fn show_save_result(result: &std::io::Result<()>) {
+    SAVE_RETRY_BUTTON.with(|button| {
+        if let Some(button) = button.borrow().as_ref() {
+            if let Err(e) = result {
+                button.set_tooltip_text(Some(&format!("{}\n\nYour changes are kept in memory. {}", e, save_error_hint(e))));
+            }
+            button.set_visible(result.is_err());
+        }
+    });
+}
+
+// What the user can do about a failed save, by the kind of error
+fn save_error_hint(e: &std::io::Error) -> &'static str {
+    match e.kind() {
+        std::io::ErrorKind::StorageFull => "Free up some disk space, then click to try again.",
+        std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::ReadOnlyFilesystem => {
+            "Make sure the library file and its folder are writable, then click to try again."
+        }
+        std::io::ErrorKind::ResourceBusy => "Another program has the library open. Close it, then click to try again.",
+        // The library failed to load, so saving stays off until the app is restarted
+        std::io::ErrorKind::InvalidData => "Trying again won't help until the file is fixed or moved and the app restarted.",
+        _ => "Fix the problem, then click to try again.",
+    }
+}
+
+// Linked files of which none exist any more. A movie with one copy left can still be played,
+// so it isn't missing. Shared by the missing badge, the filter and Prune Missing.
+fn no_file_left(paths: &[String]) -> bool {
+    !paths.is_empty() && !paths.iter().any(|path| Path::new(path).exists())
+}
+
+// No file associated, or none of its files were there at the last check
+fn file_missing(movie: &Movie) -> bool {
+    movie.file_paths.is_empty() || MISSING_FILES.with(|missing| missing.borrow().contains(&movie.id))
+}
+
+// Look for missing video files on a background thread (slow disks and network shares
+// shouldn't stall the UI), then call on_done if the set of missing movies changed
+fn check_missing_files(db: &Rc<RefCell<MovieDatabase>>, on_done: impl FnOnce() + 'static) {
+    let files: Vec<(u32, Vec<String>)> = db.borrow().movies.values()
+        .filter(|m| !m.file_paths.is_empty())
+        .map(|m| (m.id, m.file_paths.clone()))
+        .collect();
+    let (sender, receiver) = async_channel::bounded::<HashSet<u32>>(1);
+    std::thread::spawn(move || {
+        let missing = files.into_iter()
+            .filter(|(_, paths)| no_file_left(paths))
+            .map(|(id, _)| id)
+            .collect();
+        let _ = sender.send_blocking(missing);
+    });
+    
+    let db = db.clone();
+    glib::spawn_future_local(async move {
+        let Ok(missing) = receiver.recv().await else {
+            return;
+        };
+        let changed = MISSING_FILES.with(|current| {
+            let mut current = current.borrow_mut();
+            let changed = *current != missing;
+            *current = missing;
+            changed
+        });
+        if changed {
+            db.borrow().invalidate_result_cache();
+            on_done();
+        }
+    });
+}
+
+// Apply the configured theme override, or fall back to the desktop theme for "system"
+fn apply_theme(theme: &str) {
+    let Some(settings) = gtk::Settings::default() else {
+        return;
+    };
+    match theme {
+        "light" => settings.set_gtk_application_prefer_dark_theme(false),
+        "dark" => settings.set_gtk_application_prefer_dark_theme(true),
+        _ => settings.reset_property("gtk-application-prefer-dark-theme"),
+    }
+}
+
 // Save config to file
 fn save_config(config: &Config) -> std::io::Result<()> {
     let config_dir = get_config_dir();
@@ -71,9 +715,248 @@ fn save_config(config: &Config) -> std::io::Result<()> {
     
     let config_file = get_config_file();
     let json = serde_json::to_string_pretty(config)?;
-    std::fs::write(config_file, json)?;
+    write_file_atomically(&config_file, json.as_bytes())
+}
+
+// Record that a scan of these directories just finished
+fn record_scan_completed(directories: &[String]) {
+    if directories.is_empty() {
+        return;
+    }
+    let mut config = load_config().unwrap_or_default();
+    let now = chrono::Local::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+    for dir in directories {
+        config.last_scanned.insert(dir.clone(), now.clone());
+    }
+    if let Err(e) = save_config(&config) {
+        log_warn!("Warning: Failed to save last scan time: {}", e);
+    }
+}
+
+// Human-readable "last scanned" text for the Settings directory list
+fn last_scanned_text(config: &Config, dir: &str) -> String {
+    match config.last_scanned.get(dir) {
+        Some(timestamp) => match chrono::NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%dT%H:%M:%S") {
+            Ok(time) => format!("Last scanned {}", time.format("%Y-%m-%d %H:%M")),
+            Err(_) => format!("Last scanned {}", timestamp),
+        },
+        None => "Never scanned".to_string(),
+    }
+}
+
+// Overrides from the command line, taking precedence over the environment and config file
+#[derive(Debug, Default, Clone)]
+struct CliOverrides {
+    api_key: Option<String>,
+    data_file: Option<String>,
+    scan_dir: Option<String>,  // `--scan <dir>`: index the directory without the GUI and exit
+}
+
+static CLI_OVERRIDES: std::sync::OnceLock<CliOverrides> = std::sync::OnceLock::new();
+
+// Settings' TMDB language, read by every TMDB request whichever thread makes it.
+// Set at startup and when Settings are saved; empty leaves TMDB's default (English).
+static TMDB_LANGUAGE: std::sync::RwLock<String> = std::sync::RwLock::new(String::new());
+
+fn set_tmdb_language(language: &str) {
+    if let Ok(mut current) = TMDB_LANGUAGE.write() {
+        *current = language.to_string();
+    }
+}
+
+// Query parameters appended to TMDB URLs. Trailers are accepted in the chosen language or
+// English, since many films only have an English one.
+fn tmdb_language_param() -> String {
+    let language = TMDB_LANGUAGE.read().map(|language| language.clone()).unwrap_or_default();
+    if language.is_empty() {
+        return String::new();
+    }
+    let iso_639 = language.split('-').next().unwrap_or(&language);
+    format!("&language={}&include_video_language={},en", urlencoding::encode(&language), iso_639)
+}
+
+// Country whose age ratings are stored: the region of the metadata language ("en-GB" is
+// GB), or the US when the language doesn't name one
+fn tmdb_region() -> String {
+    let language = TMDB_LANGUAGE.read().map(|language| language.clone()).unwrap_or_default();
+    language.split('-').nth(1)
+        .filter(|region| region.len() == 2)
+        .map(|region| region.to_uppercase())
+        .unwrap_or_else(|| "US".to_string())
+}
+
+// Age rating for tmdb_region, falling back to the US one; empty when TMDB has neither
+fn regional_certification(lookup: impl Fn(&str) -> Option<String>) -> String {
+    lookup(&tmdb_region()).or_else(|| lookup("US")).unwrap_or_default()
+}
+
+// Pull `--api-key`/`--data-file`/`--scan` (as `--opt value` or `--opt=value`) out of the arguments.
+// Returns the overrides and the remaining arguments for GTK.
+fn parse_cli_overrides(args: Vec<String>) -> (CliOverrides, Vec<String>) {
+    let mut overrides = CliOverrides::default();
+    let mut remaining = Vec::new();
+    let mut args = args.into_iter();
+    
+    while let Some(arg) = args.next() {
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) => (name.to_string(), Some(value.to_string())),
+            None => (arg.clone(), None),
+        };
+        let slot = match name.as_str() {
+            "--api-key" => &mut overrides.api_key,
+            "--data-file" => &mut overrides.data_file,
+            "--scan" => &mut overrides.scan_dir,
+            _ => {
+                remaining.push(arg);
+                continue;
+            }
+        };
+        match inline_value.or_else(|| args.next()) {
+            Some(value) if !value.trim().is_empty() => *slot = Some(value.trim().to_string()),
+            _ => log_warn!("Warning: {} needs a value, ignoring", name),
+        }
+    }
+    
+    (overrides, remaining)
+}
+
+fn cli_overrides() -> CliOverrides {
+    CLI_OVERRIDES.get().cloned().unwrap_or_default()
+}
+
+// API key from the TMDB_API_KEY environment variable, if set and non-empty
+fn env_api_key() -> Option<String> {
+    std::env::var("TMDB_API_KEY").ok()
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty())
+}
+
+// Where the API key comes from when it isn't the config file. Precedence is
+// --api-key > TMDB_API_KEY > config file > prompt.
+fn external_api_key_source() -> Option<&'static str> {
+    if cli_overrides().api_key.is_some() {
+        Some("the --api-key argument")
+    } else if env_api_key().is_some() {
+        Some("the TMDB_API_KEY environment variable")
+    } else {
+        None
+    }
+}
+
+// Early versions kept movies.db and posters/ in whatever folder the app was started from.
+// Move them into the data directory the first time it has no database of its own; the
+// "posters/..." paths stored inside are pointed at the new folder by migrate_poster_paths.
+fn migrate_cwd_data(data_dir: &Path) {
+    let legacy_db = Path::new("movies.db");
+    let target_db = data_dir.join("movies.db");
+    if target_db.exists() || !legacy_db.is_file() {
+        return;
+    }
+    let cwd = std::env::current_dir().unwrap_or_default();
+    if std::fs::canonicalize(&cwd).ok() == std::fs::canonicalize(data_dir).ok() {
+        return;
+    }
+    if !is_movie_database(legacy_db) {
+        log_warn!("Warning: {} is not a movie database, leaving it in place", cwd.join(legacy_db).display());
+        return;
+    }
+    if let Err(e) = move_file(legacy_db, &target_db) {
+        log_warn!("Warning: Failed to move {} into {}: {}", cwd.join(legacy_db).display(), data_dir.display(), e);
+        return;
+    }
+    println!("Moved movies.db from {} to {}", cwd.display(), data_dir.display());
     
-    Ok(())
+    let legacy_posters = Path::new("posters");
+    let Ok(entries) = read_dir(legacy_posters) else {
+        return;
+    };
+    let target_posters = data_dir.join("posters");
+    if let Err(e) = create_dir_all(&target_posters) {
+        log_warn!("Warning: Failed to create {}: {}", target_posters.display(), e);
+        return;
+    }
+    let mut moved = 0;
+    for entry in entries.flatten() {
+        // Only poster images; anything else in the folder stays where it is
+        if !entry.file_type().is_ok_and(|t| t.is_file()) || !is_poster_image(&entry.path()) {
+            continue;
+        }
+        let target = target_posters.join(entry.file_name());
+        if target.exists() {
+            continue;
+        }
+        match move_file(&entry.path(), &target) {
+            Ok(()) => moved += 1,
+            Err(e) => log_warn!("Warning: Failed to move {}: {}", entry.path().display(), e),
+        }
+    }
+    // Only goes once everything in it has moved
+    let _ = std::fs::remove_dir(legacy_posters);
+    println!("Moved {} posters to {}", moved, target_posters.display());
+}
+
+// Whether a file is a movies.db this app wrote: SQLite with its movies and meta tables,
+// or one of the JSON formats whose first movie record reads back
+fn is_movie_database(path: &Path) -> bool {
+    let path_str = path.to_string_lossy();
+    if is_sqlite_file(&path_str) {
+        let Ok(conn) = rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY) else {
+            return false;
+        };
+        return conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name IN ('movies', 'meta')",
+            [],
+            |row| row.get::<_, i64>(0),
+        ).is_ok_and(|tables| tables == 2);
+    }
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return false;
+    };
+    let first_record = match serde_json::from_str::<serde_json::Value>(&contents) {
+        Ok(serde_json::Value::Object(root)) => match root.get("movies") {
+            Some(serde_json::Value::Object(movies)) => movies.values().next().cloned(),
+            Some(_) => return false,
+            // Line-delimited JSON with a single movie
+            None => Some(serde_json::Value::Object(root)),
+        },
+        Ok(serde_json::Value::Array(movies)) => movies.into_iter().next(),
+        Ok(_) => return false,
+        Err(_) => match contents.lines().find(|line| !line.trim().is_empty()) {
+            Some(line) => match serde_json::from_str(line) {
+                Ok(record) => Some(record),
+                Err(_) => return false,
+            },
+            None => return false,
+        },
+    };
+    first_record.is_none_or(|record| movie_from_record(record).is_ok())
+}
+
+fn is_poster_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ["jpg", "jpeg", "png", "webp"].contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+// Rename, or copy and delete when the two paths are on different filesystems
+fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::rename(from, to).or_else(|_| {
+        std::fs::copy(from, to)?;
+        std::fs::remove_file(from)
+    })
+}
+
+// The movies.db path: `--data-file` if given, otherwise the one in the data directory
+fn resolve_data_file(overrides: &CliOverrides, data_dir: &Path) -> String {
+    match &overrides.data_file {
+        Some(data_file) => {
+            if let Some(parent) = Path::new(data_file).parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent).expect("Could not create data file directory");
+            }
+            data_file.clone()
+        }
+        None => data_dir.join("movies.db").to_string_lossy().to_string(),
+    }
 }
 
 // Load config from file
@@ -87,41 +970,70 @@ fn load_config() -> Option<Config> {
     let mut contents = String::new();
     file.read_to_string(&mut contents).ok()?;
     
-    serde_json::from_str(&contents).ok()
+    let mut config: Config = serde_json::from_str(&contents).ok()?;
+    config.validate_list_thumb_size();
+    Some(config)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+struct CrewMember {
+    name: String,
+    #[serde(default)]
+    job: String,
+    #[serde(default)]
+    department: String,  // TMDB department, e.g. "Directing", "Camera", "Sound"
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 struct CastMember {
     name: String,
     #[serde(default)]
     profile_path: String,  // TMDB profile photo URL
     #[serde(default)]
     character: String,     // Character name
+    #[serde(default)]
+    local_photo_path: String,  // Cached copy of the profile photo, if downloaded
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct WatchLogEntry {
     date: String,  // ISO format: "2026-01-01"
+    #[serde(default)]
     rating: Option<f32>,  // Personal rating 0-10
+    #[serde(default)]
     comments: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 struct Movie {
     id: u32,
+    // Defaults throughout, so a record missing a field (written by an older or newer
+    // version) still loads instead of being skipped
+    #[serde(default)]
     title: String,
+    #[serde(default)]
     year: u16,
+    #[serde(default)]
     director: String,
+    #[serde(default)]
     genre: Vec<String>,
+    #[serde(default)]
     rating: f32,
+    #[serde(default)]
     runtime: u16,
+    #[serde(default)]
     description: String,
     #[serde(default)]
     cast: Vec<String>,  // Keep for backwards compatibility
     #[serde(default)]
     cast_details: Vec<CastMember>,  // New detailed cast info
-    file_path: String,
+    // Every copy of the movie (e.g. 1080p and 4K); the first is the primary one. Older
+    // records stored a single "file_path" string, which loads as a one-element list.
+    #[serde(default, alias = "file_path", deserialize_with = "deserialize_file_paths")]
+    file_paths: Vec<String>,
+    #[serde(default)]
     poster_url: String,
+    #[serde(default)]
     tmdb_id: u32,
     #[serde(default)]
     imdb_id: String,  // IMDb ID (e.g., "tt0111161")
@@ -129,18 +1041,318 @@ struct Movie {
     poster_path: String,  // Local cached poster path
     #[serde(default)]
     watch_log: Vec<WatchLogEntry>,  // Watch history with comments
-}
-
-#[derive(Debug, Deserialize)]
-struct TMDBSearchResponse {
-    results: Vec<TMDBMovie>,
-}
-
-#[derive(Debug, Deserialize)]
+    #[serde(default)]
+    favorite: bool,
+    #[serde(default)]
+    watched: bool,
+    #[serde(default)]
+    my_rating: Option<f32>,  // Personal 0-10 score, separate from the TMDB vote average
+    #[serde(default)]
+    edited_fields: Vec<String>,  // Metadata fields changed in Edit Metadata, kept on refresh
+    #[serde(default)]
+    writer: String,  // Writer/Screenplay credits, comma-separated
+    #[serde(default)]
+    composer: String,  // Original Music Composer credits, comma-separated
+    #[serde(default)]
+    collection_id: u32,  // TMDB collection (franchise) id, 0 if none
+    #[serde(default)]
+    collection_name: String,
+    #[serde(default)]
+    certification: String,  // Age rating such as "PG-13", for the configured region; empty if unknown
+    #[serde(default)]
+    file_size: u64,  // Bytes, as of the last scan or local file refresh
+    #[serde(default)]
+    subtitles: Vec<String>,  // Sidecar subtitle files next to the video
+    #[serde(default)]
+    tags: Vec<String>,  // User's own labels ("rewatch", "4k"), kept across refreshes
+    #[serde(default)]
+    resolution: String,  // From ffprobe, e.g. "1920x1080"; empty if never probed
+    #[serde(default)]
+    video_codec: String,
+    #[serde(default)]
+    audio_codec: String,
+    #[serde(default)]
+    file_runtime_secs: u32,  // Container duration, as opposed to TMDB's nominal runtime
+    #[serde(default)]
+    crew: Vec<CrewMember>,  // Full crew in TMDB credit order
+    #[serde(default)]
+    date_added: i64,  // Unix seconds when added to the library, 0 for records older than this field
+    #[serde(default)]
+    watch_count: u32,  // Times started with Play; counted on launch, since we can't tell if it was finished
+    #[serde(default)]
+    last_watched: Option<i64>,  // Unix seconds of the last Play
+    #[serde(default)]
+    media_type: MediaType,
+    #[serde(default)]
+    seasons: u16,  // TV series only
+    #[serde(default)]
+    episodes: u16,  // TV series only
+    #[serde(default)]
+    first_air_date: String,  // TV series only, "YYYY-MM-DD"
+    #[serde(default)]
+    trailer_youtube_key: String,  // YouTube video id of the TMDB trailer, empty if none
+    #[serde(default)]
+    source: String,  // "omdb" when the metadata came from the OMDb fallback, empty for TMDB
+}
+
+// Whether a library entry is a film or a TV series. Series are found by SxxEyy episode
+// names when scanning and looked up with TMDB's /tv endpoints.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum MediaType {
+    #[default]
+    Movie,
+    Tv,
+}
+
+// Accept both the current list of paths and the old single path ("" meaning no file)
+fn deserialize_file_paths<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum FilePaths {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match FilePaths::deserialize(deserializer)? {
+        FilePaths::One(path) => file_paths_from(path),
+        FilePaths::Many(paths) => paths.into_iter().filter(|path| !path.is_empty()).collect(),
+    })
+}
+
+// A single optional path as a file list
+fn file_paths_from(file_path: String) -> Vec<String> {
+    if file_path.is_empty() {
+        Vec::new()
+    } else {
+        vec![file_path]
+    }
+}
+
+impl Movie {
+    // Log a play started from the app: a watch history entry, the watched flag, the play
+    // count and the time. Counted on launch, as the app can't see whether playback finished.
+    fn record_play(&mut self) {
+        let now = chrono::Local::now();
+        self.watch_log.push(WatchLogEntry {
+            date: now.format("%Y-%m-%d").to_string(),
+            rating: None,
+            comments: String::from("Watched"),
+        });
+        self.watched = true;
+        self.watch_count += 1;
+        self.last_watched = Some(now.timestamp());
+    }
+
+    // The main copy: used for file size, subtitles and TMDB lookups. Empty without a file.
+    fn primary_file(&self) -> &str {
+        self.file_paths.first().map_or("", |path| path.as_str())
+    }
+
+    // Copies that can be played right now
+    fn existing_files(&self) -> Vec<String> {
+        self.file_paths.iter()
+            .filter(|path| Path::new(path).exists())
+            .cloned()
+            .collect()
+    }
+}
+
+impl MediaType {
+    // Path segment of the TMDB API endpoints for this type
+    fn tmdb_path(self) -> &'static str {
+        match self {
+            MediaType::Movie => "movie",
+            MediaType::Tv => "tv",
+        }
+    }
+
+    // append_to_response for the details request: credits, trailers and age ratings, so
+    // they come back in the same round trip
+    fn details_append(self) -> &'static str {
+        match self {
+            MediaType::Movie => "credits,videos,release_dates",
+            MediaType::Tv => "credits,videos,content_ratings",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TMDBSearchResponse {
+    results: Vec<TMDBMovie>,
+}
+
+#[derive(Debug, Deserialize)]
 struct TMDBMovie {
     id: u32,
-    #[serde(default)]
+    #[serde(default, alias = "name")]  // TV search results use name and first_air_date
+    title: String,
+    #[serde(default, alias = "first_air_date")]
     release_date: Option<String>,
+    #[serde(default)]
+    vote_count: u32,
+    #[serde(default)]
+    vote_average: f32,
+    #[serde(default)]
+    popularity: f32,
+    #[serde(default)]
+    overview: String,
+}
+
+impl TMDBMovie {
+    // Row for the search result dialogs, straight from the search response so no
+    // per-result details request is needed
+    fn result_row(&self) -> TMDBSearchRow {
+        let year: u16 = self.release_date.as_deref()
+            .and_then(|date| date.split('-').next())
+            .and_then(|year| year.parse().ok())
+            .unwrap_or(0);
+        TMDBSearchRow {
+            tmdb_id: self.id,
+            title: self.title.clone(),
+            year: year.to_string(),
+            rating: self.vote_average,
+            overview: self.overview.clone(),
+        }
+    }
+}
+
+// Choose which search result to auto-match. Prefers the most popular result released
+// before the year cutoff with at least `min_vote_count` votes, then falls back to the
+// first result before the cutoff, then the first result overall.
+fn pick_tmdb_match(results: &[TMDBMovie], year_cutoff: i32, min_vote_count: u32) -> Option<u32> {
+    let before_cutoff = |movie: &&TMDBMovie| {
+        movie.release_date.as_deref()
+            .and_then(|date| date.split('-').next())
+            .and_then(|year| year.parse::<i32>().ok())
+            .map(|year| year <= year_cutoff)
+            .unwrap_or(false)
+    };
+    
+    results.iter()
+        .filter(before_cutoff)
+        .filter(|movie| movie.vote_count >= min_vote_count)
+        .max_by(|a, b| a.popularity.partial_cmp(&b.popularity).unwrap_or(std::cmp::Ordering::Equal))
+        .or_else(|| results.iter().find(before_cutoff))
+        .or_else(|| results.first())
+        .map(|movie| movie.id)
+}
+
+// Split a release year off the end of a file or search name, e.g. "Dune (2021)" or
+// "Dune 2021 1080p" -> ("Dune", Some(2021)). The year must follow some title text, so
+// "1917" stays a title; "Blade Runner 2049" is left alone as 2049 isn't a plausible year yet.
+fn split_title_year(name: &str) -> (String, Option<u16>) {
+    let max_year = chrono::Datelike::year(&chrono::Local::now()) + 1;
+    let words: Vec<&str> = name.split_whitespace().collect();
+    for (idx, word) in words.iter().enumerate().rev().filter(|(idx, _)| *idx > 0) {
+        let digits = word.trim_matches(|c: char| "()[]{}".contains(c));
+        if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        let Ok(year) = digits.parse::<u16>() else {
+            continue;
+        };
+        if (1870..=max_year).contains(&(year as i32)) {
+            let title = words[..idx].join(" ")
+                .trim_end_matches(|c: char| c == '(' || c == '[' || c == '-' || c.is_whitespace())
+                .to_string();
+            if !title.is_empty() {
+                return (title, Some(year));
+            }
+        }
+    }
+    (name.trim().to_string(), None)
+}
+
+// Lowercase words that mark the start of a release name's quality/source/codec tags
+const RELEASE_TAGS: &[&str] = &[
+    "4k", "uhd", "hdr", "hdr10", "dv", "10bit", "bluray", "blu-ray", "bdrip", "brrip", "remux",
+    "webrip", "web-rip", "web-dl", "webdl", "hdtv", "hdrip", "dvdrip", "dvdscr", "dvd",
+    "x264", "x265", "h264", "h265", "hevc", "avc", "xvid", "divx", "aac", "ac3", "dts", "atmos",
+    "proper", "repack", "unrated", "extended", "remastered", "imax",
+];
+
+// Resolution tags such as 720p, 1080p or 2160p
+fn is_resolution_tag(word: &str) -> bool {
+    word.strip_suffix('p')
+        .is_some_and(|digits| (3..=4).contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_digit()))
+}
+
+// Turn a video file name (without extension) into a search title and year, e.g.
+// "The.Matrix.1999.1080p.BluRay.x264-GROUP" -> ("The Matrix", Some(1999)). Dots and
+// underscores become spaces, [bracketed] group names go, and everything from the year or
+// the first quality/source/codec tag on (including a "-GROUP" suffix) is dropped.
+fn clean_movie_title(raw: &str) -> (String, Option<u16>) {
+    let mut name = String::new();
+    let mut depth = 0;
+    for c in raw.chars() {
+        match c {
+            '[' => depth += 1,
+            ']' if depth > 0 => depth -= 1,
+            '.' | '_' if depth == 0 => name.push(' '),
+            c if depth == 0 => name.push(c),
+            _ => {}
+        }
+    }
+    
+    let words: Vec<&str> = name.split_whitespace().collect();
+    let tag_start = words.iter()
+        .position(|word| {
+            let word = word.trim_matches(|c: char| "()[]{}".contains(c)).to_lowercase();
+            let tag = word.split('-').next().unwrap_or(&word);
+            RELEASE_TAGS.contains(&word.as_str()) || RELEASE_TAGS.contains(&tag) || is_resolution_tag(tag)
+        })
+        .filter(|idx| *idx > 0)
+        .unwrap_or(words.len());
+    let (title, year) = split_title_year(&words[..tag_start].join(" "));
+    let title = title.trim_end_matches(|c: char| c == '-' || c == '(' || c.is_whitespace()).to_string();
+    if title.is_empty() {
+        return (raw.trim().to_string(), year);
+    }
+    (title, year)
+}
+
+fn tmdb_search_url(api_key: &str, query: &str, year: Option<u16>) -> String {
+    let mut url = format!(
+        "https://api.themoviedb.org/3/search/movie?api_key={}&query={}{}",
+        api_key,
+        urlencoding::encode(query),
+        tmdb_language_param()
+    );
+    if let Some(year) = year {
+        url.push_str(&format!("&year={}", year));
+    }
+    url
+}
+
+fn tmdb_tv_search_url(api_key: &str, query: &str, year: Option<u16>) -> String {
+    let mut url = format!(
+        "https://api.themoviedb.org/3/search/tv?api_key={}&query={}{}",
+        api_key,
+        urlencoding::encode(query),
+        tmdb_language_param()
+    );
+    if let Some(year) = year {
+        url.push_str(&format!("&first_air_date_year={}", year));
+    }
+    url
+}
+
+// Blocking TMDB search for a user-typed query. A trailing year narrows the search, with a
+// second search on the title alone if that finds nothing. Failed requests give no results.
+fn search_tmdb_blocking(api_key: &str, query: &str) -> Vec<TMDBMovie> {
+    let search = |url: String| reqwest::blocking::get(&url).ok()
+        .and_then(|response| response.json::<TMDBSearchResponse>().ok())
+        .map(|response| response.results)
+        .unwrap_or_default();
+    
+    let (title, year) = split_title_year(query);
+    if year.is_some() {
+        let results = search(tmdb_search_url(api_key, &title, year));
+        if !results.is_empty() {
+            return results;
+        }
+    }
+    search(tmdb_search_url(api_key, query, None))
 }
 
 #[derive(Debug, Deserialize)]
@@ -159,6 +1371,136 @@ struct TMDBMovieDetails {
     genres: Vec<TMDBGenre>,
     #[serde(default)]
     credits: TMDBCredits,
+    #[serde(default)]
+    belongs_to_collection: Option<TMDBCollectionRef>,
+    #[serde(default)]
+    videos: TMDBVideos,
+    #[serde(default)]
+    release_dates: TMDBReleaseDates,
+}
+
+// Movie release dates per country, each with its age rating (often blank)
+#[derive(Debug, Deserialize, Default)]
+struct TMDBReleaseDates {
+    #[serde(default)]
+    results: Vec<TMDBCountryReleases>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TMDBCountryReleases {
+    iso_3166_1: String,
+    #[serde(default)]
+    release_dates: Vec<TMDBReleaseDate>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TMDBReleaseDate {
+    #[serde(default)]
+    certification: String,
+}
+
+impl TMDBReleaseDates {
+    // First non-blank rating among the country's releases
+    fn certification(&self, country: &str) -> Option<String> {
+        self.results.iter()
+            .find(|releases| releases.iso_3166_1 == country)?
+            .release_dates.iter()
+            .map(|release| release.certification.trim())
+            .find(|certification| !certification.is_empty())
+            .map(str::to_string)
+    }
+}
+
+// TV series age ratings per country
+#[derive(Debug, Deserialize, Default)]
+struct TMDBContentRatings {
+    #[serde(default)]
+    results: Vec<TMDBContentRating>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TMDBContentRating {
+    iso_3166_1: String,
+    #[serde(default)]
+    rating: String,
+}
+
+impl TMDBContentRatings {
+    fn certification(&self, country: &str) -> Option<String> {
+        self.results.iter()
+            .find(|rating| rating.iso_3166_1 == country && !rating.rating.trim().is_empty())
+            .map(|rating| rating.rating.trim().to_string())
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct TMDBVideos {
+    #[serde(default)]
+    results: Vec<TMDBVideo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TMDBVideo {
+    key: String,
+    #[serde(default)]
+    site: String,
+    #[serde(default, rename = "type")]
+    video_type: String,
+}
+
+impl TMDBVideos {
+    // Key of the first YouTube trailer, or empty when there is none
+    fn youtube_trailer_key(&self) -> String {
+        self.results.iter()
+            .find(|video| video.site == "YouTube" && video.video_type == "Trailer")
+            .map(|video| video.key.clone())
+            .unwrap_or_default()
+    }
+}
+
+fn youtube_watch_url(key: &str) -> String {
+    format!("https://www.youtube.com/watch?v={}", key)
+}
+
+#[derive(Debug, Deserialize)]
+struct TMDBTvDetails {
+    name: String,
+    #[serde(default)]
+    first_air_date: String,
+    #[serde(default)]
+    overview: String,
+    #[serde(default)]
+    vote_average: f32,
+    #[serde(default)]
+    poster_path: Option<String>,
+    #[serde(default)]
+    episode_run_time: Vec<u16>,
+    #[serde(default)]
+    genres: Vec<TMDBGenre>,
+    #[serde(default)]
+    credits: TMDBCredits,
+    #[serde(default)]
+    created_by: Vec<TMDBCreator>,
+    #[serde(default)]
+    number_of_seasons: u16,
+    #[serde(default)]
+    number_of_episodes: u16,
+    #[serde(default)]
+    videos: TMDBVideos,
+    #[serde(default)]
+    content_ratings: TMDBContentRatings,
+}
+
+#[derive(Debug, Deserialize)]
+struct TMDBCreator {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TMDBCollectionRef {
+    id: u32,
+    #[serde(default)]
+    name: String,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -187,6 +1529,23 @@ struct TMDBCast {
 struct TMDBCrew {
     name: String,
     job: String,
+    #[serde(default)]
+    department: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TMDBCollection {
+    name: String,
+    #[serde(default)]
+    parts: Vec<TMDBCollectionPart>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TMDBCollectionPart {
+    id: u32,
+    title: String,
+    #[serde(default)]
+    release_date: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -203,6 +1562,7 @@ struct MovieDatabase {
     data_file: String,
     #[serde(skip)]  // Don't serialize posters directory path
     posters_dir: String,
+    #[serde(skip)]  // From config.json, the environment or --api-key; never written to the database
     tmdb_api_key: String,
     #[serde(default)]
     tmdb_cache: HashMap<String, CachedTMDBSearch>,  // search_query -> cached results
@@ -210,12 +1570,157 @@ struct MovieDatabase {
     poster_cache: Rc<RefCell<HashMap<u32, Pixbuf>>>,  // movie_id -> cached pixbuf
     #[serde(skip)]  // Cache for search/filter/sort results
     result_cache: RefCell<HashMap<String, Vec<Movie>>>,  // cache_key -> filtered/sorted movies
+    #[serde(skip)]  // Format written by save_to_file, from Config::storage_format
+    storage_format: String,
+    #[serde(skip)]  // Open SQLite file when storage_format is "sqlite"
+    sqlite: RefCell<Option<SqliteStore>>,
+    #[serde(skip)]  // The last movie removed with delete_movie, for a single level of undo
+    last_deleted: Option<Movie>,
+    #[serde(skip)]  // Records the last load couldn't read, copied to "<data_file>.rejected"
+    rejected_records: usize,
+    #[serde(skip)]  // Why the data file couldn't be opened; saving is refused while set
+    load_error: Option<String>,
+    #[serde(skip)]  // Movies added, changed or removed since the last SQLite save
+    changed_ids: RefCell<HashSet<u32>>,
+}
+
+// First bytes of every SQLite database file
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+// Write to "<path>.tmp" next to the target and rename it over the real file, so a crash
+// mid-write leaves the previous contents intact rather than a truncated file
+fn write_file_atomically(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut tmp_name = path.as_os_str().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_name);
+    
+    let result = File::create(&tmp_path).and_then(|mut file| {
+        std::io::Write::write_all(&mut file, contents)?;
+        file.sync_all()
+    });
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+    std::fs::rename(&tmp_path, path)
+}
+
+// Prefix an io::Error with what was being done, keeping its kind
+fn io_context(context: String) -> impl FnOnce(std::io::Error) -> std::io::Error {
+    move |e| std::io::Error::new(e.kind(), format!("{}: {}", context, e))
+}
+
+// Save the settings from a backup in place of config.json, keeping this machine's API key
+fn restore_backup_settings(mut config: Config) -> std::io::Result<()> {
+    if let Some(current) = load_config() {
+        config.tmdb_api_key = current.tmdb_api_key;
+    }
+    save_config(&config).map_err(io_context("Failed to restore settings".to_string()))
+}
+
+fn is_sqlite_file(path: &str) -> bool {
+    let mut header = [0u8; 16];
+    File::open(path).and_then(|mut f| f.read_exact(&mut header)).is_ok() && header == SQLITE_HEADER
+}
+
+// Which of the STORAGE_FORMATS a data file is written in, or None if it can't be read
+fn stored_format(path: &str) -> Option<&'static str> {
+    if is_sqlite_file(path) {
+        return Some("sqlite");
+    }
+    let contents = std::fs::read_to_string(path).ok()?;
+    match serde_json::from_str::<serde_json::Value>(&contents) {
+        Ok(serde_json::Value::Object(root)) if root.contains_key("movies") => Some("database"),
+        Ok(serde_json::Value::Array(_)) => Some("array"),
+        _ if contents.trim_start().starts_with('{') => Some("lines"),
+        _ => None,
+    }
+}
+
+// SQLite-backed movies.db. Movies are stored one JSON row each, and next_id, the API key
+// and the TMDB cache in a key/value table. `rows` and `meta` mirror what is on disk so a
+// save only touches the rows that changed.
+struct SqliteStore {
+    conn: rusqlite::Connection,
+    rows: HashMap<u32, String>,  // Movie rows as read by open; emptied once loaded or synced
+    meta: HashMap<String, String>,
+}
+
+impl SqliteStore {
+    fn open(path: &str) -> Result<Self, String> {
+        let conn = rusqlite::Connection::open(path)
+            .map_err(|e| format!("Failed to open {}: {}", path, e))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS movies (id INTEGER PRIMARY KEY, data TEXT NOT NULL);
+             CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);",
+        ).map_err(|e| format!("Failed to set up {}: {}", path, e))?;
+        // Older versions kept the API key here; it belongs in config.json or the environment
+        conn.execute("DELETE FROM meta WHERE key = 'tmdb_api_key'", [])
+            .map_err(|e| format!("Failed to set up {}: {}", path, e))?;
+        
+        let sql_err = |e: rusqlite::Error| format!("Failed to read {}: {}", path, e);
+        let rows: HashMap<u32, String> = conn.prepare("SELECT id, data FROM movies")
+            .and_then(|mut stmt| stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?.collect())
+            .map_err(sql_err)?;
+        let meta: HashMap<String, String> = conn.prepare("SELECT key, value FROM meta")
+            .and_then(|mut stmt| stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?.collect())
+            .map_err(sql_err)?;
+        
+        Ok(SqliteStore { conn, rows, meta })
+    }
+    
+    // Write the given movie rows (None deletes the row) and the meta values that differ
+    // from what is on disk, in one transaction
+    fn sync(&mut self, movies: Vec<(u32, Option<String>)>, meta: HashMap<String, String>) -> std::io::Result<()> {
+        let tx = self.conn.transaction().map_err(sqlite_save_error)?;
+        for (id, data) in &movies {
+            match data {
+                Some(data) => tx.execute("INSERT OR REPLACE INTO movies (id, data) VALUES (?1, ?2)", rusqlite::params![id, data]),
+                None => tx.execute("DELETE FROM movies WHERE id = ?1", [id]),
+            }.map_err(sqlite_save_error)?;
+        }
+        for (key, value) in &meta {
+            if self.meta.get(key) != Some(value) {
+                tx.execute("INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)", [key, value])
+                    .map_err(sqlite_save_error)?;
+            }
+        }
+        tx.commit().map_err(sqlite_save_error)?;
+        self.rows.clear();
+        self.meta = meta;
+        Ok(())
+    }
+}
+
+// A failed SQLite write as an io::Error, with the kind of the underlying problem (disk full,
+// read-only file) so a save failure is reported the same way for every storage format
+fn sqlite_save_error(e: rusqlite::Error) -> std::io::Error {
+    use rusqlite::ErrorCode;
+    let kind = match e.sqlite_error_code() {
+        Some(ErrorCode::DiskFull) => std::io::ErrorKind::StorageFull,
+        Some(ErrorCode::ReadOnly | ErrorCode::PermissionDenied | ErrorCode::CannotOpen) => std::io::ErrorKind::PermissionDenied,
+        Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked) => std::io::ErrorKind::ResourceBusy,
+        _ => std::io::ErrorKind::Other,
+    };
+    std::io::Error::new(kind, format!("Failed to save database: {}", e))
+}
+
+// One result in the "Wrong Movie?" and "Add Movie" dialogs. Older caches stored these as
+// (tmdb_id, title, year, rating) arrays, which still deserialize with an empty overview.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TMDBSearchRow {
+    tmdb_id: u32,
+    title: String,
+    year: String,
+    rating: f32,
+    #[serde(default)]
+    overview: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct CachedTMDBSearch {
     query: String,
-    results: Vec<(u32, String, String, f32)>,  // (tmdb_id, title, year, rating)
+    results: Vec<TMDBSearchRow>,
     timestamp: u64,  // Unix timestamp
 }
 
@@ -231,7 +1736,8 @@ impl CachedTMDBSearch {
     }
 }
 
-fn download_poster(poster_url: &str, movie_id: u32, posters_dir: &str) -> Option<String> {
+// `poster_key` names the file; TV series use "tv_{id}" as their ids overlap with movie ids
+fn download_poster(poster_url: &str, poster_key: impl std::fmt::Display, posters_dir: &str) -> Option<String> {
     if poster_url.is_empty() {
         return None;
     }
@@ -244,121 +1750,327 @@ fn download_poster(poster_url: &str, movie_id: u32, posters_dir: &str) -> Option
     let bytes = response.bytes().ok()?;
     
     // Save to local file
-    let poster_path = format!("{}/poster_{}.jpg", posters_dir, movie_id);
+    let poster_path = format!("{}/poster_{}.jpg", posters_dir, poster_key);
     let mut file = File::create(&poster_path).ok()?;
     std::io::copy(&mut bytes.as_ref(), &mut file).ok()?;
     
     Some(poster_path)
 }
 
-async fn download_cast_photo_async(photo_url: &str, cast_photos_dir: &str, client: &reqwest::Client) -> Option<String> {
+// Cache file name for a cast photo: a hash of its URL (FNV-1a, so it stays stable across builds)
+fn cast_photo_filename(photo_url: &str) -> String {
+    let hash = photo_url.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}.jpg", hash)
+}
+
+// Cast photos are cached next to the posters directory
+fn cast_photos_dir(posters_dir: &str) -> PathBuf {
+    Path::new(posters_dir).parent().unwrap_or(Path::new(".")).join("cast_photos")
+}
+
+// Cached path of a cast photo, downloading it first if it isn't on disk yet (blocking)
+fn download_cast_photo(client: &reqwest::blocking::Client, photo_url: &str, cast_photos_dir: &Path) -> Option<PathBuf> {
     if photo_url.is_empty() {
         return None;
     }
-    
-    let cast_photos_dir = cast_photos_dir.to_string();
-    let photo_url = photo_url.to_string();
-    
-    // Extract filename from URL path
-    let filename = photo_url
-        .trim_start_matches("https://image.tmdb.org/t/p/w185")
-        .trim_start_matches('/')
-        .replace('/', "_");
-    
-    let photo_path = format!("{}/{}", cast_photos_dir, filename);
-    
-    // Check if already downloaded (blocking operation in spawn_blocking)
-    let photo_path_check = photo_path.clone();
-    let exists = tokio::task::spawn_blocking(move || {
-        Path::new(&photo_path_check).exists()
-    }).await.ok()?;
-    
-    if exists {
+    let photo_path = cast_photos_dir.join(cast_photo_filename(photo_url));
+    if photo_path.exists() {
         return Some(photo_path);
     }
     
-    // Create directory if needed (blocking operation)
-    let cast_photos_dir_clone = cast_photos_dir.clone();
-    tokio::task::spawn_blocking(move || {
-        create_dir_all(&cast_photos_dir_clone)
-    }).await.ok()?.ok()?;
-    
-    // Download the photo
-    let response = client.get(&photo_url).send().await.ok()?;
-    let bytes = response.bytes().await.ok()?;
-    
-    // Save to local file (blocking operation in spawn_blocking)
-    let photo_path_write = photo_path.clone();
-    let bytes_vec = bytes.to_vec();
-    tokio::task::spawn_blocking(move || {
-        std::fs::write(&photo_path_write, bytes_vec)
-    }).await.ok()?.ok()?;
-    
+    create_dir_all(cast_photos_dir).ok()?;
+    let bytes = client.get(photo_url).send().ok()?.error_for_status().ok()?.bytes().ok()?;
+    std::fs::write(&photo_path, bytes).ok()?;
     Some(photo_path)
 }
 
-// Async function to fetch metadata for a single movie (non-blocking)
-async fn fetch_movie_metadata_async(
-    client: &reqwest::Client,
-    api_key: &str,
-    title: &str,
-    file_path: String,
-    posters_dir: String,
-    year_cutoff: i32,
-) -> Option<Movie> {
-    let search_url = format!(
-        "https://api.themoviedb.org/3/search/movie?api_key={}&query={}",
-        api_key,
-        urlencoding::encode(title)
-    );
-    
-    let search_response = client
-        .get(&search_url)
-        .send()
-        .await
-        .ok()?
-        .json::<TMDBSearchResponse>()
-        .await
-        .ok()?;
-    
-    if search_response.results.is_empty() {
-        return None;
+// Lowercase letters and digits only, single-spaced, for loose title comparisons
+fn normalize_title(title: &str) -> String {
+    title.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Movies saved before the watched flag existed count as watched if they have a watch history
+fn apply_legacy_watched(movie: &mut Movie, record: &serde_json::Value) {
+    if record.get("watched").is_none() {
+        movie.watched = !movie.watch_log.is_empty();
     }
-    
-    // Prioritize movies before year_cutoff (filter by release_date)
-    let movie_id = {
-        let before_cutoff = search_response.results.iter().find(|movie| {
-            if let Some(ref date) = movie.release_date {
-                if let Some(year_str) = date.split('-').next() {
-                    if let Ok(year) = year_str.parse::<i32>() {
-                        return year <= year_cutoff;
-                    }
+}
+
+fn movie_from_record(record: serde_json::Value) -> serde_json::Result<Movie> {
+    let mut movie: Movie = serde_json::from_value(record.clone())?;
+    apply_legacy_watched(&mut movie, &record);
+    Ok(movie)
+}
+
+// Combine freshly fetched TMDB metadata with the stored movie. The user's own data
+// (favorite, watched flag, personal rating, watch history and play count, tags, date added) is kept, and values TMDB doesn't have (no runtime, no
+// poster) leave the existing ones in place instead of blanking them. Fields changed in Edit
+// Metadata are kept too, unless the refresh matched a different TMDB movie.
+fn merge_refreshed_movie(old: &Movie, mut refreshed: Movie) -> Movie {
+    refreshed.id = old.id;
+    if refreshed.tmdb_id == old.tmdb_id {
+        for field in &old.edited_fields {
+            match field.as_str() {
+                "title" => refreshed.title = old.title.clone(),
+                "year" => refreshed.year = old.year,
+                "director" => refreshed.director = old.director.clone(),
+                "genre" => refreshed.genre = old.genre.clone(),
+                "rating" => refreshed.rating = old.rating,
+                "runtime" => refreshed.runtime = old.runtime,
+                "description" => refreshed.description = old.description.clone(),
+                "cast" => {
+                    refreshed.cast = old.cast.clone();
+                    refreshed.cast_details = old.cast_details.clone();
                 }
+                _ => {}
             }
-            false
-        });
-        
-        if let Some(movie) = before_cutoff {
-            movie.id
-        } else {
-            search_response.results[0].id
         }
-    };
+        refreshed.edited_fields = old.edited_fields.clone();
+    }
+    refreshed.favorite = old.favorite;
+    refreshed.watched = old.watched;
+    refreshed.my_rating = old.my_rating;
+    refreshed.watch_log = old.watch_log.clone();
+    refreshed.tags = old.tags.clone();
+    refreshed.date_added = old.date_added;
+    refreshed.watch_count = old.watch_count;
+    refreshed.last_watched = old.last_watched;
+    // Refreshes look up the primary file; the other copies stay attached
+    if refreshed.primary_file() == old.primary_file() {
+        refreshed.file_paths = old.file_paths.clone();
+        refreshed.file_size = old.file_size;
+        refreshed.subtitles = old.subtitles.clone();
+        refreshed.resolution = old.resolution.clone();
+        refreshed.video_codec = old.video_codec.clone();
+        refreshed.audio_codec = old.audio_codec.clone();
+        refreshed.file_runtime_secs = old.file_runtime_secs;
+    }
     
-    let details_url = format!(
-        "https://api.themoviedb.org/3/movie/{}?api_key={}&append_to_response=credits",
-        movie_id, api_key
-    );
+    if refreshed.runtime == 0 {
+        refreshed.runtime = old.runtime;
+    }
+    if refreshed.certification.is_empty() {
+        refreshed.certification = old.certification.clone();
+    }
+    if refreshed.poster_path.is_empty() && !old.poster_path.is_empty() {
+        refreshed.poster_url = old.poster_url.clone();
+        refreshed.poster_path = old.poster_path.clone();
+    }
+    refreshed
+}
+
+// Names of crew members with any of the given jobs, in credit order without duplicates
+fn crew_names(crew: &[TMDBCrew], jobs: &[&str]) -> String {
+    let mut names: Vec<&str> = Vec::new();
+    for member in crew.iter().filter(|c| jobs.contains(&c.job.as_str())) {
+        if !names.contains(&member.name.as_str()) {
+            names.push(&member.name);
+        }
+    }
+    names.join(", ")
+}
+
+// Personal rating for sorting, with unrated movies below any score
+fn my_rating_key(movie: &Movie) -> f32 {
+    movie.my_rating.unwrap_or(-1.0)
+}
+
+fn my_rating_display(movie: &Movie) -> String {
+    match movie.my_rating {
+        Some(rating) => format!("⭐ {:.1}/10", rating),
+        None => String::from("Not rated"),
+    }
+}
+
+// "Director" for films; a series' creators are stored in the director field
+fn director_heading(movie: &Movie) -> &'static str {
+    match movie.media_type {
+        MediaType::Movie => "Director",
+        MediaType::Tv => "Created By",
+    }
+}
+
+fn runtime_heading(movie: &Movie) -> &'static str {
+    match movie.media_type {
+        MediaType::Movie => "Runtime",
+        MediaType::Tv => "Episode Runtime",
+    }
+}
+
+// Length shown in list rows: the runtime of a film, the seasons and episodes of a series
+fn length_summary(movie: &Movie) -> String {
+    match movie.media_type {
+        MediaType::Movie => format!("{} min", movie.runtime),
+        MediaType::Tv => format!("📺 {} seasons, {} episodes", movie.seasons, movie.episodes),
+    }
+}
+
+// First air date, season and episode lines for a series' details; empty for films
+fn series_info_markup(movie: &Movie) -> String {
+    if movie.media_type != MediaType::Tv {
+        return String::new();
+    }
+    format!(
+        "<b>First Aired:</b> {}\n<b>Seasons:</b> {}\n<b>Episodes:</b> {}\n",
+        if movie.first_air_date.is_empty() { "Unknown" } else { movie.first_air_date.as_str() },
+        movie.seasons,
+        movie.episodes
+    )
+}
+
+// Actors listed under "Starring" in the details pane
+const STARRING_LIMIT: usize = 5;
+
+// Markup for the details pane next to the movie list
+fn details_pane_markup(movie: &Movie) -> String {
+    let escaped_title = escape_markup(&movie.title);
+    let escaped_director = escape_markup(&movie.director);
+    let escaped_genre = escape_markup(&movie.genre.join(", "));
+    let escaped_description = escape_markup(&movie.description);
+    let escaped_file = escape_markup(&movie.file_paths.join("\n"));
+    
+    // Crew lines are only shown when known
+    let mut crew_display = series_info_markup(movie);
+    if !movie.writer.is_empty() {
+        crew_display.push_str(&format!("<b>Writer:</b> {}\n", escape_markup(&movie.writer)));
+    }
+    if !movie.composer.is_empty() {
+        crew_display.push_str(&format!("<b>Composer:</b> {}\n", escape_markup(&movie.composer)));
+    }
+    if !movie.collection_name.is_empty() {
+        crew_display.push_str(&format!("<b>Collection:</b> {}\n", escape_markup(&movie.collection_name)));
+    }
+    if !movie.certification.is_empty() {
+        crew_display.push_str(&format!("<b>Rated:</b> {}\n", escape_markup(&movie.certification)));
+    }
     
-    let details = client
-        .get(&details_url)
-        .send()
-        .await
-        .ok()?
-        .json::<TMDBMovieDetails>()
-        .await
-        .ok()?;
+    // Format cast members, with character names when available
+    // Top-billed actors only; Show Cast has everyone stored
+    let cast_count = movie.cast_details.len().max(movie.cast.len());
+    let mut cast_display = if !movie.cast_details.is_empty() {
+        let cast_list: Vec<String> = movie.cast_details.iter()
+            .take(STARRING_LIMIT)
+            .map(|cm| format!("{} ({})", escape_markup(&cm.name), escape_markup(&cm.character)))
+            .collect();
+        cast_list.join("\n    • ")
+    } else if !movie.cast.is_empty() {
+        let cast_list: Vec<String> = movie.cast.iter()
+            .take(STARRING_LIMIT)
+            .map(|name| escape_markup(name))
+            .collect();
+        cast_list.join("\n    • ")
+    } else {
+        String::from("Unknown")
+    };
+    if cast_count > STARRING_LIMIT {
+        cast_display.push_str(&format!("\n    … and {} more", cast_count - STARRING_LIMIT));
+    }
     
+    format!(
+        "<b>{}</b> ({})\n\n\
+        <b>{}:</b> {}\n\
+        {}\
+        <b>Genre:</b> {}\n\
+        <b>TMDB Rating:</b> ⭐ {:.1}/10\n\
+        <b>My Rating:</b> {}\n\
+        {}\
+        <b>{}:</b> {} minutes\n\n\
+        <b>Starring:</b>\n    • {}\n\n\
+        <b>Description:</b>\n{}\n\n\
+        <b>File:</b> {}\n\
+        <b>TMDB ID:</b> {}\n\
+        <b>IMDb ID:</b> {}{}",
+        escaped_title, movie.year, director_heading(movie), escaped_director, crew_display,
+        escaped_genre, movie.rating, my_rating_display(movie), tags_markup(movie), runtime_heading(movie), movie.runtime,
+        cast_display, escaped_description, escaped_file,
+        tmdb_link_markup(movie), imdb_link_markup(movie), file_info_markup(movie)
+    )
+}
+
+// Tags line with each tag as a small chip; empty for untagged movies
+fn tags_markup(movie: &Movie) -> String {
+    if movie.tags.is_empty() {
+        return String::new();
+    }
+    let chips: Vec<String> = movie.tags.iter()
+        .map(|tag| format!("<span size='small' background='#3584e4' foreground='white'> {} </span>", escape_markup(tag)))
+        .collect();
+    format!("<b>Tags:</b> {}\n", chips.join(" "))
+}
+
+// Tags typed as a comma-separated list: trimmed, blanks dropped, no case-insensitive repeats
+fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in text.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+// "File Info" section with what ffprobe found; empty if the file was never probed
+fn file_info_markup(movie: &Movie) -> String {
+    if movie.resolution.is_empty() && movie.video_codec.is_empty() && movie.file_runtime_secs == 0 {
+        return String::new();
+    }
+    let mut lines = vec![String::from("\n\n<b>File Info:</b>")];
+    if !movie.resolution.is_empty() {
+        lines.push(format!("    Resolution: {}", escape_markup(&movie.resolution)));
+    }
+    if !movie.video_codec.is_empty() || !movie.audio_codec.is_empty() {
+        let or_unknown = |codec: &str| if codec.is_empty() { String::from("unknown") } else { escape_markup(codec) };
+        lines.push(format!("    Codecs: {} video, {} audio", or_unknown(&movie.video_codec), or_unknown(&movie.audio_codec)));
+    }
+    if movie.file_runtime_secs > 0 {
+        let secs = movie.file_runtime_secs;
+        lines.push(format!("    Duration: {}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60));
+    }
+    lines.join("\n")
+}
+
+// Pango link markup; labels showing it should call open_links_externally
+fn link_markup(url: &str, text: &str) -> String {
+    format!("<a href=\"{}\">{}</a>", escape_markup(url), escape_markup(text))
+}
+
+fn imdb_link_markup(movie: &Movie) -> String {
+    if movie.imdb_id.is_empty() {
+        return String::from("Not available");
+    }
+    link_markup(&format!("https://www.imdb.com/title/{}", movie.imdb_id), &movie.imdb_id)
+}
+
+fn tmdb_link_markup(movie: &Movie) -> String {
+    if movie.tmdb_id == 0 && movie.source == "omdb" {
+        return String::from("Not available (details from OMDb)");
+    }
+    if movie.tmdb_id == 0 {
+        return String::from("Not available");
+    }
+    let url = format!("https://www.themoviedb.org/{}/{}", movie.media_type.tmdb_path(), movie.tmdb_id);
+    link_markup(&url, &movie.tmdb_id.to_string())
+}
+
+// Open <a href> links in a label with the desktop's default handler (the browser)
+fn open_links_externally(label: &Label) {
+    label.connect_activate_link(|_, uri| {
+        if let Err(e) = gtk::gio::AppInfo::launch_default_for_uri(uri, None::<&gtk::gio::AppLaunchContext>) {
+            log_warn!("Warning: Failed to open {}: {}", uri, e);
+        }
+        glib::Propagation::Stop
+    });
+}
+
+// Build a Movie from TMDB details (fetched with MediaType::details_append).
+// poster_path and imdb_id are left empty for the caller to fill in after downloading.
+fn movie_from_tmdb_details(details: TMDBMovieDetails, tmdb_id: u32, file_path: String) -> Movie {
     let year: u16 = details.release_date
         .split('-')
         .next()
@@ -371,42 +2083,36 @@ async fn fetch_movie_metadata_async(
         .map(|c| c.name.clone())
         .unwrap_or_else(|| "Unknown".to_string());
     
+    let writer = crew_names(&details.credits.crew, &["Writer", "Screenplay"]);
+    let composer = crew_names(&details.credits.crew, &["Original Music Composer"]);
+    let crew: Vec<CrewMember> = details.credits.crew.iter()
+        .map(|c| CrewMember { name: c.name.clone(), job: c.job.clone(), department: c.department.clone() })
+        .collect();
+    
+    // Billing order, up to the configured number of actors (0 = the whole cast)
+    let max_cast = match load_config().unwrap_or_default().max_cast_members {
+        0 => usize::MAX,
+        max => max,
+    };
     let cast: Vec<String> = details.credits.cast
         .iter()
-        .take(5)
+        .take(max_cast)
         .map(|c| c.name.clone())
         .collect();
     
     let cast_details: Vec<CastMember> = details.credits.cast
         .iter()
-        .take(5)
+        .take(max_cast)
         .map(|c| CastMember {
             name: c.name.clone(),
             character: c.character.clone(),
             profile_path: c.profile_path.as_ref()
                 .map(|p| format!("https://image.tmdb.org/t/p/w185{}", p))
                 .unwrap_or_default(),
+            local_photo_path: String::new(),
         })
         .collect();
     
-    // Download cast photos asynchronously
-    let cast_photos_dir = Path::new(&posters_dir).parent().unwrap().join("cast_photos");
-    let cast_photos_dir_str = cast_photos_dir.to_string_lossy().to_string();
-    eprintln!("Cast photos directory: {}", cast_photos_dir_str);
-    eprintln!("Found {} cast members with details", cast_details.len());
-    for cast_member in &cast_details {
-        if !cast_member.profile_path.is_empty() {
-            eprintln!("Downloading photo for: {} from {}", cast_member.name, cast_member.profile_path);
-            if let Some(path) = download_cast_photo_async(&cast_member.profile_path, &cast_photos_dir_str, client).await {
-                eprintln!("  ✓ Saved to: {}", path);
-            } else {
-                eprintln!("  ✗ Failed to download");
-            }
-        } else {
-            eprintln!("No photo URL for: {}", cast_member.name);
-        }
-    }
-    
     let genres: Vec<String> = details.genres
         .iter()
         .map(|g| g.name.clone())
@@ -416,29 +2122,13 @@ async fn fetch_movie_metadata_async(
         .map(|p| format!("https://image.tmdb.org/t/p/original{}", p))
         .unwrap_or_default();
     
-    let poster_path = if !poster_url.is_empty() {
-        download_poster(&poster_url, movie_id, &posters_dir).unwrap_or_default()
-    } else {
-        String::new()
-    };
-    
-    // Fetch IMDb ID from external_ids endpoint
-    let external_ids_url = format!(
-        "https://api.themoviedb.org/3/movie/{}/external_ids?api_key={}",
-        movie_id, api_key
-    );
-    
-    let imdb_id = if let Ok(response) = client.get(&external_ids_url).send().await {
-        if let Ok(external_ids) = response.json::<TMDBExternalIds>().await {
-            external_ids.imdb_id.unwrap_or_default()
-        } else {
-            String::new()
-        }
-    } else {
-        String::new()
-    };
+    let (collection_id, collection_name) = details.belongs_to_collection
+        .map(|c| (c.id, c.name))
+        .unwrap_or_default();
+    let trailer_youtube_key = details.videos.youtube_trailer_key();
+    let certification = regional_certification(|country| details.release_dates.certification(country));
     
-    Some(Movie {
+    Movie {
         id: 0,
         title: details.title,
         year,
@@ -449,843 +2139,4444 @@ async fn fetch_movie_metadata_async(
         description: details.overview,
         cast,
         cast_details,
-        file_path,
+        file_paths: file_paths_from(file_path),
         poster_url,
-        tmdb_id: movie_id,
-        imdb_id,
-        poster_path,
+        tmdb_id,
+        imdb_id: String::new(),
+        poster_path: String::new(),
         watch_log: Vec::new(),
-    })
+        favorite: false,
+        watched: false,
+        my_rating: None,
+        edited_fields: Vec::new(),
+        writer,
+        composer,
+        collection_id,
+        collection_name,
+        certification,
+        file_size: 0,
+        subtitles: Vec::new(),
+        tags: Vec::new(),
+        resolution: String::new(),
+        video_codec: String::new(),
+        audio_codec: String::new(),
+        file_runtime_secs: 0,
+        crew,
+        date_added: 0,
+        watch_count: 0,
+        last_watched: None,
+        media_type: MediaType::Movie,
+        seasons: 0,
+        episodes: 0,
+        first_air_date: String::new(),
+        trailer_youtube_key,
+        source: String::new(),
+    }
 }
 
-impl MovieDatabase {
-    fn new(data_file: &str, posters_dir: &str, api_key: &str) -> Self {
-        let mut db = MovieDatabase {
-            movies: HashMap::new(),
-            next_id: 1,
-            data_file: data_file.to_string(),
-            posters_dir: posters_dir.to_string(),
-            tmdb_api_key: api_key.to_string(),
-            tmdb_cache: HashMap::new(),
-            poster_cache: Rc::new(RefCell::new(HashMap::new())),
-            result_cache: RefCell::new(HashMap::new()),
-        };
-        db.load_from_file();
-        db
+// Build a TV series entry from TMDB /tv details (with credits). Goes through the movie
+// conversion for genres, cast and poster; the series' creators stand in for the director.
+fn movie_from_tmdb_tv_details(details: TMDBTvDetails, tmdb_id: u32, file_path: String) -> Movie {
+    let creators: Vec<String> = details.created_by.iter().map(|c| c.name.clone()).collect();
+    let first_air_date = details.first_air_date.clone();
+    let (seasons, episodes) = (details.number_of_seasons, details.number_of_episodes);
+    let certification = regional_certification(|country| details.content_ratings.certification(country));
+    let as_movie = TMDBMovieDetails {
+        title: details.name,
+        release_date: details.first_air_date,
+        overview: details.overview,
+        vote_average: details.vote_average,
+        poster_path: details.poster_path,
+        runtime: details.episode_run_time.first().copied(),
+        genres: details.genres,
+        credits: details.credits,
+        belongs_to_collection: None,
+        videos: details.videos,
+        release_dates: TMDBReleaseDates::default(),
+    };
+    let mut movie = movie_from_tmdb_details(as_movie, tmdb_id, file_path);
+    if !creators.is_empty() {
+        movie.director = creators.join(", ");
     }
+    movie.media_type = MediaType::Tv;
+    movie.seasons = seasons;
+    movie.episodes = episodes;
+    movie.first_air_date = first_air_date;
+    movie.certification = certification;
+    movie
+}
 
-    fn add_movie(&mut self, mut movie: Movie) {
-        movie.id = self.next_id;
-        self.movies.insert(self.next_id, movie);
-        self.next_id += 1;
-        self.invalidate_result_cache();
-        if let Err(e) = self.save_to_file() {
-            eprintln!("Warning: Failed to save database after adding movie: {}", e);
+// Crew grouped by department (in the order departments first appear), each line
+// "Name (Job, Job)" with a person's jobs in a department merged
+fn crew_by_department(crew: &[CrewMember]) -> Vec<(String, Vec<String>)> {
+    let department_of = |member: &CrewMember| {
+        if member.department.is_empty() { "Other".to_string() } else { member.department.clone() }
+    };
+    let mut departments: Vec<String> = Vec::new();
+    for member in crew {
+        let department = department_of(member);
+        if !departments.contains(&department) {
+            departments.push(department);
         }
     }
+    
+    departments.into_iter()
+        .map(|department| {
+            let mut people: Vec<(&str, Vec<&str>)> = Vec::new();
+            for member in crew.iter().filter(|m| department_of(m) == department) {
+                match people.iter_mut().find(|(name, _)| *name == member.name) {
+                    Some((_, jobs)) if !jobs.contains(&member.job.as_str()) => jobs.push(&member.job),
+                    Some(_) => {}
+                    None => people.push((&member.name, vec![&member.job])),
+                }
+            }
+            let lines = people.into_iter()
+                .map(|(name, jobs)| format!("{} ({})", name, jobs.join(", ")))
+                .collect();
+            (department, lines)
+        })
+        .collect()
+}
 
-    fn search_by_title(&self, query: &str) -> Vec<Movie> {
-        let query_lower = query.to_lowercase();
-        self.movies
-            .values()
-            .filter(|m| m.title.to_lowercase().contains(&query_lower))
-            .cloned()
-            .collect()
-    }
+// Crew grouped by department as label markup, one bulleted line per person
+fn crew_markup(crew: &[CrewMember]) -> String {
+    crew_by_department(crew).iter()
+        .map(|(department, people)| format!(
+            "<b>{}</b>\n    • {}",
+            escape_markup(department),
+            people.iter().map(|p| escape_markup(p)).collect::<Vec<_>>().join("\n    • ")
+        ))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
 
-    fn search_by_genre(&self, genre: &str) -> Vec<Movie> {
-        if genre.is_empty() || genre == "All" {
-            return self.list_all();
-        }
-        let genre_lower = genre.to_lowercase();
-        self.movies
-            .values()
-            .filter(|m| m.genre.iter().any(|g| g.to_lowercase().contains(&genre_lower)))
-            .cloned()
-            .collect()
-    }
+// Show a movie's crew in the details pane's expander, hiding it when there is none
+fn show_crew_in_pane(crew_expander: &gtk::Expander, crew_label: &Label, movie: Option<&Movie>) {
+    let crew = movie.map_or(&[][..], |m| m.crew.as_slice());
+    crew_expander.set_visible(!crew.is_empty());
+    crew_expander.set_label(Some(&format!("Crew ({})", crew.len())));
+    crew_label.set_markup(&crew_markup(crew));
+}
 
-    fn delete_movie(&mut self, id: u32) -> bool {
-        if self.movies.remove(&id).is_some() {
-            self.invalidate_result_cache();
-            if let Err(e) = self.save_to_file() {
-                eprintln!("Warning: Failed to save database after deleting movie: {}", e);
-                // Still return true - movie was deleted from memory
-            }
-            true
-        } else {
-            false
+// Fetch the IMDb ID from TMDB's external_ids endpoint (blocking)
+fn fetch_imdb_id(client: &reqwest::blocking::Client, tmdb_id: u32, api_key: &str) -> String {
+    let external_ids_url = format!(
+        "https://api.themoviedb.org/3/movie/{}/external_ids?api_key={}",
+        tmdb_id, api_key
+    );
+    
+    client.get(&external_ids_url).send().ok()
+        .and_then(|response| response.json::<TMDBExternalIds>().ok())
+        .and_then(|external_ids| external_ids.imdb_id)
+        .unwrap_or_default()
+}
+
+// Download any cast photos not already cached, recording where each was saved (blocking)
+fn download_cast_photos(client: &reqwest::blocking::Client, cast_details: &mut [CastMember], posters_dir: &str) {
+    let cast_photos_dir = cast_photos_dir(posters_dir);
+    for cast_member in cast_details {
+        if let Some(path) = download_cast_photo(client, &cast_member.profile_path, &cast_photos_dir) {
+            cast_member.local_photo_path = path.to_string_lossy().to_string();
         }
     }
+}
 
-    fn save_to_file(&self) -> Result<(), String> {
-        // Serialize entire database to JSON (including cache)
-        let json = serde_json::to_string_pretty(&self)
-            .map_err(|e| format!("Failed to serialize database: {}", e))?;
-        
-        std::fs::write(&self.data_file, json)
-            .map_err(|e| format!("Failed to write to file {}: {}", self.data_file, e))?;
-        
-        Ok(())
+// Fetch full metadata for a known TMDB id, downloading its poster and cast photos (blocking)
+fn fetch_movie_by_tmdb_id(
+    client: &reqwest::blocking::Client,
+    tmdb_id: u32,
+    api_key: &str,
+    file_path: String,
+    posters_dir: &str,
+) -> Option<Movie> {
+    let details_url = format!(
+        "https://api.themoviedb.org/3/movie/{}?api_key={}&append_to_response={}{}",
+        tmdb_id, api_key, MediaType::Movie.details_append(),
+        tmdb_language_param()
+    );
+    
+    let details = client.get(&details_url).send().ok()?.json::<TMDBMovieDetails>().ok()?;
+    let mut movie = movie_from_tmdb_details(details, tmdb_id, file_path);
+    
+    download_cast_photos(client, &mut movie.cast_details, posters_dir);
+    
+    if !movie.poster_url.is_empty() {
+        movie.poster_path = download_poster(&movie.poster_url, tmdb_id, posters_dir).unwrap_or_default();
     }
+    movie.imdb_id = fetch_imdb_id(client, tmdb_id, api_key);
+    
+    Some(movie)
+}
 
-    fn load_from_file(&mut self) {
-        if !Path::new(&self.data_file).exists() {
-            return;
-        }
+// Fetch the collection a movie belongs to (blocking). Movies saved before collections were
+// stored have collection_id 0, so their details are looked up first.
+fn fetch_collection(client: &reqwest::blocking::Client, movie: &Movie, api_key: &str) -> Option<TMDBCollection> {
+    let collection_id = if movie.collection_id != 0 {
+        movie.collection_id
+    } else if movie.tmdb_id != 0 {
+        let details_url = format!("https://api.themoviedb.org/3/movie/{}?api_key={}{}", movie.tmdb_id, api_key, tmdb_language_param());
+        let details = client.get(&details_url).send().ok()?.json::<TMDBMovieDetails>().ok()?;
+        details.belongs_to_collection?.id
+    } else {
+        return None;
+    };
+    
+    let collection_url = format!("https://api.themoviedb.org/3/collection/{}?api_key={}{}", collection_id, api_key, tmdb_language_param());
+    let mut collection = client.get(&collection_url).send().ok()?.json::<TMDBCollection>().ok()?;
+    collection.parts.sort_by_key(|p| p.release_date.clone());
+    Some(collection)
+}
 
-        // Try to load new format (entire database as JSON)
-        if let Ok(contents) = std::fs::read_to_string(&self.data_file) {
-            if let Ok(loaded_db) = serde_json::from_str::<MovieDatabase>(&contents) {
-                // Successfully loaded new format
-                self.movies = loaded_db.movies;
-                self.next_id = loaded_db.next_id;
-                self.tmdb_api_key = loaded_db.tmdb_api_key;
-                self.tmdb_cache = loaded_db.tmdb_cache;
-                
-                // Migrate old poster paths to new location
-                self.migrate_poster_paths();
-                
-                return;
-            }
-        }
-        
-        // Fall back to old format (line-by-line movies) for backwards compatibility
-        if let Ok(file) = File::open(&self.data_file) {
-            let reader = BufReader::new(file);
-            for line in reader.lines() {
-                if let Ok(line) = line {
-                    if let Ok(movie) = serde_json::from_str::<Movie>(&line) {
-                        let id = movie.id;
-                        self.movies.insert(id, movie);
-                        if id >= self.next_id {
-                            self.next_id = id + 1;
-                        }
-                    }
-                }
-            }
-        }
+// Per-scan memo of TMDB lookups so files with the same cleaned title (or titles that
+// resolve to the same movie) don't repeat the search, details and download requests.
+// Created by run_scan_job and dropped when the scan completes.
+#[derive(Default)]
+struct ScanCache {
+    matches: HashMap<String, Option<u32>>,      // media type + lowercased search title -> picked TMDB id (None = no match)
+    movies: HashMap<(MediaType, u32), Movie>,  // TMDB id -> fully fetched movie or series
+}
+
+impl ScanCache {
+    fn cached_movie(&self, media_type: MediaType, tmdb_id: u32, file_path: &str) -> Option<Movie> {
+        self.movies.get(&(media_type, tmdb_id)).map(|movie| Movie { file_paths: file_paths_from(file_path.to_string()), ..movie.clone() })
     }
+}
 
-    fn migrate_poster_paths(&mut self) {
-        // Update old poster paths to use new location
-        let mut needs_save = false;
-        
-        for movie in self.movies.values_mut() {
-            if !movie.poster_path.is_empty() {
-                // Check if using old "posters/" or "~/posters/" path
-                if movie.poster_path.starts_with("posters/") || movie.poster_path.starts_with("~/posters/") {
-                    // Extract just the filename
-                    if let Some(filename) = movie.poster_path.split('/').last() {
-                        // Update to new path
-                        let new_path = format!("{}/{}", self.posters_dir, filename);
-                        movie.poster_path = new_path;
-                        needs_save = true;
-                    }
-                }
-            }
-        }
-        
-        // Save if we made any changes
-        if needs_save {
-            if let Err(e) = self.save_to_file() {
-                eprintln!("Warning: Failed to save after poster path migration: {}", e);
-            }
+const TMDB_MAX_RETRIES: u32 = 3;
+
+// The one cap on TMDB requests in flight, shared by every scan, refresh and poster fetch in
+// the process so running several at once can't exceed it. It is sized from Settings the
+// first time it is needed; a changed limit takes effect after a restart.
+fn tmdb_limiter(max_concurrent_requests: usize) -> Arc<tokio::sync::Semaphore> {
+    static LIMITER: std::sync::OnceLock<Arc<tokio::sync::Semaphore>> = std::sync::OnceLock::new();
+    LIMITER.get_or_init(|| Arc::new(tokio::sync::Semaphore::new(max_concurrent_requests.max(1)))).clone()
+}
+
+// GET a TMDB endpoint and parse the JSON, taking a permit from `limiter` for each attempt so a
+// scan's requests stay within the configured concurrency. Rate limiting (429) and server errors are retried
+// up to TMDB_MAX_RETRIES times with exponential backoff (1s, 2s, 4s), waiting for Retry-After
+// instead when TMDB sends it. Other failures (bad key, not found) give up straight away.
+async fn tmdb_get_json<T: serde::de::DeserializeOwned>(
+    client: &reqwest::Client,
+    limiter: &tokio::sync::Semaphore,
+    url: &str,
+) -> Option<T> {
+    let mut attempt = 0;
+    loop {
+        let backoff = std::time::Duration::from_secs(1 << attempt);
+        // The permit is held for the request only, not while backing off
+        let permit = limiter.acquire().await.ok()?;
+        let sent = client.get(url).send().await;
+        drop(permit);
+        let delay = match sent {
+            Ok(response) if response.status().is_success() => return response.json::<T>().await.ok(),
+            Ok(response) if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS => response.headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .map(|secs| std::time::Duration::from_secs(secs.min(60)))
+                .unwrap_or(backoff),
+            Ok(response) if response.status().is_server_error() => backoff,
+            Ok(_) => return None,
+            Err(e) if e.is_timeout() || e.is_connect() => backoff,
+            Err(_) => return None,
+        };
+        if attempt >= TMDB_MAX_RETRIES {
+            log_warn!("Warning: Giving up on TMDB request after {} retries", TMDB_MAX_RETRIES);
+            return None;
         }
+        attempt += 1;
+        tokio::time::sleep(delay).await;
     }
+}
 
-    fn list_all(&self) -> Vec<Movie> {
-        let mut movies: Vec<Movie> = self.movies.values().cloned().collect();
-        movies.sort_by(|a, b| a.title.cmp(&b.title));
-        movies
+// Search TMDB and pick the best match. With a year from the file name the search is
+// limited to that year first, falling back to the title alone if that finds nothing.
+// The outer None is a failed request; the inner one means the search worked but nothing matched.
+async fn search_tmdb_match(
+    client: &reqwest::Client,
+    limiter: &tokio::sync::Semaphore,
+    api_key: &str,
+    title: &str,
+    year: Option<u16>,
+    year_cutoff: i32,
+    min_vote_count: u32,
+) -> Option<Option<u32>> {
+    let search = |year: Option<u16>| async move {
+        tmdb_get_json::<TMDBSearchResponse>(client, limiter, &tmdb_search_url(api_key, title, year)).await
+    };
+    
+    let mut search_response = search(year).await?;
+    if year.is_some() && search_response.results.is_empty() {
+        search_response = search(None).await?;
     }
     
-    // TMDB Cache methods
-    fn get_cached_search(&self, query: &str) -> Option<Vec<(u32, String, String, f32)>> {
-        let cache_max_age_days = 30;  // Cache expires after 30 days
-        
-        if let Some(cached) = self.tmdb_cache.get(query) {
-            if !cached.is_expired(cache_max_age_days) {
-                return Some(cached.results.clone());
-            }
-        }
-        None
-    }
-    
-    fn cache_search_results(&mut self, query: String, results: Vec<(u32, String, String, f32)>) {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        
-        let cached = CachedTMDBSearch {
-            query: query.clone(),
-            results,
-            timestamp,
-        };
-        
-        self.tmdb_cache.insert(query, cached);
-        // Save to persist cache
-        if let Err(e) = self.save_to_file() {
-            eprintln!("Warning: Failed to save TMDB cache: {}", e);
-        }
-    }
-    
-    // Result cache methods for search/filter/sort
-    fn get_cached_results(&self, cache_key: &str) -> Option<Vec<Movie>> {
-        self.result_cache.borrow().get(cache_key).cloned()
-    }
-    
-    fn cache_results(&self, cache_key: String, results: Vec<Movie>) {
-        self.result_cache.borrow_mut().insert(cache_key, results);
-    }
+    // Prioritize popular movies before year_cutoff
+    Some(pick_tmdb_match(&search_response.results, year_cutoff, min_vote_count))
+}
+
+// TV counterpart of search_tmdb_match. The year cutoff is a film preference, so series just
+// need the vote threshold; the year from the file name still narrows the first search.
+async fn search_tmdb_tv_match(
+    client: &reqwest::Client,
+    limiter: &tokio::sync::Semaphore,
+    api_key: &str,
+    title: &str,
+    year: Option<u16>,
+    min_vote_count: u32,
+) -> Option<Option<u32>> {
+    let search = |year: Option<u16>| async move {
+        tmdb_get_json::<TMDBSearchResponse>(client, limiter, &tmdb_tv_search_url(api_key, title, year)).await
+    };
     
-    fn invalidate_result_cache(&self) {
-        self.result_cache.borrow_mut().clear();
+    let mut search_response = search(year).await?;
+    if year.is_some() && search_response.results.is_empty() {
+        search_response = search(None).await?;
     }
+    Some(pick_tmdb_match(&search_response.results, i32::MAX, min_vote_count))
 }
 
-fn create_movie_row(movie: &Movie, poster_cache: &Rc<RefCell<HashMap<u32, Pixbuf>>>) -> gtk::ListBoxRow {
-    let row = gtk::ListBoxRow::new();
-    
-    // Store the movie ID in the row's name property for later retrieval
-    row.set_widget_name(&movie.id.to_string());
+async fn fetch_movie_details_async(
+    client: &reqwest::Client,
+    limiter: &tokio::sync::Semaphore,
+    api_key: &str,
+    media_type: MediaType,
+    movie_id: u32,
+    file_path: String,
+    posters_dir: String,
+) -> Option<Movie> {
+    let details_url = format!(
+        "https://api.themoviedb.org/3/{}/{}?api_key={}&append_to_response={}{}",
+        media_type.tmdb_path(), movie_id, api_key, media_type.details_append(),
+        tmdb_language_param()
+    );
     
-    let hbox = Box::new(Orientation::Horizontal, 12);
-    hbox.set_margin_start(12);
-    hbox.set_margin_end(12);
-    hbox.set_margin_top(8);
-    hbox.set_margin_bottom(8);
-
-    // Add poster thumbnail
-    let poster_container = gtk::Overlay::new();
-    poster_container.set_size_request(60, 90);
+    let mut movie = match media_type {
+        MediaType::Movie => {
+            let details = tmdb_get_json::<TMDBMovieDetails>(client, limiter, &details_url).await?;
+            movie_from_tmdb_details(details, movie_id, file_path)
+        }
+        MediaType::Tv => {
+            let details = tmdb_get_json::<TMDBTvDetails>(client, limiter, &details_url).await?;
+            movie_from_tmdb_tv_details(details, movie_id, file_path)
+        }
+    };
     
-    let poster_box = Box::new(Orientation::Vertical, 0);
-    poster_box.set_size_request(60, 90);
+    // Cast photos go through the same (blocking) cache as everywhere else, off the runtime thread
+    let mut cast_details = std::mem::take(&mut movie.cast_details);
+    let cast_posters_dir = posters_dir.clone();
+    let permit = limiter.acquire().await.ok()?;
+    movie.cast_details = tokio::task::spawn_blocking(move || {
+        download_cast_photos(&reqwest::blocking::Client::new(), &mut cast_details, &cast_posters_dir);
+        cast_details
+    }).await.ok()?;
+    drop(permit);
     
-    if !movie.poster_path.is_empty() && Path::new(&movie.poster_path).exists() {
-        // Check cache first
-        let cache_borrow = poster_cache.borrow();
-        
-        if let Some(thumbnail) = cache_borrow.get(&movie.id) {
-            // Use cached thumbnail directly (FAST!)
-            let picture = Picture::for_pixbuf(thumbnail);
-            picture.set_can_shrink(true);
-            poster_box.append(&picture);
-        } else {
-            drop(cache_borrow); // Release read lock before writing
-            
-            // Load from disk and create thumbnail
-            if let Ok(pixbuf) = Pixbuf::from_file(&movie.poster_path) {
-                // Scale to thumbnail size BEFORE caching (saves 1500x memory!)
-                if let Some(thumbnail) = pixbuf.scale_simple(60, 90, gtk::gdk_pixbuf::InterpType::Bilinear) {
-                    // Cache ONLY the thumbnail (~16KB instead of ~24MB!)
-                    let mut cache_mut = poster_cache.borrow_mut();
-                    cache_mut.insert(movie.id, thumbnail.clone());
-                    drop(cache_mut);
-                    
-                    // Display the thumbnail
-                    let picture = Picture::for_pixbuf(&thumbnail);
-                    picture.set_can_shrink(true);
-                    poster_box.append(&picture);
-                }
-            }
-        }
-    } else {
-        // Placeholder for missing poster
-        let placeholder = Label::new(Some("🎬"));
-        placeholder.set_markup("<span size='xx-large'>🎬</span>");
-        poster_box.append(&placeholder);
+    if !movie.poster_url.is_empty() {
+        let poster_key = match media_type {
+            MediaType::Movie => movie_id.to_string(),
+            MediaType::Tv => format!("tv_{}", movie_id),
+        };
+        movie.poster_path = download_poster(&movie.poster_url, poster_key, &posters_dir).unwrap_or_default();
     }
     
-    poster_container.set_child(Some(&poster_box));
+    // Fetch IMDb ID from external_ids endpoint
+    let external_ids_url = format!(
+        "https://api.themoviedb.org/3/{}/{}/external_ids?api_key={}",
+        media_type.tmdb_path(), movie_id, api_key
+    );
     
-    // Add "SEEN" badge if movie has been watched
-    if !movie.watch_log.is_empty() {
-        let seen_label = Label::new(Some("SEEN"));
-        seen_label.set_markup("<span foreground='red' weight='bold' size='large'>SEEN</span>");
-        seen_label.set_halign(Align::Center);
-        seen_label.set_valign(Align::Center);
-        poster_container.add_overlay(&seen_label);
+    if let Some(external_ids) = tmdb_get_json::<TMDBExternalIds>(client, limiter, &external_ids_url).await {
+        movie.imdb_id = external_ids.imdb_id.unwrap_or_default();
     }
     
-    hbox.append(&poster_container);
+    Some(movie)
+}
 
-    let vbox = Box::new(Orientation::Vertical, 4);
-    
-    let title_label = Label::new(Some(&format!("{} ({})", movie.title, movie.year)));
-    title_label.set_xalign(0.0);
-    // Escape special characters for Pango markup
-    let escaped_title = escape_markup(&movie.title);
-    title_label.set_markup(&format!("<b>{}</b> ({})", escaped_title, movie.year));
-    
-    let info_label = Label::new(Some(&format!("⭐ {:.1}/10 | {} | {} min", 
-        movie.rating, movie.genre.join(", "), movie.runtime)));
-    info_label.set_xalign(0.0);
-    info_label.set_opacity(0.7);
-    
-    let director_label = Label::new(Some(&format!("Director: {}", movie.director)));
-    director_label.set_xalign(0.0);
-    director_label.set_opacity(0.6);
+// OMDb's title lookup. Missing values come back as "N/A", and a title it doesn't know is
+// a 200 response with Response "False".
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct OMDbMovie {
+    response: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    year: String,  // "1999", or "1999–2003" for series
+    #[serde(default)]
+    genre: String,  // "Crime, Drama"
+    #[serde(default)]
+    runtime: String,  // "142 min"
+    #[serde(default)]
+    plot: String,
+    #[serde(default)]
+    director: String,
+    #[serde(default, rename = "imdbRating")]
+    imdb_rating: String,
+    #[serde(default, rename = "imdbID")]
+    imdb_id: String,
+    #[serde(default)]
+    rated: String,  // "PG-13", "N/A"
+}
 
-    vbox.append(&title_label);
-    vbox.append(&info_label);
-    vbox.append(&director_label);
-    
-    hbox.append(&vbox);
-    row.set_child(Some(&hbox));
+// Fallback for movies TMDB can't find: look the title up on OMDb and build a movie from
+// its basic fields, marked with source "omdb". None without a key or a match.
+async fn fetch_omdb_movie_async(
+    client: &reqwest::Client,
+    limiter: &tokio::sync::Semaphore,
+    omdb_api_key: &str,
+    title: &str,
+    year: Option<u16>,
+    file_path: String,
+) -> Option<Movie> {
+    if omdb_api_key.is_empty() {
+        return None;
+    }
+    let mut url = format!(
+        "https://www.omdbapi.com/?apikey={}&type=movie&t={}",
+        urlencoding::encode(omdb_api_key),
+        urlencoding::encode(title)
+    );
+    if let Some(year) = year {
+        url.push_str(&format!("&y={}", year));
+    }
+    let found = tmdb_get_json::<OMDbMovie>(client, limiter, &url).await?;
+    if found.response != "True" {
+        return None;
+    }
     
-    row
+    let known = |value: String| if value == "N/A" { String::new() } else { value };
+    Some(Movie {
+        title: if found.title.is_empty() { title.to_string() } else { found.title },
+        year: found.year.get(..4).and_then(|y| y.parse().ok()).or(year).unwrap_or(0),
+        director: known(found.director),
+        genre: known(found.genre).split(',').map(|g| g.trim().to_string()).filter(|g| !g.is_empty()).collect(),
+        rating: found.imdb_rating.parse().unwrap_or(0.0),
+        runtime: found.runtime.split_whitespace().next().and_then(|m| m.parse().ok()).unwrap_or(0),
+        description: known(found.plot),
+        imdb_id: known(found.imdb_id),
+        certification: if found.rated == "Not Rated" { String::new() } else { known(found.rated) },
+        file_paths: file_paths_from(file_path),
+        source: String::from("omdb"),
+        ..Default::default()
+    })
 }
 
-fn create_movie_row_with_context(
-    movie: &Movie,
-    poster_cache: &Rc<RefCell<HashMap<u32, Pixbuf>>>,
-    db: &Rc<RefCell<MovieDatabase>>,
-) -> gtk::ListBoxRow {
-    let row = create_movie_row(movie, poster_cache);
-    
-    // Add right-click context menu
-    let gesture = gtk::GestureClick::new();
-    gesture.set_button(3); // Right mouse button
-    
-    let movie_id = movie.id;
-    let file_path = movie.file_path.clone();
-    let movie_title = movie.title.clone();
-    let db_clone = db.clone();
-    let row_clone = row.clone();
-    
-    gesture.connect_released(move |_, _, x, y| {
-        let menu_model = gtk::gio::Menu::new();
-        menu_model.append(Some("▶️ Play in VLC"), Some("movie.play"));
-        menu_model.append(Some("ℹ️ View Details"), Some("movie.details"));
-        menu_model.append(Some("🗑️ Delete Movie Metadata"), Some("movie.delete"));
-        
-        let menu = gtk::PopoverMenu::from_model(Some(&menu_model));
-        menu.set_parent(&row_clone);
-        menu.set_pointing_to(Some(&gtk::gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
-        
-        // Create action group
-        let actions = gtk::gio::SimpleActionGroup::new();
-        
-        // Play action
-        let play_action = gtk::gio::SimpleAction::new("play", None);
-        let file_path_clone = file_path.clone();
-        let movie_title_clone = movie_title.clone();
-        let menu_clone = menu.clone();
-        let db_clone_for_play = db_clone.clone();
-        let movie_id_for_play = movie_id;
-        play_action.connect_activate(move |_, _| {
-            if !file_path_clone.is_empty() && Path::new(&file_path_clone).exists() {
-                // Try regular VLC first
-                match Command::new("vlc")
-                    .arg(&file_path_clone)
-                    .stdout(Stdio::null())
-                    .stderr(Stdio::null())
-                    .spawn()
-                {
-                    Ok(_) => {
-                        // Auto-log to watch history
-                        if let Ok(mut db_mut) = db_clone_for_play.try_borrow_mut() {
-                            if let Some(movie) = db_mut.movies.get_mut(&movie_id_for_play) {
-                                let today = chrono::Local::now().format("%Y-%m-%d").to_string();
-                                let entry = WatchLogEntry {
-                                    date: today,
-                                    rating: None,
-                                    comments: String::from("Watched"),
-                                };
-                                movie.watch_log.push(entry);
-                            }
-                            if let Err(e) = db_mut.save_to_file() {
-                                eprintln!("Playing: {} (logged but save failed: {})", movie_title_clone, e);
-                            } else {
-                                eprintln!("Playing: {} (logged to watch history)", movie_title_clone);
-                            }
-                        } else {
-                            eprintln!("Playing: {} (couldn't log - database busy)", movie_title_clone);
-                        }
-                    }
-                    Err(_) => {
-                        // Try flatpak VLC
-                        match Command::new("flatpak")
-                            .args(["run", "org.videolan.VLC", &file_path_clone])
-                            .stdout(Stdio::null())
-                            .stderr(Stdio::null())
-                            .spawn()
-                        {
-                            Ok(_) => {
-                                // Auto-log to watch history
-                                if let Ok(mut db_mut) = db_clone_for_play.try_borrow_mut() {
-                                    if let Some(movie) = db_mut.movies.get_mut(&movie_id_for_play) {
-                                        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
-                                        let entry = WatchLogEntry {
-                                            date: today,
-                                            rating: None,
-                                            comments: String::from("Watched"),
-                                        };
-                                        movie.watch_log.push(entry);
-                                    }
-                                    if let Err(e) = db_mut.save_to_file() {
-                                        eprintln!("Playing: {} (logged but save failed: {})", movie_title_clone, e);
-                                    } else {
-                                        eprintln!("Playing: {} (logged to watch history)", movie_title_clone);
-                                    }
-                                } else {
-                                    eprintln!("Playing: {} (couldn't log - database busy)", movie_title_clone);
-                                }
-                            }
-                            Err(_) => {
-                                eprintln!("VLC not found");
-                            }
-                        }
+impl MovieDatabase {
+    // Open the library, converting it to the storage format picked in Settings
+    fn new(data_file: &str, posters_dir: &str, api_key: &str) -> Self {
+        let storage_format = load_config().map(|c| c.storage_format).unwrap_or_else(default_storage_format);
+        Self::open(data_file, posters_dir, api_key, storage_format)
+    }
+
+    // Open some other library file in the format it is already stored in, so writing to it
+    // doesn't convert it. A file that doesn't exist yet gets the format from Settings.
+    fn open_as_stored(data_file: &str, posters_dir: &str, api_key: &str) -> Self {
+        let storage_format = stored_format(data_file).map(String::from)
+            .unwrap_or_else(|| load_config().map(|c| c.storage_format).unwrap_or_else(default_storage_format));
+        Self::open(data_file, posters_dir, api_key, storage_format)
+    }
+
+    fn open(data_file: &str, posters_dir: &str, api_key: &str, storage_format: String) -> Self {
+        let mut db = MovieDatabase {
+            movies: HashMap::new(),
+            next_id: 1,
+            data_file: data_file.to_string(),
+            posters_dir: posters_dir.to_string(),
+            tmdb_api_key: api_key.to_string(),
+            tmdb_cache: HashMap::new(),
+            poster_cache: Rc::new(RefCell::new(HashMap::new())),
+            result_cache: RefCell::new(HashMap::new()),
+            storage_format,
+            sqlite: RefCell::new(None),
+            last_deleted: None,
+            rejected_records: 0,
+            load_error: None,
+            changed_ids: RefCell::new(HashSet::new()),
+        };
+        db.load_from_file();
+        db
+    }
+
+    // The one way new movies enter the library, so every caller gets the same duplicate
+    // check, made in the same step as the insert. A movie is a duplicate if one of its files
+    // is already in the library, or if an entry of the same kind has its (non-zero) TMDB id.
+    // The new files are attached to that entry (a film's extra copies, a show's new
+    // episodes), so later scans don't keep finding them. Returns the new movie's id, or None for a duplicate.
+    fn add_movie(&mut self, movie: Movie) -> Option<u32> {
+        self.add_checked(movie, true)
+    }
+
+    // For when the user has confirmed they want a second entry for a movie they already
+    // own. Only the file check is made - a file still can't belong to two entries.
+    fn add_movie_allowing_duplicate(&mut self, movie: Movie) -> Option<u32> {
+        self.add_checked(movie, false)
+    }
+
+    fn add_checked(&mut self, mut movie: Movie, check_tmdb_id: bool) -> Option<u32> {
+        if self.movies.values().any(|m| m.file_paths.iter().any(|path| movie.file_paths.contains(path))) {
+            return None;
+        }
+        if check_tmdb_id && movie.tmdb_id != 0 {
+            let existing = self.movies.values_mut()
+                .find(|m| m.tmdb_id == movie.tmdb_id && m.media_type == movie.media_type);
+            if let Some(existing) = existing {
+                if !movie.file_paths.is_empty() {
+                    existing.file_paths.extend(movie.file_paths);
+                    let existing_id = existing.id;
+                    self.forget_last_deleted();
+                    self.mark_changed(existing_id);
+                    self.invalidate_result_cache();
+                    if let Err(e) = self.save_to_file() {
+                        log_warn!("Warning: Failed to save database after attaching a file: {}", e);
                     }
                 }
+                return None;
             }
-            menu_clone.popdown();
-        });
-        
-        // View Details action
-        let details_action = gtk::gio::SimpleAction::new("details", None);
-        let db_clone2 = db_clone.clone();
-        let menu_clone_details = menu.clone();
-        details_action.connect_activate(move |_, _| {
-            let db = db_clone2.borrow();
-            if let Some(movie) = db.movies.get(&movie_id) {
-                // Create details dialog
-                let details_dialog = gtk::Window::builder()
-                    .title(&format!("{} - Details", movie.title))
-                    .modal(true)
-                    .default_width(700)
-                    .default_height(600)
-                    .build();
-                
-                let scroll = gtk::ScrolledWindow::new();
-                scroll.set_vexpand(true);
-                
-                let details_box = gtk::Box::new(gtk::Orientation::Vertical, 16);
-                details_box.set_margin_start(20);
-                details_box.set_margin_end(20);
-                details_box.set_margin_top(20);
-                details_box.set_margin_bottom(20);
-                
-                // Poster at top
-                if !movie.poster_path.is_empty() && std::path::Path::new(&movie.poster_path).exists() {
-                    if let Ok(pixbuf) = gtk::gdk_pixbuf::Pixbuf::from_file(&movie.poster_path) {
-                        if let Some(scaled) = pixbuf.scale_simple(200, 300, gtk::gdk_pixbuf::InterpType::Bilinear) {
-                            let poster_img = gtk::Picture::for_pixbuf(&scaled);
-                            poster_img.set_halign(gtk::Align::Center);
-                            details_box.append(&poster_img);
-                        }
-                    }
-                }
-                
-                // Movie information
-                let info_label = gtk::Label::new(None);
-                info_label.set_xalign(0.0);
-                info_label.set_wrap(true);
-                info_label.set_selectable(true);
-                
-                let escaped_title = escape_markup(&movie.title);
-                let escaped_director = escape_markup(&movie.director);
-                let escaped_genre = escape_markup(&movie.genre.join(", "));
-                let escaped_description = escape_markup(&movie.description);
-                let escaped_file = escape_markup(&movie.file_path);
-                
-                let cast_display = if !movie.cast.is_empty() {
-                    movie.cast.iter().map(|n| escape_markup(n)).collect::<Vec<_>>().join("\n    • ")
-                } else {
-                    String::from("Unknown")
-                };
-                
-                let imdb_display = if !movie.imdb_id.is_empty() {
-                    format!("{} (https://www.imdb.com/title/{})", movie.imdb_id, movie.imdb_id)
+        }
+        movie.date_added = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        Some(self.insert_movie(movie))
+    }
+
+    // Take the next unused id. next_id only ever grows and is saved with the database, but
+    // ids already in use win over a stale counter (e.g. after the file was edited by hand).
+    fn allocate_id(&mut self) -> u32 {
+        let after_existing = self.movies.keys().max().map_or(1, |id| id + 1);
+        let id = self.next_id.max(after_existing);
+        self.next_id = id + 1;
+        id
+    }
+
+    // Store a movie under the next id, keeping its date_added as is. Returns the id.
+    fn insert_movie(&mut self, mut movie: Movie) -> u32 {
+        self.forget_last_deleted();
+        let id = self.allocate_id();
+        movie.id = id;
+        if !movie.file_paths.is_empty() {
+            update_local_file_info(&mut movie);
+        }
+        self.movies.insert(id, movie);
+        self.mark_changed(id);
+        self.invalidate_result_cache();
+        if let Err(e) = self.save_to_file() {
+            log_warn!("Warning: Failed to save database after adding movie: {}", e);
+        }
+        id
+    }
+
+    // TMDB ids of every movie in the library (owned files and watchlist entries)
+    fn owned_tmdb_ids(&self) -> std::collections::HashSet<u32> {
+        self.movies.values()
+            .map(|m| m.tmdb_id)
+            .filter(|id| *id != 0)
+            .collect()
+    }
+
+    // Every file attached to a movie in the library, for scans to skip
+    fn existing_file_paths(&self) -> HashSet<String> {
+        self.movies.values()
+            .flat_map(|m| m.file_paths.iter().cloned())
+            .collect()
+    }
+
+    fn search_by_title(&self, query: &str) -> Vec<Movie> {
+        let query_lower = query.to_lowercase();
+        self.movies
+            .values()
+            .filter(|m| m.title.to_lowercase().contains(&query_lower))
+            .cloned()
+            .collect()
+    }
+
+    // Movies whose title, director, cast or description contain every word of the query
+    fn search_all(&self, query: &str) -> Vec<Movie> {
+        let words: Vec<String> = query.split_whitespace().map(|w| w.to_lowercase()).collect();
+        self.movies
+            .values()
+            .filter(|m| {
+                let text = searchable_text(m);
+                words.iter().all(|word| text.contains(word.as_str()))
+            })
+            .cloned()
+            .collect()
+    }
+
+    // Ids of movies released from `min` to `max` inclusive, either way round; an unknown
+    // year (0) never matches
+    fn filter_by_year_range(&self, min: u16, max: u16) -> HashSet<u32> {
+        let (min, max) = (min.min(max), min.max(max));
+        self.movies
+            .values()
+            .filter(|m| m.year != 0 && (min..=max).contains(&m.year))
+            .map(|m| m.id)
+            .collect()
+    }
+
+    // Ids of movies rated at least `rating` on TMDB
+    fn filter_by_min_rating(&self, rating: f32) -> HashSet<u32> {
+        self.movies
+            .values()
+            .filter(|m| m.rating >= rating)
+            .map(|m| m.id)
+            .collect()
+    }
+
+    // Movies with every one of `genres` (match_all) or any of them; no genres means all movies
+    fn search_by_genres(&self, genres: &[String], match_all: bool) -> Vec<Movie> {
+        if genres.is_empty() {
+            return self.list_all();
+        }
+        let has_genre = |movie: &Movie, genre: &String| movie.genre.iter().any(|g| g.eq_ignore_ascii_case(genre));
+        self.movies
+            .values()
+            .filter(|m| if match_all {
+                genres.iter().all(|genre| has_genre(m, genre))
+            } else {
+                genres.iter().any(|genre| has_genre(m, genre))
+            })
+            .cloned()
+            .collect()
+    }
+
+    // Every genre used in the library, sorted case-insensitively. Feeds the genre filter and
+    // the edit dialog, so genres TMDB adds later (or custom ones) show up without code changes
+    fn all_genres(&self) -> Vec<String> {
+        let mut genres: Vec<String> = Vec::new();
+        for genre in self.movies.values().flat_map(|m| m.genre.iter()) {
+            if !genres.iter().any(|g| g.eq_ignore_ascii_case(genre)) {
+                genres.push(genre.clone());
+            }
+        }
+        genres.sort_by_key(|g| g.to_lowercase());
+        genres
+    }
+
+    // Every tag used in the library, sorted case-insensitively, for the tag filter
+    fn all_tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = Vec::new();
+        for tag in self.movies.values().flat_map(|m| m.tags.iter()) {
+            if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                tags.push(tag.clone());
+            }
+        }
+        tags.sort_by_key(|t| t.to_lowercase());
+        tags
+    }
+
+    // Age ratings used in the library, youngest audience first for the US and TV ratings,
+    // others after them by name
+    fn all_certifications(&self) -> Vec<String> {
+        let mut certifications: Vec<String> = self.movies.values()
+            .map(|m| m.certification.clone())
+            .filter(|c| !c.is_empty())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        certifications.sort_by_key(|c| {
+            let rank = CERTIFICATION_ORDER.iter().position(|known| known == c).unwrap_or(CERTIFICATION_ORDER.len());
+            (rank, c.clone())
+        });
+        certifications
+    }
+
+    // TMDB collections with at least one movie in the library: id, name and movie count, by name
+    fn collections(&self) -> Vec<(u32, String, usize)> {
+        let mut counts: HashMap<u32, (String, usize)> = HashMap::new();
+        for movie in self.movies.values().filter(|m| m.collection_id != 0) {
+            counts.entry(movie.collection_id).or_insert_with(|| (movie.collection_name.clone(), 0)).1 += 1;
+        }
+        let mut collections: Vec<(u32, String, usize)> = counts.into_iter()
+            .map(|(id, (name, count))| (id, name, count))
+            .collect();
+        collections.sort_by_key(|(_, name, _)| name.to_lowercase());
+        collections
+    }
+
+    // Poster files in posters_dir that no movie points at, with their sizes. Files are matched
+    // by name, and the movie held for Undo Delete still counts, so undoing gets its poster back.
+    fn orphaned_posters(&self) -> Vec<(PathBuf, u64)> {
+        let referenced: HashSet<std::ffi::OsString> = self.movies.values()
+            .chain(self.last_deleted.iter())
+            .filter_map(|m| Path::new(&m.poster_path).file_name().map(|name| name.to_os_string()))
+            .collect();
+        let Ok(entries) = read_dir(&self.posters_dir) else {
+            return Vec::new();
+        };
+        let mut orphans: Vec<(PathBuf, u64)> = entries.flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with("poster_"))
+            .filter(|entry| !referenced.contains(&entry.file_name()))
+            .filter_map(|entry| {
+                let meta = entry.metadata().ok().filter(|meta| meta.is_file())?;
+                Some((entry.path(), meta.len()))
+            })
+            .collect();
+        orphans.sort();
+        orphans
+    }
+
+    // Delete the given poster files unless a movie (or the one held for Undo Delete) still
+    // uses them, e.g. posters fetched for scan preview matches that were skipped
+    fn remove_unused_posters(&self, paths: &[String]) -> usize {
+        let referenced: HashSet<&str> = self.movies.values()
+            .chain(self.last_deleted.iter())
+            .map(|m| m.poster_path.as_str())
+            .collect();
+        let mut removed = 0;
+        for path in paths.iter().filter(|path| !path.is_empty() && !referenced.contains(path.as_str())) {
+            match std::fs::remove_file(path) {
+                Ok(()) => removed += 1,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => log_warn!("Warning: Failed to delete {}: {}", path, e),
+            }
+        }
+        removed
+    }
+
+    // What a background refresh needs to know about each of the given movies
+    fn refresh_targets(&self, ids: &[u32]) -> Vec<RefreshTarget> {
+        ids.iter()
+            .filter_map(|id| self.movies.get(id))
+            .map(|m| RefreshTarget {
+                id: m.id,
+                title: m.title.clone(),
+                year: m.year,
+                tmdb_id: m.tmdb_id,
+                media_type: m.media_type,
+                file_path: m.primary_file().to_string(),
+            })
+            .collect()
+    }
+
+    // Swap in refreshed metadata for a movie under the same id, see merge_refreshed_movie
+    // for what is kept. A movie deleted while the refresh ran is added back as new.
+    fn replace_with_refreshed(&mut self, old_id: u32, movie: Movie) {
+        let Some(old) = self.movies.get(&old_id) else {
+            self.insert_movie(movie);
+            return;
+        };
+        let mut movie = merge_refreshed_movie(old, movie);
+        if !movie.file_paths.is_empty() && movie.primary_file() != old.primary_file() {
+            update_local_file_info(&mut movie);
+        }
+        self.forget_last_deleted();
+        self.movies.insert(old_id, movie);
+        self.mark_changed(old_id);
+        self.poster_cache.borrow_mut().remove(&old_id);
+        self.invalidate_result_cache();
+        if let Err(e) = self.save_to_file() {
+            log_warn!("Warning: Failed to save refreshed movie: {}", e);
+        }
+    }
+
+    // Flip a movie's favorite flag. Returns the new state, or None if the movie doesn't exist.
+    fn toggle_favorite(&mut self, id: u32) -> Option<bool> {
+        let movie = self.movie_mut(id)?;
+        movie.favorite = !movie.favorite;
+        let favorite = movie.favorite;
+        self.invalidate_result_cache();
+        if let Err(e) = self.save_to_file() {
+            log_warn!("Warning: Failed to save favorite: {}", e);
+        }
+        Some(favorite)
+    }
+
+    // Flip a movie's watched flag. Returns the new state, or None if the movie doesn't exist.
+    fn toggle_watched(&mut self, id: u32) -> Option<bool> {
+        let movie = self.movie_mut(id)?;
+        movie.watched = !movie.watched;
+        let watched = movie.watched;
+        self.invalidate_result_cache();
+        if let Err(e) = self.save_to_file() {
+            log_warn!("Warning: Failed to save watched flag: {}", e);
+        }
+        Some(watched)
+    }
+
+    // Remove one movie, keeping it for undo_delete until the library next changes
+    fn delete_movie(&mut self, id: u32) -> bool {
+        if let Some(movie) = self.movies.remove(&id) {
+            self.last_deleted = Some(movie);
+            self.mark_changed(id);
+            self.invalidate_result_cache();
+            if let Err(e) = self.save_to_file() {
+                log_warn!("Warning: Failed to save database after deleting movie: {}", e);
+                // Still return true - movie was deleted from memory
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    // Put back the movie removed by the last delete_movie, under its old id unless that has
+    // been taken since. Returns the restored movie's id and title, or None if there's nothing
+    // to undo.
+    fn undo_delete(&mut self) -> Option<(u32, String)> {
+        let movie = self.last_deleted.take()?;
+        let title = movie.title.clone();
+        if self.movies.contains_key(&movie.id) {
+            return Some((self.insert_movie(movie), title));
+        }
+        let id = movie.id;
+        self.next_id = self.next_id.max(id + 1);
+        self.movies.insert(id, movie);
+        self.mark_changed(id);
+        self.invalidate_result_cache();
+        if let Err(e) = self.save_to_file() {
+            log_warn!("Warning: Failed to save database after undoing delete: {}", e);
+        }
+        Some((id, title))
+    }
+
+    // Groups of ids for movies that look like the same film: the same non-zero TMDB id, or for
+    // unmatched entries the same normalized title and year. Ids within a group are ascending.
+    fn find_duplicates(&self) -> Vec<Vec<u32>> {
+        let mut groups: HashMap<String, Vec<u32>> = HashMap::new();
+        for movie in self.movies.values() {
+            let key = if movie.tmdb_id != 0 {
+                format!("tmdb:{}", movie.tmdb_id)
+            } else {
+                format!("title:{}:{}", normalize_title(&movie.title), movie.year)
+            };
+            groups.entry(key).or_default().push(movie.id);
+        }
+        let mut duplicates: Vec<Vec<u32>> = groups.into_values()
+            .filter(|ids| ids.len() > 1)
+            .map(|mut ids| {
+                ids.sort_unstable();
+                ids
+            })
+            .collect();
+        duplicates.sort();
+        duplicates
+    }
+
+    // Movies none of whose linked files exist any more. Entries without a file (watchlist/manual) are skipped.
+    fn missing_file_ids(&self) -> Vec<u32> {
+        self.movies.values()
+            .filter(|m| no_file_left(&m.file_paths))
+            .map(|m| m.id)
+            .collect()
+    }
+
+    // Compare the library with the video files a rescan found. Found files the library
+    // doesn't have are reported as new. A movie's files that weren't found and aren't on
+    // disk are gone: a movie with another copy left reports them as missing copies, and one
+    // with none left is reported for pruning. Nothing is changed until the caller asks.
+    fn reconcile(&self, found_paths: &HashSet<String>) -> ReconcileReport {
+        let known_paths = self.existing_file_paths();
+        let mut report = ReconcileReport {
+            new_files: found_paths.iter().filter(|path| !known_paths.contains(*path)).cloned().collect(),
+            ..Default::default()
+        };
+        report.new_files.sort();
+        
+        for movie in self.movies.values() {
+            if movie.file_paths.is_empty() {
+                continue;  // Watchlist entry
+            }
+            let (present, missing): (Vec<&String>, Vec<&String>) = movie.file_paths.iter()
+                .partition(|path| found_paths.contains(*path) || Path::new(path).exists());
+            if present.is_empty() {
+                report.missing_ids.push(movie.id);
+            } else if !missing.is_empty() {
+                report.missing_copies.extend(missing.into_iter().map(|path| (movie.id, path.clone())));
+            } else {
+                report.unchanged += 1;
+            }
+        }
+        report.missing_ids.sort();
+        report.missing_copies.sort();
+        report
+    }
+
+    // Detach the given (movie id, path) copies, as reported by reconcile. A movie keeps
+    // its last file even if it is listed. Returns how many copies were removed.
+    fn remove_copies(&mut self, copies: &[(u32, String)]) -> usize {
+        let mut removed = 0;
+        for (id, path) in copies {
+            let Some(movie) = self.movies.get_mut(id) else {
+                continue;
+            };
+            if movie.file_paths.len() > 1 && movie.file_paths.contains(path) {
+                movie.file_paths.retain(|p| p != path);
+                removed += 1;
+                self.changed_ids.borrow_mut().insert(*id);
+            }
+        }
+        if removed > 0 {
+            self.forget_last_deleted();
+            self.invalidate_result_cache();
+            if let Err(e) = self.save_to_file() {
+                log_warn!("Warning: Failed to save database after removing missing copies: {}", e);
+            }
+        }
+        removed
+    }
+
+    // Re-read file size and subtitle sidecars for every movie with a file on disk, without
+    // any TMDB calls. Saves once if anything changed. Returns (files checked, movies updated).
+    fn refresh_local_file_info(&mut self) -> (usize, usize) {
+        let mut checked = 0;
+        let mut updated = 0;
+        for movie in self.movies.values_mut() {
+            if !Path::new(movie.primary_file()).is_file() {
+                continue;
+            }
+            checked += 1;
+            if update_local_file_info(movie) {
+                self.changed_ids.borrow_mut().insert(movie.id);
+                updated += 1;
+            }
+        }
+        if updated > 0 {
+            self.forget_last_deleted();
+            self.invalidate_result_cache();
+            if let Err(e) = self.save_to_file() {
+                log_warn!("Warning: Failed to save local file info: {}", e);
+            }
+        }
+        (checked, updated)
+    }
+
+    // Delete several movies, saving once at the end. Returns how many were removed.
+    fn delete_many(&mut self, ids: &[u32]) -> usize {
+        self.forget_last_deleted();
+        let removed = ids.iter().filter(|id| self.movies.remove(id).is_some()).count();
+        self.changed_ids.borrow_mut().extend(ids);
+        if removed > 0 {
+            self.invalidate_result_cache();
+            if let Err(e) = self.save_to_file() {
+                log_warn!("Warning: Failed to save database after deleting movies: {}", e);
+            }
+        }
+        removed
+    }
+
+    // Write a backup zip holding the data file (as "movies.db"), the given settings (as
+    // "config.json", minus the API key) and every poster the library references (under
+    // "posters/"). Returns the number of posters included.
+    fn export_archive(&self, path: &Path, config: Option<&Config>) -> std::io::Result<usize> {
+        self.save_to_file()?;
+        let data = std::fs::read(&self.data_file)
+            .map_err(io_context(format!("Failed to read {}", self.data_file)))?;
+        
+        let file = File::create(path)
+            .map_err(io_context(format!("Failed to create {}", path.display())))?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::FileOptions::default();
+        
+        zip.start_file("movies.db", options)?;
+        std::io::Write::write_all(&mut zip, &data)?;
+        
+        if let Some(config) = config {
+            let config = Config { tmdb_api_key: String::new(), ..config.clone() };
+            zip.start_file("config.json", options)?;
+            std::io::Write::write_all(&mut zip, &serde_json::to_vec_pretty(&config)?)?;
+        }
+        
+        let mut poster_names = std::collections::HashSet::new();
+        for movie in self.movies.values() {
+            let poster = Path::new(&movie.poster_path);
+            let Some(name) = poster.file_name().map(|n| n.to_string_lossy().to_string()) else {
+                continue;
+            };
+            if !poster.is_file() || !poster_names.insert(name.clone()) {
+                continue;
+            }
+            // Posters are already compressed images
+            let stored = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+            zip.start_file(format!("posters/{}", name), stored)?;
+            let bytes = std::fs::read(poster).map_err(io_context(format!("Failed to read {}", poster.display())))?;
+            std::io::Write::write_all(&mut zip, &bytes)?;
+        }
+        
+        zip.finish()?;
+        Ok(poster_names.len())
+    }
+
+    // Restore a backup made by export_archive, replacing the current library. Posters go
+    // into this database's posters directory and poster paths are rewritten to match.
+    // Returns the number of movies restored and the backup's settings, if it has them, for
+    // the caller to apply with restore_backup_settings.
+    fn import_archive(&mut self, path: &Path) -> std::io::Result<(usize, Option<Config>)> {
+        let invalid = |message: String| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+        let file = File::open(path)
+            .map_err(io_context(format!("Failed to open {}", path.display())))?;
+        let mut archive = zip::ZipArchive::new(file)
+            .map_err(|e| invalid(format!("Not a valid backup archive: {}", e)))?;
+        
+        let mut data = Vec::new();
+        archive.by_name("movies.db")
+            .map_err(|_| invalid("Backup does not contain movies.db".to_string()))?
+            .read_to_end(&mut data)
+            .map_err(io_context("Failed to read movies.db from backup".to_string()))?;
+        
+        // Backups made before settings were included have no config.json
+        let mut restored_config = None;
+        if let Ok(mut entry) = archive.by_name("config.json") {
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents)
+                .map_err(io_context("Failed to read config.json from backup".to_string()))?;
+            let config: Config = serde_json::from_str(&contents)
+                .map_err(|e| invalid(format!("Backup has an unreadable config.json: {}", e)))?;
+            restored_config = Some(config);
+        }
+        
+        create_dir_all(&self.posters_dir)
+            .map_err(io_context(format!("Failed to create {}", self.posters_dir)))?;
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i)?;
+            let Some(name) = entry.enclosed_name()
+                .filter(|p| p.starts_with("posters"))
+                .and_then(|p| p.file_name().map(|n| n.to_os_string()))
+            else {
+                continue;
+            };
+            let target = Path::new(&self.posters_dir).join(name);
+            let mut out = File::create(&target)
+                .map_err(io_context(format!("Failed to write {}", target.display())))?;
+            std::io::copy(&mut entry, &mut out)
+                .map_err(io_context(format!("Failed to write {}", target.display())))?;
+        }
+        
+        // Close the SQLite file before it is replaced
+        *self.sqlite.borrow_mut() = None;
+        write_file_atomically(Path::new(&self.data_file), &data)
+            .map_err(io_context(format!("Failed to write {}", self.data_file)))?;
+        
+        // Reload
+        self.movies.clear();
+        self.forget_last_deleted();
+        self.next_id = 1;
+        self.tmdb_cache.clear();
+        self.load_from_file();
+        
+        for movie in self.movies.values_mut() {
+            if let Some(name) = Path::new(&movie.poster_path).file_name() {
+                let local = Path::new(&self.posters_dir).join(name);
+                movie.poster_path = if local.is_file() { local.to_string_lossy().to_string() } else { String::new() };
+            }
+        }
+        self.changed_ids.borrow_mut().extend(self.movies.keys());
+        self.poster_cache.borrow_mut().clear();
+        self.invalidate_result_cache();
+        self.save_to_file()?;
+        Ok((self.movies.len(), restored_config))
+    }
+
+    // Copy a movie record (and its cached poster) into another library's data file
+    // without re-fetching from TMDB. Returns the id assigned in the other library.
+    fn copy_movie_to(&self, id: u32, target_data_file: &Path) -> Result<u32, String> {
+        let mut movie = self.movies.get(&id).cloned()
+            .ok_or_else(|| format!("Movie {} not found", id))?;
+
+        let same_file = match (std::fs::canonicalize(target_data_file), std::fs::canonicalize(&self.data_file)) {
+            (Ok(target), Ok(current)) => target == current,
+            _ => target_data_file == Path::new(&self.data_file),
+        };
+        if same_file {
+            return Err("Target is the current library".to_string());
+        }
+
+        // Libraries keep their posters in a "posters" folder next to the data file
+        let target_dir = target_data_file.parent().unwrap_or_else(|| Path::new("."));
+        let target_posters_dir = target_dir.join("posters");
+
+        if !movie.poster_path.is_empty() && Path::new(&movie.poster_path).exists() {
+            create_dir_all(&target_posters_dir)
+                .map_err(|e| format!("Failed to create {}: {}", target_posters_dir.display(), e))?;
+            if let Some(file_name) = Path::new(&movie.poster_path).file_name() {
+                let target_poster = target_posters_dir.join(file_name);
+                if !target_poster.exists() {
+                    std::fs::copy(&movie.poster_path, &target_poster)
+                        .map_err(|e| format!("Failed to copy poster: {}", e))?;
+                }
+                movie.poster_path = target_poster.to_string_lossy().to_string();
+            }
+        }
+
+        let mut target = MovieDatabase::open_as_stored(
+            &target_data_file.to_string_lossy(),
+            &target_posters_dir.to_string_lossy(),
+            &self.tmdb_api_key,
+        );
+
+        let new_id = target.allocate_id();
+        movie.id = new_id;
+        target.movies.insert(new_id, movie);
+        target.mark_changed(new_id);
+        target.save_to_file().map_err(|e| e.to_string())?;
+
+        Ok(new_id)
+    }
+
+    // Write the library out. A failure also shows the status bar's retry button until a later
+    // save works; the movies stay in memory either way, so nothing is lost while it's fixed.
+    fn save_to_file(&self) -> std::io::Result<()> {
+        let result = self.write_to_disk();
+        show_save_result(&result);
+        result
+    }
+
+    fn write_to_disk(&self) -> std::io::Result<()> {
+        // The library in memory is empty or partial, and writing it would delete the rest
+        if let Some(e) = &self.load_error {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!(
+                "{} couldn't be loaded ({}), so it isn't being overwritten. Fix or move the file, then restart.",
+                self.data_file, e
+            )));
+        }
+        if self.storage_format == "sqlite" {
+            return self.save_to_sqlite();
+        }
+        // Switching away from SQLite - close it before overwriting the file
+        *self.sqlite.borrow_mut() = None;
+        
+        let json = match self.storage_format.as_str() {
+            // Movies only, sorted by id so the file diffs cleanly
+            "array" | "lines" => {
+                let mut movies: Vec<&Movie> = self.movies.values().collect();
+                movies.sort_by_key(|m| m.id);
+                if self.storage_format == "array" {
+                    serde_json::to_string_pretty(&movies)?
                 } else {
-                    String::from("Not available")
-                };
-                
-                let watch_log_display = if !movie.watch_log.is_empty() {
-                    movie.watch_log.iter()
-                        .map(|entry| {
-                            let rating = entry.rating.map(|r| format!(" - Rated: {}/10", r)).unwrap_or_default();
-                            let comments = if !entry.comments.is_empty() {
-                                format!(" - {}", escape_markup(&entry.comments))
-                            } else {
-                                String::new()
+                    let mut lines = String::new();
+                    for movie in movies {
+                        lines.push_str(&serde_json::to_string(movie)?);
+                        lines.push('\n');
+                    }
+                    lines
+                }
+            }
+            // Serialize entire database to JSON (including cache)
+            _ => serde_json::to_string_pretty(&self)?,
+        };
+        
+        write_file_atomically(Path::new(&self.data_file), json.as_bytes())
+            .map_err(|e| std::io::Error::new(e.kind(), format!("Failed to write to file {}: {}", self.data_file, e)))?;
+        // JSON files are rewritten whole, so there is nothing left to track
+        self.changed_ids.borrow_mut().clear();
+        Ok(())
+    }
+
+    fn save_to_sqlite(&self) -> std::io::Result<()> {
+        let mut sqlite = self.sqlite.borrow_mut();
+        let fresh = sqlite.is_none();
+        if fresh {
+            // One-time migration: keep a JSON movies.db as a backup and start a fresh SQLite file
+            if Path::new(&self.data_file).exists() && !is_sqlite_file(&self.data_file) {
+                let backup = format!("{}.json.bak", self.data_file);
+                std::fs::rename(&self.data_file, &backup)
+                    .map_err(|e| std::io::Error::new(e.kind(), format!("Failed to move {} to {}: {}", self.data_file, backup, e)))?;
+                println!("Migrating {} to SQLite, old file kept as {}", self.data_file, backup);
+            }
+            *sqlite = Some(SqliteStore::open(&self.data_file).map_err(std::io::Error::other)?);
+        }
+        let store = sqlite.as_mut().expect("SQLite store was just opened");
+        
+        // A store that was just opened gets every movie (and loses rows for ones that are
+        // gone); otherwise only the movies changed since the last save are written
+        let ids: Vec<u32> = if fresh {
+            self.movies.keys().chain(store.rows.keys()).copied().collect::<HashSet<u32>>().into_iter().collect()
+        } else {
+            self.changed_ids.borrow().iter().copied().collect()
+        };
+        let mut movies = Vec::new();
+        for id in ids {
+            let data = match self.movies.get(&id) {
+                Some(movie) => Some(serde_json::to_string(movie)?),
+                None => None,
+            };
+            movies.push((id, data));
+        }
+        let mut meta = HashMap::new();
+        meta.insert("next_id".to_string(), self.next_id.to_string());
+        meta.insert("tmdb_cache".to_string(), serde_json::to_string(&self.tmdb_cache)?);
+        store.sync(movies, meta)?;
+        self.changed_ids.borrow_mut().clear();
+        Ok(())
+    }
+
+    fn load_from_sqlite(&mut self) -> Result<(), String> {
+        let mut store = SqliteStore::open(&self.data_file)?;
+        let mut rejected = Vec::new();
+        for (id, data) in &std::mem::take(&mut store.rows) {
+            match serde_json::from_str(data).and_then(movie_from_record) {
+                Ok(movie) => {
+                    self.movies.insert(*id, movie);
+                }
+                Err(e) => {
+                    log_warn!("Warning: Skipping unreadable movie {} in {}: {}", id, self.data_file, e);
+                    rejected.push(data.clone());
+                }
+            }
+        }
+        self.keep_rejected_records(rejected);
+        let max_id = self.movies.keys().max().map_or(0, |id| id + 1);
+        self.next_id = store.meta.get("next_id").and_then(|v| v.parse().ok()).unwrap_or(1).max(max_id);
+        if let Some(cache) = store.meta.get("tmdb_cache") {
+            self.tmdb_cache = serde_json::from_str(cache).unwrap_or_default();
+        }
+        *self.sqlite.borrow_mut() = Some(store);
+        Ok(())
+    }
+
+    fn load_from_file(&mut self) {
+        self.rejected_records = 0;
+        self.load_error = None;
+        if !Path::new(&self.data_file).exists() {
+            return;
+        }
+        
+        if is_sqlite_file(&self.data_file) {
+            if let Err(e) = self.load_from_sqlite() {
+                log_warn!("Warning: Failed to load {}: {}", self.data_file, e);
+                self.load_error = Some(e);
+                return;
+            }
+            self.migrate_poster_paths();
+            if self.storage_format != "sqlite" {
+                // Rewrite in the JSON format picked in Settings
+                if let Err(e) = self.save_to_file() {
+                    log_warn!("Warning: Failed to save database in new format: {}", e);
+                }
+            }
+            return;
+        }
+        
+        self.load_from_json();
+        if self.storage_format == "sqlite" && self.load_error.is_none() {
+            if let Err(e) = self.save_to_file() {
+                log_warn!("Warning: Failed to migrate database to SQLite: {}", e);
+            }
+        }
+    }
+
+    // Read a JSON database object, a JSON array of movies or line-delimited JSON. Movies are
+    // read one record at a time, so one bad record is skipped (and kept aside) instead of
+    // losing the whole file.
+    fn load_from_json(&mut self) {
+        let contents = match std::fs::read_to_string(&self.data_file) {
+            Ok(contents) => contents,
+            Err(e) => {
+                log_warn!("Warning: Failed to load {}: {}", self.data_file, e);
+                self.load_error = Some(e.to_string());
+                return;
+            }
+        };
+        let mut records = Vec::new();
+        let mut rejected = Vec::new();
+        
+        match serde_json::from_str::<serde_json::Value>(&contents) {
+            // A line-delimited file with a single movie is also one object, so check for "movies"
+            Ok(serde_json::Value::Object(root)) if root.contains_key("movies") => {
+                // Entire database as JSON
+                if let Some(serde_json::Value::Object(movies)) = root.get("movies") {
+                    for (id, record) in movies {
+                        match id.parse::<u32>() {
+                            Ok(id) => records.push((Some(id), record.clone())),
+                            Err(_) => rejected.push(record.to_string()),
+                        }
+                    }
+                }
+                if let Some(next_id) = root.get("next_id").and_then(|v| v.as_u64()) {
+                    self.next_id = next_id as u32;
+                }
+                if let Some(cache) = root.get("tmdb_cache") {
+                    self.tmdb_cache = serde_json::from_value(cache.clone()).unwrap_or_default();
+                }
+            }
+            // JSON array of movies
+            Ok(serde_json::Value::Array(movies)) => {
+                records.extend(movies.into_iter().map(|record| (None, record)));
+            }
+            // Old format (line-by-line movies), kept for backwards compatibility
+            _ => {
+                for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+                    match serde_json::from_str(line) {
+                        Ok(record) => records.push((None, record)),
+                        Err(e) => {
+                            log_warn!("Warning: Skipping unreadable line in {}: {}", self.data_file, e);
+                            rejected.push(line.to_string());
+                        }
+                    }
+                }
+            }
+        }
+        
+        for (key, record) in records {
+            match movie_from_record(record.clone()) {
+                Ok(mut movie) => {
+                    if let Some(id) = key {
+                        movie.id = id;
+                    }
+                    self.next_id = self.next_id.max(movie.id + 1);
+                    self.movies.insert(movie.id, movie);
+                }
+                Err(e) => {
+                    log_warn!("Warning: Skipping unreadable movie in {}: {}", self.data_file, e);
+                    rejected.push(record.to_string());
+                }
+            }
+        }
+        self.keep_rejected_records(rejected);
+        
+        // Migrate old poster paths to new location
+        self.migrate_poster_paths();
+    }
+
+    // Append records that failed to load to "<data_file>.rejected", one per line, so they
+    // can be fixed by hand rather than disappearing with the next save. Records already in
+    // the file from an earlier load aren't written twice.
+    fn keep_rejected_records(&mut self, rejected: Vec<String>) {
+        self.rejected_records = rejected.len();
+        if rejected.is_empty() {
+            return;
+        }
+        let path = format!("{}.rejected", self.data_file);
+        let existing = std::fs::read_to_string(&path).unwrap_or_default();
+        let kept: HashSet<&str> = existing.lines().collect();
+        let new_lines: String = rejected.iter()
+            .filter(|record| !kept.contains(record.as_str()))
+            .map(|record| format!("{}\n", record))
+            .collect();
+        let result = std::fs::OpenOptions::new().create(true).append(true).open(&path)
+            .and_then(|mut file| std::io::Write::write_all(&mut file, new_lines.as_bytes()));
+        match result {
+            Ok(()) => log_warn!("Warning: {} unreadable record(s) in {} skipped and copied to {}", rejected.len(), self.data_file, path),
+            Err(e) => log_warn!("Warning: Failed to write {}: {}", path, e),
+        }
+    }
+
+    fn migrate_poster_paths(&mut self) {
+        // Update old poster paths to use new location
+        let mut needs_save = false;
+        
+        for movie in self.movies.values_mut() {
+            if !movie.poster_path.is_empty() {
+                // Check if using old "posters/" or "~/posters/" path
+                if movie.poster_path.starts_with("posters/") || movie.poster_path.starts_with("~/posters/") {
+                    // Extract just the filename
+                    if let Some(filename) = movie.poster_path.split('/').last() {
+                        // Update to new path
+                        let new_path = format!("{}/{}", self.posters_dir, filename);
+                        movie.poster_path = new_path;
+                        self.changed_ids.borrow_mut().insert(movie.id);
+                        needs_save = true;
+                    }
+                }
+            }
+        }
+        
+        // Save if we made any changes
+        if needs_save {
+            if let Err(e) = self.save_to_file() {
+                log_warn!("Warning: Failed to save after poster path migration: {}", e);
+            }
+        }
+    }
+
+    fn list_all(&self) -> Vec<Movie> {
+        let mut movies: Vec<Movie> = self.movies.values().cloned().collect();
+        movies.sort_by(|a, b| a.title.cmp(&b.title));
+        movies
+    }
+    
+    fn compute_statistics(&self) -> Stats {
+        let movies: Vec<&Movie> = self.movies.values().collect();
+        let total_movies = movies.len();
+        let total_runtime: u32 = movies.iter().map(|m| m.runtime as u32).sum();
+        let avg_runtime = if total_movies > 0 { total_runtime / total_movies as u32 } else { 0 };
+        
+        let avg_rating: f32 = if total_movies > 0 {
+            movies.iter().map(|m| m.rating).sum::<f32>() / total_movies as f32
+        } else {
+            0.0
+        };
+        
+        let oldest_year = movies.iter().filter(|m| m.year > 0).map(|m| m.year).min().unwrap_or(0);
+        let newest_year = movies.iter().map(|m| m.year).max().unwrap_or(0);
+        
+        let genres = top_counts(movies.iter().flat_map(|m| m.genre.iter().map(String::as_str)), usize::MAX);
+        
+        // Most frequent people; a movie's cast_details replace its older plain cast list
+        let top_actors = top_counts(movies.iter().flat_map(|m| {
+            let names: Vec<&str> = if m.cast_details.is_empty() {
+                m.cast.iter().map(String::as_str).collect()
+            } else {
+                m.cast_details.iter().map(|c| c.name.as_str()).collect()
+            };
+            names
+        }), 10);
+        let top_directors = top_counts(movies.iter().flat_map(|m| m.director.split(',').map(str::trim))
+            .filter(|name| *name != "Unknown" && *name != "N/A"), 10);
+        
+        let mut decade_counts: HashMap<u16, usize> = HashMap::new();
+        for movie in &movies {
+            if movie.year > 0 {
+                *decade_counts.entry((movie.year / 10) * 10).or_insert(0) += 1;
+            }
+        }
+        let mut decades: Vec<(u16, usize)> = decade_counts.into_iter().collect();
+        decades.sort_by_key(|(decade, _)| *decade);
+        
+        let mut top_rated = movies.clone();
+        top_rated.sort_by(|a, b| b.rating.partial_cmp(&a.rating).unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.title.cmp(&b.title)));
+        let top_rated = top_rated.iter()
+            .take(100)
+            .map(|m| (m.title.clone(), m.year, m.rating))
+            .collect();
+        
+        Stats {
+            total_movies,
+            avg_rating,
+            total_runtime,
+            avg_runtime,
+            oldest_year,
+            newest_year,
+            top_rated,
+            genres,
+            top_actors,
+            top_directors,
+            decades,
+        }
+    }
+    
+    // TMDB Cache methods
+    fn get_cached_search(&self, query: &str) -> Option<Vec<TMDBSearchRow>> {
+        let cache_max_age_days = 30;  // Cache expires after 30 days
+        
+        if let Some(cached) = self.tmdb_cache.get(query) {
+            if !cached.is_expired(cache_max_age_days) {
+                return Some(cached.results.clone());
+            }
+        }
+        None
+    }
+    
+    fn cache_search_results(&mut self, query: String, results: Vec<TMDBSearchRow>) {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        
+        let cached = CachedTMDBSearch {
+            query: query.clone(),
+            results,
+            timestamp,
+        };
+        
+        self.tmdb_cache.insert(query, cached);
+        // Save to persist cache
+        if let Err(e) = self.save_to_file() {
+            log_warn!("Warning: Failed to save TMDB cache: {}", e);
+        }
+    }
+    
+    // Movies matching the list filters, sorted, using the result cache
+    fn filtered_movies(&self, filters: &ListFilters) -> Vec<Movie> {
+        // Create cache key from current filters
+        let cache_key = filters.cache_key();
+        
+        // Check cache first
+        if let Some(cached) = self.get_cached_results(&cache_key) {
+            return cached;
+        }
+        
+        // Cache miss - compute results
+        let mut results = if filters.search_query.is_empty() {
+            self.search_by_genres(&filters.genres, filters.match_all_genres)
+        } else if filters.search_everything {
+            self.search_all(&filters.search_query)
+        } else {
+            self.search_by_title(&filters.search_query)
+        };
+        
+        // Year and rating narrow whatever the title/genre search found
+        let in_years = filters.year_range.map(|(from, to)| self.filter_by_year_range(from, to));
+        let rated = (filters.min_rating > 0.0).then(|| self.filter_by_min_rating(filters.min_rating));
+        results.retain(|m| {
+            filters.matches_runtime(m)
+                && in_years.as_ref().is_none_or(|ids| ids.contains(&m.id))
+                && rated.as_ref().is_none_or(|ids| ids.contains(&m.id))
+                && (!filters.favorites_only || m.favorite)
+                && filters.watched.is_none_or(|watched| m.watched == watched)
+                && (!filters.missing_files_only || file_missing(m))
+                && filters.tags.iter().all(|tag| m.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+                && filters.collection_id.is_none_or(|id| m.collection_id == id)
+                && (filters.certifications.is_empty() || filters.certifications.contains(&m.certification))
+        });
+        
+        // Apply sorting
+        match filters.sort_by.as_str() {
+            "Title (A-Z)" => {
+                results.sort_by(|a, b| a.title.cmp(&b.title));
+            }
+            "Year (Newest)" => {
+                results.sort_by(|a, b| b.year.cmp(&a.year));
+            }
+            "Year (Oldest)" => {
+                results.sort_by(|a, b| a.year.cmp(&b.year));
+            }
+            "Rating (High-Low)" => {
+                results.sort_by(|a, b| b.rating.partial_cmp(&a.rating).unwrap_or(std::cmp::Ordering::Equal));
+            }
+            "Rating (Low-High)" => {
+                results.sort_by(|a, b| a.rating.partial_cmp(&b.rating).unwrap_or(std::cmp::Ordering::Equal));
+            }
+            // Unrated movies (None) count as lowest in both directions
+            "My Rating (High-Low)" => {
+                results.sort_by(|a, b| my_rating_key(b).partial_cmp(&my_rating_key(a)).unwrap_or(std::cmp::Ordering::Equal));
+            }
+            "My Rating (Low-High)" => {
+                results.sort_by(|a, b| my_rating_key(a).partial_cmp(&my_rating_key(b)).unwrap_or(std::cmp::Ordering::Equal));
+            }
+            // Records without a timestamp (0) count as oldest, ordered by id among themselves
+            "Date Added (Newest)" => {
+                results.sort_by(|a, b| (b.date_added, b.id).cmp(&(a.date_added, a.id)));
+            }
+            "Date Added (Oldest)" => {
+                results.sort_by(|a, b| (a.date_added, a.id).cmp(&(b.date_added, b.id)));
+            }
+            // Unknown runtimes (0) go last in both directions
+            "Runtime (Shortest)" => {
+                results.sort_by(|a, b| (a.runtime == 0).cmp(&(b.runtime == 0)).then(a.runtime.cmp(&b.runtime)));
+            }
+            "Runtime (Longest)" => {
+                results.sort_by(|a, b| (a.runtime == 0).cmp(&(b.runtime == 0)).then(b.runtime.cmp(&a.runtime)));
+            }
+            // Never-played movies (None) go last, by title
+            "Recently Watched" => {
+                results.sort_by(|a, b| b.last_watched.cmp(&a.last_watched).then_with(|| a.title.cmp(&b.title)));
+            }
+            // Films of the same collection together in release order, collections by name;
+            // films that aren't in one go last, by title. Unknown years (0) end their collection.
+            "Collection order" => {
+                results.sort_by_cached_key(|m| {
+                    let in_collection = !m.collection_name.is_empty();
+                    let year = if in_collection && m.year != 0 { m.year } else { u16::MAX };
+                    (!in_collection, m.collection_name.to_lowercase(), year, m.title.clone())
+                });
+            }
+            _ => {}
+        }
+        
+        // Cache the results
+        self.cache_results(cache_key, results.clone());
+        results
+    }
+
+    // Set the watched flag on each listed movie, saving once. Returns how many were marked.
+    fn mark_watched(&mut self, ids: &[u32]) -> usize {
+        let mut marked = 0;
+        for id in ids {
+            if let Some(movie) = self.movie_mut(*id).filter(|m| !m.watched) {
+                movie.watched = true;
+                marked += 1;
+            }
+        }
+        if marked > 0 {
+            self.invalidate_result_cache();
+            if let Err(e) = self.save_to_file() {
+                log_warn!("Warning: Failed to save watched movies: {}", e);
+            }
+        }
+        marked
+    }
+
+    // Add tags to each listed movie, skipping ones it already has (ignoring case) and saving
+    // once. Returns how many movies gained a tag.
+    fn add_tags(&mut self, ids: &[u32], tags: &[String]) -> usize {
+        let mut tagged = 0;
+        for id in ids {
+            let Some(movie) = self.movie_mut(*id) else {
+                continue;
+            };
+            let before = movie.tags.len();
+            for tag in tags {
+                if !movie.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                    movie.tags.push(tag.clone());
+                }
+            }
+            if movie.tags.len() > before {
+                tagged += 1;
+            }
+        }
+        if tagged > 0 {
+            self.invalidate_result_cache();
+            if let Err(e) = self.save_to_file() {
+                log_warn!("Warning: Failed to save tags: {}", e);
+            }
+        }
+        tagged
+    }
+
+    // Result cache methods for search/filter/sort
+    fn get_cached_results(&self, cache_key: &str) -> Option<Vec<Movie>> {
+        self.result_cache.borrow().get(cache_key).cloned()
+    }
+    
+    fn cache_results(&self, cache_key: String, results: Vec<Movie>) {
+        self.result_cache.borrow_mut().insert(cache_key, results);
+    }
+    
+    fn invalidate_result_cache(&self) {
+        self.result_cache.borrow_mut().clear();
+    }
+    
+    // Note a movie to write on the next SQLite save
+    fn mark_changed(&self, id: u32) {
+        self.changed_ids.borrow_mut().insert(id);
+    }
+    
+    // A movie to modify in place, noted for the next save
+    fn movie_mut(&mut self, id: u32) -> Option<&mut Movie> {
+        self.forget_last_deleted();
+        self.mark_changed(id);
+        self.movies.get_mut(&id)
+    }
+
+    // Any change other than the delete itself ends the chance to undo it, so undo_delete
+    // never puts back a movie over a library that has moved on
+    fn forget_last_deleted(&mut self) {
+        if self.last_deleted.take().is_some() {
+            hide_undo_delete();
+        }
+    }
+}
+
+// TMDB's canonical movie genre names
+const TMDB_GENRES: [&str; 19] = [
+    "Action", "Adventure", "Animation", "Comedy", "Crime", "Documentary", "Drama", "Family", "Fantasy",
+    "History", "Horror", "Music", "Mystery", "Romance", "Science Fiction", "TV Movie", "Thriller", "War", "Western",
+];
+
+// Genre choices for the edit dialog: TMDB's list followed by any custom genres already in the library
+fn genre_choices(library_genres: &[String]) -> Vec<String> {
+    let mut choices: Vec<String> = TMDB_GENRES.iter().map(|g| g.to_string()).collect();
+    for genre in library_genres {
+        if genre != "Unknown" && !choices.iter().any(|g| g.eq_ignore_ascii_case(genre)) {
+            choices.push(genre.clone());
+        }
+    }
+    choices
+}
+
+// Button label for the genre filter
+fn genre_filter_label(genres: &[String], match_all: bool) -> String {
+    match genres {
+        [] => "All genres".to_string(),
+        [genre] => genre.clone(),
+        _ if match_all => format!("{} genres (all)", genres.len()),
+        _ => format!("{} genres (any)", genres.len()),
+    }
+}
+
+// US film and TV age ratings from youngest audience up, for ordering the rating filter
+const CERTIFICATION_ORDER: [&str; 12] = ["G", "TV-Y", "TV-Y7", "TV-G", "PG", "TV-PG", "PG-13", "TV-14", "R", "TV-MA", "NC-17", "NR"];
+
+fn certification_filter_label(certifications: &[String]) -> String {
+    match certifications {
+        [] => "Any rating".to_string(),
+        [certification] => certification.clone(),
+        _ => format!("{} ratings", certifications.len()),
+    }
+}
+
+fn collection_filter_label(collection: Option<&(u32, String)>) -> String {
+    collection.map_or_else(|| "All collections".to_string(), |(_, name)| name.clone())
+}
+
+fn tag_filter_label(tags: &[String]) -> String {
+    match tags {
+        [] => "All tags".to_string(),
+        [tag] => tag.clone(),
+        _ => format!("{} tags", tags.len()),
+    }
+}
+
+// Lowest year the year filter offers; its upper end is a couple of years past the current one
+const MIN_FILTER_YEAR: u16 = 1870;
+
+fn max_filter_year() -> u16 {
+    (chrono::Datelike::year(&chrono::Local::now()) + 2) as u16
+}
+
+// The spin button values as a year filter, lowest year first; the full span means no filter
+fn year_range_filter(from: u16, to: u16) -> Option<(u16, u16)> {
+    let (from, to) = (from.min(to), from.max(to));
+    if from <= MIN_FILTER_YEAR && to >= max_filter_year() {
+        None
+    } else {
+        Some((from, to))
+    }
+}
+
+fn year_rating_filter_label(year_range: Option<(u16, u16)>, min_rating: f32) -> String {
+    let years = match year_range {
+        None => "Any year".to_string(),
+        Some((from, to)) if from == to => from.to_string(),
+        Some((from, to)) => format!("{}–{}", from, to),
+    };
+    if min_rating > 0.0 {
+        format!("{}, ⭐ {:.1}+", years, min_rating)
+    } else {
+        years
+    }
+}
+
+const SORT_OPTIONS: [&str; 13] = ["Title (A-Z)", "Year (Newest)", "Year (Oldest)", "Rating (High-Low)", "Rating (Low-High)", "My Rating (High-Low)", "My Rating (Low-High)", "Date Added (Newest)", "Date Added (Oldest)", "Runtime (Shortest)", "Runtime (Longest)", "Recently Watched", "Collection order"];
+
+// Save the sort and genre filter so the next launch opens the list the same way
+fn remember_list_filters(filters: &ListFilters) {
+    let mut config = load_config().unwrap_or_default();
+    if config.last_sort == filters.sort_by
+        && config.last_genres == filters.genres
+        && config.last_match_all_genres == filters.match_all_genres
+    {
+        return;
+    }
+    config.last_sort = filters.sort_by.clone();
+    config.last_genres = filters.genres.clone();
+    config.last_match_all_genres = filters.match_all_genres;
+    if let Err(e) = save_config(&config) {
+        log_warn!("Warning: Failed to save list filters: {}", e);
+    }
+}
+
+// Pause in typing before the search entry refreshes the list
+const SEARCH_DEBOUNCE_MS: u64 = 250;
+
+// Search entry scopes: label and whether to search everything rather than titles only
+const SEARCH_SCOPES: [(&str, bool); 2] = [
+    ("Title only", false),
+    ("Everything", true),
+];
+
+// Lowercased title, director, cast names and description, for "Everything" searches
+fn searchable_text(movie: &Movie) -> String {
+    let mut text = vec![movie.title.as_str(), movie.director.as_str(), movie.description.as_str()];
+    text.extend(movie.cast.iter().map(String::as_str));
+    text.extend(movie.cast_details.iter().map(|c| c.name.as_str()));
+    text.join("\n").to_lowercase()
+}
+
+// Watched status filter: label and required watched flag (None = any)
+const WATCHED_FILTERS: [(&str, Option<bool>); 3] = [
+    ("All", None),
+    ("Watched", Some(true)),
+    ("Unwatched", Some(false)),
+];
+
+// "Fits my evening" presets: label and maximum runtime in minutes (None = any length)
+const RUNTIME_LIMITS: [(&str, Option<u16>); 5] = [
+    ("Any length", None),
+    ("Up to 90 min", Some(90)),
+    ("Up to 100 min", Some(100)),
+    ("Up to 120 min", Some(120)),
+    ("Up to 150 min", Some(150)),
+];
+
+// Current state of the search, filter and sort controls
+#[derive(Debug, Clone, Default)]
+struct ListFilters {
+    search_query: String,
+    search_everything: bool,  // Match the query against director, cast and description too
+    genres: Vec<String>,  // Empty = all genres
+    match_all_genres: bool,  // Movies need every selected genre rather than any of them
+    tags: Vec<String>,  // Movies need every selected tag; empty = no tag filter
+    collection_id: Option<u32>,  // Only movies of this TMDB collection
+    certifications: Vec<String>,  // Movies need one of these age ratings; empty = no rating filter
+    sort_by: String,
+    max_runtime: Option<u16>,
+    include_unknown_runtime: bool,  // Keep runtime == 0 movies when a limit is set
+    year_range: Option<(u16, u16)>,  // Inclusive; movies with an unknown year are left out
+    min_rating: f32,  // 0 = no rating filter
+    favorites_only: bool,
+    watched: Option<bool>,  // None = any, Some(true) = watched only, Some(false) = unwatched only
+    missing_files_only: bool,  // Movies without a playable file, to re-associate
+}
+
+impl ListFilters {
+    fn matches_runtime(&self, movie: &Movie) -> bool {
+        match self.max_runtime {
+            None => true,
+            Some(_) if movie.runtime == 0 => self.include_unknown_runtime,
+            Some(max) => movie.runtime <= max,
+        }
+    }
+
+    // Key for the filtered/sorted result cache
+    fn cache_key(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+// Messages from the Fix Missing Posters worker
+enum PosterProgress {
+    // One movie checked, with (poster_url, poster_path) if its poster was downloaded
+    Checked(u32, Option<(String, String)>),
+    // Every movie has been checked
+    Done,
+}
+
+// A poster is usable if its cached file still exists on disk
+fn has_usable_poster(movie: &Movie) -> bool {
+    !movie.poster_path.is_empty() && Path::new(&movie.poster_path).exists()
+}
+
+// Download a missing poster, using the stored URL or looking it up again from the TMDB id.
+// Every request takes a permit from `limiter`, so many can run at once within the limit.
+async fn fetch_missing_poster_async(
+    client: &reqwest::Client,
+    limiter: &tokio::sync::Semaphore,
+    movie: &Movie,
+    api_key: &str,
+    posters_dir: &str,
+) -> Option<(String, String)> {
+    let poster_url = if !movie.poster_url.is_empty() {
+        movie.poster_url.clone()
+    } else if movie.tmdb_id != 0 && !api_key.is_empty() {
+        let details_url = format!(
+            "https://api.themoviedb.org/3/{}/{}?api_key={}{}",
+            movie.media_type.tmdb_path(), movie.tmdb_id, api_key, tmdb_language_param()
+        );
+        let poster_path = match movie.media_type {
+            MediaType::Movie => tmdb_get_json::<TMDBMovieDetails>(client, limiter, &details_url).await?.poster_path,
+            MediaType::Tv => tmdb_get_json::<TMDBTvDetails>(client, limiter, &details_url).await?.poster_path,
+        };
+        format!("https://image.tmdb.org/t/p/original{}", poster_path?)
+    } else {
+        return None;
+    };
+    
+    // Same file names as scans use, so a later refresh overwrites rather than duplicates
+    let poster_key = match (movie.media_type, movie.tmdb_id) {
+        (_, 0) => movie.id.to_string(),
+        (MediaType::Movie, tmdb_id) => tmdb_id.to_string(),
+        (MediaType::Tv, tmdb_id) => format!("tv_{}", tmdb_id),
+    };
+    let permit = limiter.acquire().await.ok()?;
+    let bytes = client.get(&poster_url).send().await.ok()?
+        .error_for_status().ok()?
+        .bytes().await.ok()?;
+    drop(permit);
+    
+    let poster_path = format!("{}/poster_{}.jpg", posters_dir, poster_key);
+    let posters_dir = posters_dir.to_string();
+    let write_path = poster_path.clone();
+    tokio::task::spawn_blocking(move || {
+        create_dir_all(&posters_dir)?;
+        std::fs::write(&write_path, bytes)
+    }).await.ok()?.ok()?;
+    
+    Some((poster_url, poster_path))
+}
+
+fn create_movie_row(movie: &Movie, poster_cache: &Rc<RefCell<HashMap<u32, Pixbuf>>>) -> gtk::ListBoxRow {
+    let row = gtk::ListBoxRow::new();
+    
+    // Store the movie ID in the row's name property for later retrieval
+    row.set_widget_name(&movie.id.to_string());
+    
+    let hbox = Box::new(Orientation::Horizontal, 12);
+    hbox.set_margin_start(12);
+    hbox.set_margin_end(12);
+    hbox.set_margin_top(8);
+    hbox.set_margin_bottom(8);
+
+    // Add poster thumbnail
+    let (thumb_width, thumb_height) = LIST_THUMB_SIZE.with(|size| size.get());
+    let poster_container = gtk::Overlay::new();
+    poster_container.set_size_request(thumb_width, thumb_height);
+    
+    let poster_box = Box::new(Orientation::Vertical, 0);
+    poster_box.set_size_request(thumb_width, thumb_height);
+    
+    let cached = poster_cache.borrow().get(&movie.id).cloned();
+    if let Some(thumbnail) = cached {
+        // Use cached thumbnail directly (FAST!)
+        let picture = Picture::for_pixbuf(&thumbnail);
+        picture.set_can_shrink(true);
+        poster_box.append(&picture);
+    } else {
+        // Placeholder for missing poster, or until the thumbnail has loaded
+        let placeholder = Label::new(Some("🎬"));
+        placeholder.set_markup("<span size='xx-large'>🎬</span>");
+        poster_box.append(&placeholder);
+        
+        if !movie.poster_path.is_empty() && Path::new(&movie.poster_path).exists() {
+            // Load at thumbnail size BEFORE caching (saves 1500x memory!)
+            let poster_cache = poster_cache.clone();
+            let movie_id = movie.id;
+            load_poster_async(&movie.poster_path, (thumb_width, thumb_height, true), &poster_box, &placeholder, move |thumbnail| {
+                poster_cache.borrow_mut().insert(movie_id, thumbnail.clone());
+            });
+        }
+    }
+    
+    poster_container.set_child(Some(&poster_box));
+    
+    // Add "SEEN" badge if movie has been watched
+    if movie.watched {
+        let seen_label = Label::new(Some("SEEN"));
+        seen_label.set_markup("<span foreground='red' weight='bold' size='large'>SEEN</span>");
+        seen_label.set_halign(Align::Center);
+        seen_label.set_valign(Align::Center);
+        poster_container.add_overlay(&seen_label);
+    }
+    
+    // Warning badge when there's nothing to play
+    if file_missing(movie) {
+        let missing_label = Label::new(Some("⚠️"));
+        missing_label.set_tooltip_text(Some(if movie.file_paths.is_empty() {
+            "No video file associated"
+        } else {
+            "Video file not found"
+        }));
+        missing_label.set_halign(Align::End);
+        missing_label.set_valign(Align::Start);
+        poster_container.add_overlay(&missing_label);
+    }
+    
+    hbox.append(&poster_container);
+
+    let vbox = Box::new(Orientation::Vertical, 4);
+    
+    let title_label = Label::new(Some(&format!("{} ({})", movie.title, movie.year)));
+    title_label.set_xalign(0.0);
+    // Escape special characters for Pango markup
+    let escaped_title = escape_markup(&movie.title);
+    let favorite_mark = if movie.favorite { " <span foreground='red'>♥</span>" } else { "" };
+    let watched_mark = if movie.watched { "<span foreground='green'>✓</span> " } else { "" };
+    title_label.set_markup(&format!("{}<b>{}</b> ({}){}", watched_mark, escaped_title, movie.year, favorite_mark));
+    
+    let certification = if movie.certification.is_empty() { String::new() } else { format!("{} | ", movie.certification) };
+    let info_label = Label::new(Some(&format!("⭐ {:.1}/10 | {}{} | {}", 
+        movie.rating, certification, movie.genre.join(", "), length_summary(movie))));
+    info_label.set_xalign(0.0);
+    info_label.set_opacity(0.7);
+    
+    let director_label = Label::new(Some(&format!("{}: {}", director_heading(movie), movie.director)));
+    director_label.set_xalign(0.0);
+    director_label.set_opacity(0.6);
+
+    vbox.append(&title_label);
+    vbox.append(&info_label);
+    vbox.append(&director_label);
+    
+    hbox.append(&vbox);
+    row.set_child(Some(&hbox));
+    
+    row
+}
+
+// Raw pixels of a decoded poster: (bytes, has_alpha, width, height, rowstride).
+// Pixbuf isn't Send, so the loader threads hand these back instead.
+type DecodedPoster = (glib::Bytes, bool, i32, i32, i32);
+
+struct PosterRequest {
+    path: String,
+    width: i32,
+    height: i32,
+    preserve_aspect: bool,
+    reply: async_channel::Sender<DecodedPoster>,
+}
+
+const POSTER_LOADER_THREADS: usize = 4;
+
+// Queue feeding a few poster decoding threads, started on first use
+fn poster_loader() -> &'static async_channel::Sender<PosterRequest> {
+    static LOADER: std::sync::OnceLock<async_channel::Sender<PosterRequest>> = std::sync::OnceLock::new();
+    LOADER.get_or_init(|| {
+        let (sender, receiver) = async_channel::unbounded::<PosterRequest>();
+        for _ in 0..POSTER_LOADER_THREADS {
+            let receiver = receiver.clone();
+            std::thread::spawn(move || {
+                while let Ok(request) = receiver.recv_blocking() {
+                    if let Ok(pixbuf) = Pixbuf::from_file_at_scale(&request.path, request.width, request.height, request.preserve_aspect) {
+                        let _ = request.reply.send_blocking((
+                            pixbuf.read_pixel_bytes(),
+                            pixbuf.has_alpha(),
+                            pixbuf.width(),
+                            pixbuf.height(),
+                            pixbuf.rowstride(),
+                        ));
+                    }
+                }
+            });
+        }
+        sender
+    })
+}
+
+// Decode a poster at (width, height, preserve aspect) on a worker thread, then swap it into
+// `poster_box` in place of `placeholder` and pass it to `on_loaded`. If decoding fails the
+// placeholder just stays.
+fn load_poster_async(
+    poster_path: &str,
+    (width, height, preserve_aspect): (i32, i32, bool),
+    poster_box: &Box,
+    placeholder: &Label,
+    on_loaded: impl FnOnce(&Pixbuf) + 'static,
+) {
+    let (sender, receiver) = async_channel::bounded(1);
+    let request = PosterRequest { path: poster_path.to_string(), width, height, preserve_aspect, reply: sender };
+    if poster_loader().send_blocking(request).is_err() {
+        return;
+    }
+    
+    let poster_box = poster_box.clone();
+    let placeholder = placeholder.clone();
+    glib::spawn_future_local(async move {
+        if let Ok((bytes, has_alpha, width, height, rowstride)) = receiver.recv().await {
+            let pixbuf = Pixbuf::from_bytes(&bytes, gtk::gdk_pixbuf::Colorspace::Rgb, has_alpha, 8, width, height, rowstride);
+            on_loaded(&pixbuf);
+            let picture = Picture::for_pixbuf(&pixbuf);
+            picture.set_can_shrink(true);
+            poster_box.remove(&placeholder);
+            poster_box.append(&picture);
+        }
+    });
+}
+
+// Replace the buttons in `links_row` with one per configured link template the movie has
+// the IDs for. The row is hidden when there are none, or when no movie is given.
+fn fill_link_buttons(links_row: &Box, movie: Option<&Movie>) {
+    while let Some(child) = links_row.first_child() {
+        links_row.remove(&child);
+    }
+    let links: Vec<(String, String)> = match movie {
+        Some(movie) => load_config().unwrap_or_default().link_templates.iter()
+            .filter_map(|link| link.resolve(movie).map(|url| (link.name.clone(), url)))
+            .collect(),
+        None => Vec::new(),
+    };
+    links_row.set_visible(!links.is_empty());
+    for (name, url) in links {
+        let link_btn = Button::with_label(&format!("🔗 {}", name));
+        link_btn.set_tooltip_text(Some(&url));
+        link_btn.connect_clicked(move |button| {
+            let parent = button.root().and_downcast::<Window>();
+            gtk::UriLauncher::new(&url).launch(parent.as_ref(), None::<&gtk::gio::Cancellable>, |result| {
+                if let Err(e) = result {
+                    log_warn!("Warning: Failed to open link: {}", e);
+                }
+            });
+        });
+        links_row.append(&link_btn);
+    }
+}
+
+// Label of the details pane's favorite toggle for a movie's current state
+fn favorite_button_label(favorite: bool) -> &'static str {
+    if favorite { "♥ Favorite" } else { "☆ Favorite" }
+}
+
+// Label of the details pane's watched toggle for a movie's current state
+fn watched_button_label(watched: bool) -> &'static str {
+    if watched { "✓ Watched" } else { "✓ Mark Watched" }
+}
+
+// Rebuild a list row's contents after its movie changed (e.g. favorite toggled)
+fn refresh_row_contents(
+    row: &gtk::ListBoxRow,
+    db: &Rc<RefCell<MovieDatabase>>,
+    movie_id: u32,
+    poster_cache: &Rc<RefCell<HashMap<u32, Pixbuf>>>,
+) {
+    let Some(movie) = db.borrow().movies.get(&movie_id).cloned() else {
+        return;
+    };
+    let fresh = create_movie_row(&movie, poster_cache);
+    let child = fresh.child();
+    fresh.set_child(None::<&gtk::Widget>);
+    row.set_child(child.as_ref());
+}
+
+// Redraw a movie's row in the list and its tile in the grid, wherever they are shown
+fn refresh_movie_in_views(
+    list_box: &ListBox,
+    grid_flow: &gtk::FlowBox,
+    db: &Rc<RefCell<MovieDatabase>>,
+    movie_id: u32,
+    poster_cache: &Rc<RefCell<HashMap<u32, Pixbuf>>>,
+) {
+    let name = movie_id.to_string();
+    let mut child = list_box.first_child();
+    while let Some(widget) = child {
+        child = widget.next_sibling();
+        if let Some(row) = widget.downcast_ref::<gtk::ListBoxRow>().filter(|row| row.widget_name() == name) {
+            refresh_row_contents(row, db, movie_id, poster_cache);
+        }
+    }
+    let Some(movie) = db.borrow().movies.get(&movie_id).cloned() else {
+        return;
+    };
+    let mut child = grid_flow.first_child();
+    while let Some(widget) = child {
+        child = widget.next_sibling();
+        if let Some(item) = widget.downcast_ref::<gtk::FlowBoxChild>().filter(|item| item.widget_name() == name) {
+            let fresh = create_movie_grid_item(&movie, poster_cache);
+            let content = fresh.child();
+            fresh.set_child(None::<&gtk::Widget>);
+            item.set_child(content.as_ref());
+        }
+    }
+}
+
+fn create_movie_row_with_context(
+    movie: &Movie,
+    poster_cache: &Rc<RefCell<HashMap<u32, Pixbuf>>>,
+    db: &Rc<RefCell<MovieDatabase>>,
+) -> gtk::ListBoxRow {
+    let row = create_movie_row(movie, poster_cache);
+    
+    // Add right-click context menu
+    let gesture = gtk::GestureClick::new();
+    gesture.set_button(3); // Right mouse button
+    
+    let movie_id = movie.id;
+    let movie_title = movie.title.clone();
+    let db_clone = db.clone();
+    let row_clone = row.clone();
+    let poster_cache = poster_cache.clone();
+    
+    gesture.connect_released(move |_, _, x, y| {
+        // Select just this row first so the "win." actions below act on this movie
+        if let Some(list_box) = row_clone.parent().and_downcast::<ListBox>() {
+            list_box.unselect_all();
+            list_box.select_row(Some(&row_clone));
+        }
+        
+        let menu_model = gtk::gio::Menu::new();
+        menu_model.append(Some("▶️ Play in VLC"), Some("win.play-selected"));
+        menu_model.append(Some("ℹ️ View Details"), Some("movie.details"));
+        menu_model.append(Some("🔄 Refresh Metadata"), Some("win.refresh-selected"));
+        menu_model.append(Some("✏️ Edit Metadata"), Some("win.edit-selected"));
+        menu_model.append(Some("🎞️ Wrong Movie?"), Some("win.wrong-movie-selected"));
+        let is_favorite = db_clone.borrow().movies.get(&movie_id).map(|m| m.favorite).unwrap_or(false);
+        menu_model.append(Some(if is_favorite { "♡ Remove from Favorites" } else { "♥ Add to Favorites" }), Some("movie.favorite"));
+        let is_watched = db_clone.borrow().movies.get(&movie_id).map(|m| m.watched).unwrap_or(false);
+        menu_model.append(Some(if is_watched { "✗ Mark Unwatched" } else { "✓ Mark Watched" }), Some("movie.watched"));
+        menu_model.append(Some("🎬 View Collection"), Some("movie.collection"));
+        menu_model.append(Some("📋 Copy to Another Library..."), Some("movie.copy"));
+        menu_model.append(Some("🗑️ Delete Movie Metadata"), Some("movie.delete"));
+        
+        let menu = gtk::PopoverMenu::from_model(Some(&menu_model));
+        menu.set_parent(&row_clone);
+        menu.set_pointing_to(Some(&gtk::gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+        
+        // Create action group
+        let actions = gtk::gio::SimpleActionGroup::new();
+        
+        // View Details action
+        let details_action = gtk::gio::SimpleAction::new("details", None);
+        let db_clone2 = db_clone.clone();
+        let menu_clone_details = menu.clone();
+        let row_clone_details = row_clone.clone();
+        let poster_cache_clone = poster_cache.clone();
+        details_action.connect_activate(move |_, _| {
+            let db = db_clone2.borrow();
+            if let Some(movie) = db.movies.get(&movie_id) {
+                // Create details dialog
+                let details_dialog = gtk::Window::builder()
+                    .title(&format!("{} - Details", movie.title))
+                    .modal(true)
+                    .default_width(700)
+                    .default_height(600)
+                    .build();
+                
+                let scroll = gtk::ScrolledWindow::new();
+                scroll.set_vexpand(true);
+                
+                let details_box = gtk::Box::new(gtk::Orientation::Vertical, 16);
+                details_box.set_margin_start(20);
+                details_box.set_margin_end(20);
+                details_box.set_margin_top(20);
+                details_box.set_margin_bottom(20);
+                
+                // Poster at top
+                if !movie.poster_path.is_empty() && std::path::Path::new(&movie.poster_path).exists() {
+                    if let Ok(pixbuf) = gtk::gdk_pixbuf::Pixbuf::from_file(&movie.poster_path) {
+                        if let Some(scaled) = pixbuf.scale_simple(200, 300, gtk::gdk_pixbuf::InterpType::Bilinear) {
+                            let poster_img = gtk::Picture::for_pixbuf(&scaled);
+                            poster_img.set_halign(gtk::Align::Center);
+                            details_box.append(&poster_img);
+                        }
+                    }
+                }
+                
+                // Movie information
+                let info_label = gtk::Label::new(None);
+                info_label.set_xalign(0.0);
+                info_label.set_wrap(true);
+                info_label.set_selectable(true);
+                open_links_externally(&info_label);
+                
+                let escaped_title = escape_markup(&movie.title);
+                let escaped_director = escape_markup(&movie.director);
+                let escaped_genre = escape_markup(&movie.genre.join(", "));
+                let escaped_description = escape_markup(&movie.description);
+                let escaped_file = escape_markup(&movie.file_paths.join("\n"));
+                
+                let cast_display = if !movie.cast.is_empty() {
+                    movie.cast.iter().map(|n| escape_markup(n)).collect::<Vec<_>>().join("\n    • ")
+                } else {
+                    String::from("Unknown")
+                };
+                
+                let watch_log_display = if !movie.watch_log.is_empty() {
+                    movie.watch_log.iter()
+                        .map(|entry| {
+                            let rating = entry.rating.map(|r| format!(" - Rated: {}/10", r)).unwrap_or_default();
+                            let comments = if !entry.comments.is_empty() {
+                                format!(" - {}", escape_markup(&entry.comments))
+                            } else {
+                                String::new()
+                            };
+                            format!("    • {}{}{}", entry.date, rating, comments)
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                } else {
+                    String::from("Not yet watched")
+                };
+                
+                let mut crew_display = series_info_markup(movie);
+                if !movie.writer.is_empty() {
+                    crew_display.push_str(&format!("<b>Writer:</b> {}\n", escape_markup(&movie.writer)));
+                }
+                if !movie.composer.is_empty() {
+                    crew_display.push_str(&format!("<b>Composer:</b> {}\n", escape_markup(&movie.composer)));
+                }
+                if !movie.collection_name.is_empty() {
+                    crew_display.push_str(&format!("<b>Collection:</b> {}\n", escape_markup(&movie.collection_name)));
+                }
+                if !movie.certification.is_empty() {
+                    crew_display.push_str(&format!("<b>Rated:</b> {}\n", escape_markup(&movie.certification)));
+                }
+                
+                let mut file_info_display = String::new();
+                if movie.file_size > 0 {
+                    file_info_display.push_str(&format!("\n<b>Size:</b> {:.2} GB", movie.file_size as f64 / 1_073_741_824.0));
+                }
+                if !movie.subtitles.is_empty() {
+                    let names: Vec<String> = movie.subtitles.iter()
+                        .map(|s| escape_markup(&Path::new(s).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()))
+                        .collect();
+                    file_info_display.push_str(&format!("\n<b>Subtitles:</b> {}", names.join(", ")));
+                }
+                
+                let details_text = format!(
+                    "<b>{}</b> ({})\n\n\
+                    <b>{}:</b> {}\n\
+                    {}\
+                    <b>Genre:</b> {}\n\
+                    <b>TMDB Rating:</b> ⭐ {:.1}/10\n\
+                    <b>My Rating:</b> {}\n\
+                    <b>{}:</b> {} minutes\n\
+                    <b>IMDb ID:</b> {}\n\n\
+                    <b>Description:</b>\n{}\n\n\
+                    <b>Cast:</b>\n    • {}\n\n\
+                    <b>Watch History:</b>\n{}\n\n\
+                    <b>File:</b> {}{}",
+                    escaped_title, movie.year,
+                    director_heading(movie),
+                    escaped_director,
+                    crew_display,
+                    escaped_genre,
+                    movie.rating,
+                    my_rating_display(movie),
+                    runtime_heading(movie),
+                    movie.runtime,
+                    imdb_link_markup(movie),
+                    escaped_description,
+                    cast_display,
+                    watch_log_display,
+                    escaped_file,
+                    file_info_display
+                );
+                
+                info_label.set_markup(&details_text);
+                details_box.append(&info_label);
+                
+                // Full crew by department, collapsed by default
+                if !movie.crew.is_empty() {
+                    let crew_label = gtk::Label::new(None);
+                    crew_label.set_xalign(0.0);
+                    crew_label.set_wrap(true);
+                    crew_label.set_selectable(true);
+                    crew_label.set_markup(&crew_markup(&movie.crew));
+                    let crew_expander = gtk::Expander::new(Some(&format!("Crew ({})", movie.crew.len())));
+                    crew_expander.set_child(Some(&crew_label));
+                    details_box.append(&crew_expander);
+                }
+                
+                // External links from the configured templates
+                let links_row = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+                fill_link_buttons(&links_row, Some(movie));
+                details_box.append(&links_row);
+                
+                // Cast photos section (if available)
+                if !movie.cast_details.is_empty() {
+                    let cast_label = gtk::Label::new(None);
+                    cast_label.set_markup("<b>Cast Photos:</b>");
+                    cast_label.set_xalign(0.0);
+                    cast_label.set_margin_top(12);
+                    details_box.append(&cast_label);
+                    
+                    let cast_flow = gtk::FlowBox::new();
+                    cast_flow.set_selection_mode(gtk::SelectionMode::None);
+                    cast_flow.set_column_spacing(12);
+                    cast_flow.set_row_spacing(12);
+                    cast_flow.set_margin_top(8);
+                    cast_flow.set_homogeneous(true);
+                    cast_flow.set_max_children_per_line(4);
+                    
+                    for cast_member in &movie.cast_details {
+                        let member_box = gtk::Box::new(gtk::Orientation::Vertical, 8);
+                        member_box.set_size_request(120, 200);
+                        
+                        // Photo
+                        let photo_box = gtk::Box::new(gtk::Orientation::Vertical, 0);
+                        photo_box.set_size_request(100, 150);
+                        photo_box.set_halign(gtk::Align::Center);
+                        
+                        if !cast_member.profile_path.is_empty() {
+                            // Try to load cached photo
+                            let cached_path = if cast_member.local_photo_path.is_empty() {
+                                cast_photos_dir(&db_clone2.borrow().posters_dir).join(cast_photo_filename(&cast_member.profile_path))
+                            } else {
+                                PathBuf::from(&cast_member.local_photo_path)
+                            };
+                            
+                            eprintln!("Looking for cast photo: {:?} (exists: {})", cached_path, cached_path.exists());
+                            
+                            if cached_path.exists() {
+                                if let Ok(pixbuf) = gtk::gdk_pixbuf::Pixbuf::from_file(&cached_path) {
+                                    if let Some(scaled) = pixbuf.scale_simple(100, 150, gtk::gdk_pixbuf::InterpType::Bilinear) {
+                                        let photo = gtk::Picture::for_pixbuf(&scaled);
+                                        photo_box.append(&photo);
+                                    }
+                                }
+                            } else {
+                                // Placeholder if not cached
+                                let placeholder = gtk::Label::new(Some("👤"));
+                                placeholder.set_markup("<span size='xx-large'>👤</span>");
+                                photo_box.append(&placeholder);
+                            }
+                        } else {
+                            let placeholder = gtk::Label::new(Some("👤"));
+                            placeholder.set_markup("<span size='xx-large'>👤</span>");
+                            photo_box.append(&placeholder);
+                        }
+                        
+                        member_box.append(&photo_box);
+                        
+                        // Name
+                        let name_label = gtk::Label::new(Some(&cast_member.name));
+                        name_label.set_xalign(0.5);
+                        name_label.set_wrap(true);
+                        name_label.set_max_width_chars(15);
+                        name_label.set_markup(&format!("<b>{}</b>", escape_markup(&cast_member.name)));
+                        member_box.append(&name_label);
+                        
+                        // Character
+                        if !cast_member.character.is_empty() {
+                            let char_label = gtk::Label::new(Some(&cast_member.character));
+                            char_label.set_xalign(0.5);
+                            char_label.set_wrap(true);
+                            char_label.set_max_width_chars(15);
+                            char_label.set_markup(&format!("<i><small>{}</small></i>", escape_markup(&cast_member.character)));
+                            member_box.append(&char_label);
+                        }
+                        
+                        cast_flow.append(&member_box);
+                    }
+                    
+                    details_box.append(&cast_flow);
+                } else if movie.cast_details.is_empty() && !movie.cast.is_empty() {
+                    // Show message if cast photos aren't available yet
+                    let cast_note = gtk::Label::new(None);
+                    cast_note.set_markup("<i><small>Cast photos not available. Refresh metadata to download.</small></i>");
+                    cast_note.set_xalign(0.0);
+                    cast_note.set_margin_top(8);
+                    details_box.append(&cast_note);
+                }
+                
+                // Favorite toggle and close button
+                let button_row = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+                button_row.set_halign(gtk::Align::Center);
+                
+                let favorite_btn = gtk::ToggleButton::with_label(if movie.favorite { "♥ Favorite" } else { "♡ Favorite" });
+                favorite_btn.set_active(movie.favorite);
+                let db_clone_fav = db_clone2.clone();
+                let row_clone_fav = row_clone_details.clone();
+                let poster_cache_fav = poster_cache_clone.clone();
+                favorite_btn.connect_toggled(move |button| {
+                    let is_favorite = db_clone_fav.borrow().movies.get(&movie_id).map(|m| m.favorite);
+                    if is_favorite.is_some_and(|favorite| favorite != button.is_active()) {
+                        db_clone_fav.borrow_mut().toggle_favorite(movie_id);
+                        refresh_row_contents(&row_clone_fav, &db_clone_fav, movie_id, &poster_cache_fav);
+                    }
+                    button.set_label(if button.is_active() { "♥ Favorite" } else { "♡ Favorite" });
+                });
+                button_row.append(&favorite_btn);
+                
+                let watched_btn = gtk::ToggleButton::with_label(if movie.watched { "✓ Watched" } else { "✓ Mark Watched" });
+                watched_btn.set_active(movie.watched);
+                let db_clone_watched = db_clone2.clone();
+                let row_clone_watched = row_clone_details.clone();
+                let poster_cache_watched = poster_cache_clone.clone();
+                watched_btn.connect_toggled(move |button| {
+                    let is_watched = db_clone_watched.borrow().movies.get(&movie_id).map(|m| m.watched);
+                    if is_watched.is_some_and(|watched| watched != button.is_active()) {
+                        db_clone_watched.borrow_mut().toggle_watched(movie_id);
+                        refresh_row_contents(&row_clone_watched, &db_clone_watched, movie_id, &poster_cache_watched);
+                    }
+                    button.set_label(if button.is_active() { "✓ Watched" } else { "✓ Mark Watched" });
+                });
+                button_row.append(&watched_btn);
+                
+                let close_btn = gtk::Button::with_label("Close");
+                let dialog_clone = details_dialog.clone();
+                close_btn.connect_clicked(move |_| {
+                    dialog_clone.close();
+                });
+                button_row.append(&close_btn);
+                details_box.append(&button_row);
+                
+                scroll.set_child(Some(&details_box));
+                details_dialog.set_child(Some(&scroll));
+                details_dialog.present();
+            }
+            menu_clone_details.popdown();
+        });
+        
+        // Delete action
+        let delete_action = gtk::gio::SimpleAction::new("delete", None);
+        let db_clone3 = db_clone.clone();
+        let menu_clone2 = menu.clone();
+        let movie_title_clone2 = movie_title.clone();
+        let row_clone2 = row_clone.clone();
+        delete_action.connect_activate(move |_, _| {
+            menu_clone2.popdown();
+            let Some(window) = row_clone2.root().and_downcast::<Window>() else {
+                return;
+            };
+
+            let confirmed = confirm(
+                &window,
+                "Delete Movie",
+                &format!("Are you sure you want to delete \"{}\"?", movie_title_clone2),
+                "Delete",
+            );
+
+            let db_clone4 = db_clone3.clone();
+            let movie_title_clone3 = movie_title_clone2.clone();
+            let row_clone3 = row_clone2.clone();
+            glib::spawn_future_local(async move {
+                if !confirmed.await {
+                    return;
+                }
+
+                // Delete the movie from database
+                let mut db_mut = db_clone4.borrow_mut();
+                if db_mut.delete_movie(movie_id) {
+                    eprintln!("Deleted movie metadata: {}", movie_title_clone3);
+                    drop(db_mut);
+                    offer_undo_delete(&movie_title_clone3);
+
+                    // Remove the row from UI
+                    if let Some(parent) = row_clone3.parent() {
+                        if let Some(list_box) = parent.downcast_ref::<ListBox>() {
+                            list_box.remove(&row_clone3);
+                        }
+                    }
+                } else {
+                    log_warn!("Failed to delete movie - not found");
+                }
+            });
+        });
+        
+        // Copy to another library action
+        let copy_action = gtk::gio::SimpleAction::new("copy", None);
+        let db_clone5 = db_clone.clone();
+        let menu_clone3 = menu.clone();
+        let row_clone4 = row_clone.clone();
+        copy_action.connect_activate(move |_, _| {
+            menu_clone3.popdown();
+            let Some(window) = row_clone4.root().and_downcast::<Window>() else {
+                return;
+            };
+
+            let file_dialog = gtk::FileDialog::builder()
+                .title("Select Library Data File")
+                .modal(true)
+                .build();
+
+            let db_clone6 = db_clone5.clone();
+            let window_clone = window.clone();
+            file_dialog.open(Some(&window), gtk::gio::Cancellable::NONE, move |result| {
+                let Some(target) = result.ok().and_then(|file| file.path()) else {
+                    return;
+                };
+
+                let detail = match db_clone6.borrow().copy_movie_to(movie_id, &target) {
+                    Ok(new_id) => format!("Copied to {} (id {})", target.display(), new_id),
+                    Err(e) => format!("Copy failed: {}", e),
+                };
+                gtk::AlertDialog::builder()
+                    .message("Copy to Another Library")
+                    .detail(&detail)
+                    .buttons(vec!["OK"])
+                    .build()
+                    .show(Some(&window_clone));
+            });
+        });
+
+        // Favorite toggle action
+        let favorite_action = gtk::gio::SimpleAction::new("favorite", None);
+        let db_clone8 = db_clone.clone();
+        let menu_clone5 = menu.clone();
+        let row_clone6 = row_clone.clone();
+        let poster_cache_clone = poster_cache.clone();
+        favorite_action.connect_activate(move |_, _| {
+            menu_clone5.popdown();
+            db_clone8.borrow_mut().toggle_favorite(movie_id);
+            refresh_row_contents(&row_clone6, &db_clone8, movie_id, &poster_cache_clone);
+        });
+
+        // Watched toggle action
+        let watched_action = gtk::gio::SimpleAction::new("watched", None);
+        let db_clone9 = db_clone.clone();
+        let menu_clone6 = menu.clone();
+        let row_clone7 = row_clone.clone();
+        let poster_cache_clone2 = poster_cache.clone();
+        watched_action.connect_activate(move |_, _| {
+            menu_clone6.popdown();
+            db_clone9.borrow_mut().toggle_watched(movie_id);
+            refresh_row_contents(&row_clone7, &db_clone9, movie_id, &poster_cache_clone2);
+        });
+
+        // View Collection action - owned vs. missing entries of the movie's franchise
+        let collection_action = gtk::gio::SimpleAction::new("collection", None);
+        let db_clone7 = db_clone.clone();
+        let menu_clone4 = menu.clone();
+        let row_clone5 = row_clone.clone();
+        collection_action.connect_activate(move |_, _| {
+            menu_clone4.popdown();
+            let Some(window) = row_clone5.root().and_downcast::<Window>() else {
+                return;
+            };
+            let Some(movie) = db_clone7.borrow().movies.get(&movie_id).cloned() else {
+                return;
+            };
+            // Rows can't see the filters, so the window's refresh-view action redraws the list
+            let window_clone = window.clone();
+            show_collection_dialog(&window, &movie, &db_clone7, move || {
+                let _ = window_clone.activate_action("win.refresh-view", None);
+            });
+        });
+
+        actions.add_action(&details_action);
+        actions.add_action(&favorite_action);
+        actions.add_action(&watched_action);
+        actions.add_action(&collection_action);
+        actions.add_action(&copy_action);
+        actions.add_action(&delete_action);
+        menu.insert_action_group("movie", Some(&actions));
+        
+        menu.popup();
+    });
+    
+    row.add_controller(gesture);
+    
+    row
+}
+
+// Show which films of a movie's TMDB collection are in the library, with an option
+// to add the missing ones to the watchlist (entries without a file)
+// `on_added` runs after movies were added to the watchlist, to show them in the main list
+fn show_collection_dialog(window: &Window, movie: &Movie, db: &Rc<RefCell<MovieDatabase>>, on_added: impl Fn() + 'static) {
+    let dialog = Window::builder()
+        .title(format!("{} - Collection", movie.title))
+        .modal(true)
+        .transient_for(window)
+        .default_width(500)
+        .default_height(450)
+        .build();
+    
+    let content = Box::new(Orientation::Vertical, 12);
+    content.set_margin_start(20);
+    content.set_margin_end(20);
+    content.set_margin_top(20);
+    content.set_margin_bottom(20);
+    
+    let header_label = Label::new(Some("Loading collection..."));
+    header_label.set_xalign(0.0);
+    content.append(&header_label);
+    
+    let parts_list = ListBox::new();
+    parts_list.set_selection_mode(gtk::SelectionMode::None);
+    let scroll = ScrolledWindow::new();
+    scroll.set_vexpand(true);
+    scroll.set_child(Some(&parts_list));
+    content.append(&scroll);
+    
+    let button_box = Box::new(Orientation::Horizontal, 8);
+    button_box.set_halign(Align::End);
+    let add_all_button = Button::with_label("Add All Missing to Watchlist");
+    add_all_button.set_sensitive(false);
+    let close_button = Button::with_label("Close");
+    button_box.append(&add_all_button);
+    button_box.append(&close_button);
+    content.append(&button_box);
+    
+    dialog.set_child(Some(&content));
+    
+    let dialog_clone = dialog.clone();
+    close_button.connect_clicked(move |_| {
+        dialog_clone.close();
+    });
+    
+    let api_key = db.borrow().tmdb_api_key.clone();
+    let posters_dir = db.borrow().posters_dir.clone();
+    let movie_for_fetch = movie.clone();
+    let (sender, receiver) = async_channel::bounded::<Option<TMDBCollection>>(1);
+    std::thread::spawn(move || {
+        let client = reqwest::blocking::Client::new();
+        let _ = sender.send_blocking(fetch_collection(&client, &movie_for_fetch, &api_key));
+    });
+    
+    let db_clone = db.clone();
+    glib::spawn_future_local(async move {
+        let Ok(Some(collection)) = receiver.recv().await else {
+            header_label.set_text("This movie is not part of a TMDB collection.");
+            return;
+        };
+        
+        let owned: std::collections::HashSet<u32> = db_clone.borrow().movies.values()
+            .map(|m| m.tmdb_id)
+            .filter(|id| *id != 0)
+            .collect();
+        let missing: Vec<u32> = collection.parts.iter()
+            .map(|p| p.id)
+            .filter(|id| !owned.contains(id))
+            .collect();
+        
+        header_label.set_markup(&format!(
+            "<b>{}</b>\n{} of {} owned",
+            escape_markup(&collection.name),
+            collection.parts.len() - missing.len(),
+            collection.parts.len()
+        ));
+        
+        // "Add to Watchlist" button of each missing part, by TMDB id
+        let part_buttons: Rc<RefCell<HashMap<u32, Button>>> = Rc::new(RefCell::new(HashMap::new()));
+        
+        // Fetch parts by TMDB id and add them without a file (watchlist entries). Only the
+        // buttons of parts that were added are marked; the others can be tried again.
+        let add_to_watchlist: Rc<dyn Fn(Vec<u32>, Option<Button>)> = {
+            let db_clone = db_clone.clone();
+            let header_label = header_label.clone();
+            let posters_dir = posters_dir.clone();
+            let part_buttons = part_buttons.clone();
+            let on_added = Rc::new(on_added);
+            Rc::new(move |tmdb_ids: Vec<u32>, add_all_button: Option<Button>| {
+                for tmdb_id in &tmdb_ids {
+                    if let Some(button) = part_buttons.borrow().get(tmdb_id) {
+                        button.set_sensitive(false);
+                    }
+                }
+                if let Some(button) = &add_all_button {
+                    button.set_sensitive(false);
+                }
+                header_label.set_text(&format!("Adding {} movies to watchlist...", tmdb_ids.len()));
+                
+                let api_key = db_clone.borrow().tmdb_api_key.clone();
+                let posters_dir = posters_dir.clone();
+                let (sender, receiver) = async_channel::unbounded::<(u32, Option<Movie>)>();
+                std::thread::spawn(move || {
+                    let client = reqwest::blocking::Client::new();
+                    for tmdb_id in tmdb_ids {
+                        let movie = fetch_movie_by_tmdb_id(&client, tmdb_id, &api_key, String::new(), &posters_dir);
+                        let _ = sender.send_blocking((tmdb_id, movie));
+                    }
+                });
+                
+                let db_clone = db_clone.clone();
+                let header_label = header_label.clone();
+                let part_buttons = part_buttons.clone();
+                let on_added = on_added.clone();
+                glib::spawn_future_local(async move {
+                    let mut added = 0;
+                    let mut failed = 0;
+                    while let Ok((tmdb_id, movie_opt)) = receiver.recv().await {
+                        let was_added = movie_opt.is_some_and(|movie| db_clone.borrow_mut().add_movie(movie).is_some());
+                        let Some(button) = part_buttons.borrow_mut().remove(&tmdb_id) else {
+                            continue;
+                        };
+                        if was_added {
+                            added += 1;
+                            button.set_label("✓ Added");
+                        } else {
+                            failed += 1;
+                            button.set_sensitive(true);
+                            part_buttons.borrow_mut().insert(tmdb_id, button);
+                        }
+                    }
+                    if failed > 0 {
+                        header_label.set_text(&format!("Added {} movies to watchlist, {} could not be added", added, failed));
+                    } else {
+                        header_label.set_text(&format!("Added {} movies to watchlist", added));
+                    }
+                    if let Some(button) = &add_all_button {
+                        if part_buttons.borrow().is_empty() {
+                            button.set_label("✓ All Added");
+                        } else {
+                            button.set_sensitive(true);
+                        }
+                    }
+                    if added > 0 {
+                        on_added();
+                    }
+                });
+            })
+        };
+        
+        for part in &collection.parts {
+            let year = part.release_date.as_deref()
+                .and_then(|d| d.split('-').next())
+                .filter(|y| !y.is_empty())
+                .unwrap_or("?");
+            let row_box = Box::new(Orientation::Horizontal, 8);
+            row_box.set_margin_start(6);
+            row_box.set_margin_end(6);
+            row_box.set_margin_top(4);
+            row_box.set_margin_bottom(4);
+            
+            let is_owned = owned.contains(&part.id);
+            let label = Label::new(Some(&format!("{} {} ({})", if is_owned { "✓" } else { "✗" }, part.title, year)));
+            label.set_xalign(0.0);
+            label.set_hexpand(true);
+            if !is_owned {
+                label.set_opacity(0.7);
+            }
+            row_box.append(&label);
+            
+            if !is_owned {
+                let add_button = Button::with_label("Add to Watchlist");
+                let add_to_watchlist = add_to_watchlist.clone();
+                let tmdb_id = part.id;
+                add_button.connect_clicked(move |_| {
+                    add_to_watchlist(vec![tmdb_id], None);
+                });
+                row_box.append(&add_button);
+                part_buttons.borrow_mut().insert(tmdb_id, add_button);
+            }
+            parts_list.append(&row_box);
+        }
+        
+        if !missing.is_empty() {
+            add_all_button.set_sensitive(true);
+            add_all_button.connect_clicked(move |button| {
+                // Skip parts already added, or being added, from their own button
+                let remaining: Vec<u32> = missing.iter()
+                    .copied()
+                    .filter(|id| part_buttons.borrow().get(id).is_some_and(|b| b.is_sensitive()))
+                    .collect();
+                add_to_watchlist(remaining, Some(button.clone()));
+            });
+        }
+    });
+    
+    dialog.present();
+}
+
+fn create_movie_grid_item(movie: &Movie, poster_cache: &Rc<RefCell<HashMap<u32, Pixbuf>>>) -> gtk::FlowBoxChild {
+    let child = gtk::FlowBoxChild::new();
+    child.set_widget_name(&movie.id.to_string());
+    
+    let vbox = Box::new(Orientation::Vertical, 8);
+    vbox.set_size_request(180, 320);
+    
+    // Poster with overlay
+    let poster_container = gtk::Overlay::new();
+    poster_container.set_size_request(160, 240);
+    
+    let poster_box = Box::new(Orientation::Vertical, 0);
+    poster_box.set_size_request(160, 240);
+    
+    let placeholder = Label::new(Some("🎬"));
+    placeholder.set_markup("<span size='70000'>🎬</span>");
+    poster_box.append(&placeholder);
+    
+    if !movie.poster_path.is_empty() && Path::new(&movie.poster_path).exists() {
+        // For grid view, always load from disk at proper size (160×240)
+        // Don't scale up the small list thumbnails - that causes blurriness!
+        let poster_cache = poster_cache.clone();
+        let movie_id = movie.id;
+        load_poster_async(&movie.poster_path, (160, 240, true), &poster_box, &placeholder, move |pixbuf| {
+            // Also ensure we have a thumbnail cached for list view
+            if poster_cache.borrow().get(&movie_id).is_none() {
+                let (box_width, box_height) = LIST_THUMB_SIZE.with(|size| size.get());
+                // Fit inside the list box, keeping the poster's own aspect ratio
+                let scale = (box_width as f64 / pixbuf.width() as f64).min(box_height as f64 / pixbuf.height() as f64);
+                let thumb_width = ((pixbuf.width() as f64 * scale).round() as i32).max(1);
+                let thumb_height = ((pixbuf.height() as f64 * scale).round() as i32).max(1);
+                if let Some(thumbnail) = pixbuf.scale_simple(thumb_width, thumb_height, gtk::gdk_pixbuf::InterpType::Bilinear) {
+                    poster_cache.borrow_mut().insert(movie_id, thumbnail);
+                }
+            }
+        });
+    }
+    
+    poster_container.set_child(Some(&poster_box));
+    
+    // Add "SEEN" badge if movie has been watched
+    if movie.watched {
+        let seen_label = Label::new(Some("SEEN"));
+        seen_label.set_markup("<span foreground='red' weight='bold' size='x-large'>SEEN</span>");
+        seen_label.set_halign(Align::Center);
+        seen_label.set_valign(Align::Center);
+        poster_container.add_overlay(&seen_label);
+    }
+    
+    vbox.append(&poster_container);
+    
+    // Title and year
+    let title_label = Label::new(Some(&format!("{} ({})", movie.title, movie.year)));
+    title_label.set_wrap(true);
+    title_label.set_wrap_mode(gtk::pango::WrapMode::WordChar);
+    title_label.set_max_width_chars(20);
+    title_label.set_justify(gtk::Justification::Center);
+    let escaped_title = escape_markup(&movie.title);
+    title_label.set_markup(&format!("<b>{}</b>\n<small>({})</small>", escaped_title, movie.year));
+    
+    // Rating
+    let rating_label = Label::new(Some(&format!("⭐ {:.1}/10", movie.rating)));
+    rating_label.set_opacity(0.8);
+    
+    vbox.append(&title_label);
+    vbox.append(&rating_label);
+    
+    child.set_child(Some(&vbox));
+    child
+}
+
+// A poster at full size, scaled down to fit the window with its aspect ratio kept.
+// Clicking it or pressing Escape closes the window.
+fn show_poster_preview(window: &impl IsA<Window>, title: &str, poster_path: &str) {
+    let preview = Window::builder()
+        .title(title)
+        .modal(true)
+        .transient_for(window)
+        .default_width(680)
+        .default_height(1000)
+        .build();
+    
+    let picture = Picture::for_filename(poster_path);
+    picture.set_can_shrink(true);
+    picture.set_content_fit(gtk::ContentFit::Contain);
+    preview.set_child(Some(&picture));
+    
+    let click = gtk::GestureClick::new();
+    let preview_clone = preview.clone();
+    click.connect_released(move |_, _, _, _| preview_clone.close());
+    picture.add_controller(click);
+    
+    let keys = gtk::EventControllerKey::new();
+    let preview_clone = preview.clone();
+    keys.connect_key_pressed(move |_, key, _, _| {
+        if key == gtk::gdk::Key::Escape {
+            preview_clone.close();
+            return glib::Propagation::Stop;
+        }
+        glib::Propagation::Proceed
+    });
+    preview.add_controller(keys);
+    
+    preview.present();
+}
+
+// Movie ids of the selected list rows, in list order
+fn selected_row_ids(list_box: &ListBox) -> Vec<u32> {
+    list_box.selected_rows()
+        .iter()
+        .filter_map(|row| row.widget_name().parse::<u32>().ok())
+        .collect()
+}
+
+// Ask the user to confirm a destructive or long-running action.
+// Cancel is the default (Enter/Escape both cancel), and the result is a plain bool
+// so callers never have to care about button indices.
+fn confirm(
+    window: &impl IsA<Window>,
+    message: &str,
+    detail: &str,
+    confirm_label: &str,
+) -> impl std::future::Future<Output = bool> {
+    const CANCEL: i32 = 0;
+    const CONFIRM: i32 = 1;
+
+    let dialog = gtk::AlertDialog::builder()
+        .message(message)
+        .detail(detail)
+        .buttons(vec!["Cancel", confirm_label])
+        .cancel_button(CANCEL)
+        .default_button(CANCEL)
+        .build();
+    let window = window.clone().upcast::<Window>();
+
+    async move {
+        matches!(dialog.choose_future(Some(&window)).await, Ok(CONFIRM))
+    }
+}
+
+// Pick one of a movie's copies to play. With a single file it is chosen straight away;
+// None if the user cancels.
+fn choose_file_to_play(
+    window: &impl IsA<Window>,
+    title: &str,
+    files: Vec<String>,
+) -> impl std::future::Future<Output = Option<String>> {
+    let dialog = (files.len() > 1).then(|| {
+        let mut buttons: Vec<String> = files.iter()
+            .map(|path| Path::new(path).file_name().map_or(path.clone(), |name| name.to_string_lossy().to_string()))
+            .collect();
+        buttons.push("Cancel".to_string());
+        gtk::AlertDialog::builder()
+            .message(format!("Play which copy of {}?", title))
+            .detail(files.join("\n"))
+            .buttons(buttons.iter().map(|b| b.as_str()).collect::<Vec<_>>())
+            .cancel_button(files.len() as i32)
+            .default_button(0)
+            .build()
+    });
+    let window = window.clone().upcast::<Window>();
+
+    async move {
+        let Some(dialog) = dialog else {
+            return files.into_iter().next();
+        };
+        let choice = dialog.choose_future(Some(&window)).await.ok()?;
+        files.into_iter().nth(usize::try_from(choice).ok()?)
+    }
+}
+
+fn show_api_key_dialog(window: &ApplicationWindow) -> Option<String> {
+    // The environment wins over the config file, and is never written to disk
+    if let Some(key) = env_api_key() {
+        println!("Using API key from TMDB_API_KEY");
+        return Some(key);
+    }
+    
+    // Then try the existing config
+    if let Some(config) = load_config() {
+        if !config.tmdb_api_key.is_empty() {
+            println!("Loaded API key from config");
+            return Some(config.tmdb_api_key);
+        }
+    }
+    
+    let dialog = Window::builder()
+        .title("TMDB API Key Required")
+        .modal(true)
+        .transient_for(window)
+        .default_width(500)
+        .default_height(220)
+        .build();
+
+    let content = Box::new(Orientation::Vertical, 12);
+    content.set_margin_start(12);
+    content.set_margin_end(12);
+    content.set_margin_top(12);
+    content.set_margin_bottom(12);
+
+    let info_label = Label::new(Some(
+        "To fetch movie metadata, you need a TMDB API key.\n\
+        Get one free at: https://www.themoviedb.org/settings/api\n\n\
+        Enter your API key below (it will be saved for future use):"
+    ));
+    info_label.set_wrap(true);
+
+    let api_entry = Entry::new();
+    api_entry.set_placeholder_text(Some("Enter TMDB API key"));
+    api_entry.set_visibility(false);  // Hide the key like a password
+
+    let button_box = Box::new(Orientation::Horizontal, 8);
+    button_box.set_halign(gtk::Align::End);
+    let ok_btn = Button::with_label("OK");
+    button_box.append(&ok_btn);
+
+    content.append(&info_label);
+    content.append(&api_entry);
+    content.append(&button_box);
+
+    dialog.set_child(Some(&content));
+
+    let api_key = Rc::new(RefCell::new(String::new()));
+    let api_key_clone = api_key.clone();
+    let dialog_clone = dialog.clone();
+    
+    ok_btn.connect_clicked(move |_| {
+        let key = api_entry.text().to_string();
+        if !key.is_empty() {
+            // Save the API key to config, preserving existing settings
+            let mut config = load_config().unwrap_or_default();
+            config.tmdb_api_key = key.clone();
+            
+            if let Err(e) = save_config(&config) {
+                log_warn!("Warning: Could not save config: {}", e);
+            } else {
+                println!("API key saved to config");
+            }
+            *api_key_clone.borrow_mut() = key;
+        }
+        dialog_clone.close();
+    });
+
+    dialog.present();
+    
+    while dialog.is_visible() {
+        gtk::glib::MainContext::default().iteration(true);
+    }
+    
+    let key = api_key.borrow().clone();
+    if key.is_empty() {
+        None
+    } else {
+        Some(key)
+    }
+}
+
+// Split a player command into words, keeping "double quoted" parts together
+fn split_command(command: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in command.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+// Build (program, args) for playing a file: %f in the command is replaced by the path,
+// otherwise the path is added as the last argument
+fn player_invocation(command: &str, file_path: &str) -> Option<(String, Vec<String>)> {
+    let mut words = split_command(command);
+    if words.is_empty() {
+        return None;
+    }
+    let program = words.remove(0);
+    if words.iter().any(|w| w.contains("%f")) {
+        words = words.into_iter().map(|w| w.replace("%f", file_path)).collect();
+    } else {
+        words.push(file_path.to_string());
+    }
+    Some((program, words))
+}
+
+// Start the configured player on a file, falling back to flatpak VLC.
+// Returns the player that was started, or a message for the status bar.
+fn launch_player(file_path: &str) -> Result<String, String> {
+    let command = load_config().map(|c| c.player_command).unwrap_or_else(default_player_command);
+    if let Some((program, args)) = player_invocation(&command, file_path) {
+        let spawned = Command::new(&program)
+            .args(&args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        match spawned {
+            Ok(_) => return Ok(program),
+            Err(e) => log_warn!("Warning: Failed to start player '{}': {}", program, e),
+        }
+    }
+    
+    Command::new("flatpak")
+        .args(["run", "org.videolan.VLC", file_path])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| "flatpak VLC".to_string())
+        .map_err(|_| format!("Player not found: {}. Set the player command in Settings.", command))
+}
+
+const SUBTITLE_EXTENSIONS: &[&str] = &["srt", "sub", "idx", "ass", "ssa", "vtt"];
+
+// Subtitle files in the video's folder named after it (e.g. "Movie.srt", "Movie.en.srt"), sorted
+fn find_subtitle_sidecars(video_path: &Path) -> Vec<String> {
+    let (Some(dir), Some(stem)) = (video_path.parent(), video_path.file_stem()) else {
+        return Vec::new();
+    };
+    let stem = stem.to_string_lossy().to_lowercase();
+    let Ok(entries) = read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut subtitles: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let is_subtitle = path.extension()
+                .map(|ext| SUBTITLE_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()))
+                .unwrap_or(false);
+            let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+            is_subtitle && name.starts_with(&stem)
+        })
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    subtitles.sort();
+    subtitles
+}
+
+// Update a movie's file size and subtitle list from disk. Returns whether anything changed.
+fn update_local_file_info(movie: &mut Movie) -> bool {
+    let path = Path::new(movie.primary_file());
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return false;
+    };
+    let subtitles = find_subtitle_sidecars(path);
+    let changed = movie.file_size != metadata.len() || movie.subtitles != subtitles;
+    movie.file_size = metadata.len();
+    movie.subtitles = subtitles;
+    changed
+}
+
+// Technical details of a video file, as reported by ffprobe
+#[derive(Debug, Default)]
+struct ProbedFile {
+    resolution: String,
+    video_codec: String,
+    audio_codec: String,
+    runtime_secs: u32,
+}
+
+impl ProbedFile {
+    fn apply_to(self, movie: &mut Movie) {
+        movie.resolution = self.resolution;
+        movie.video_codec = self.video_codec;
+        movie.audio_codec = self.audio_codec;
+        movie.file_runtime_secs = self.runtime_secs;
+    }
+}
+
+// Parse `ffprobe -print_format json -show_format -show_streams` output, using the
+// first video and first audio stream
+fn parse_ffprobe_output(json: &str) -> Option<ProbedFile> {
+    let root: serde_json::Value = serde_json::from_str(json).ok()?;
+    let streams = root.get("streams").and_then(|s| s.as_array()).map(Vec::as_slice).unwrap_or_default();
+    let first_stream = |codec_type: &str| streams.iter().find(|s| s.get("codec_type").and_then(|t| t.as_str()) == Some(codec_type));
+    let codec_name = |stream: Option<&serde_json::Value>| {
+        stream.and_then(|s| s.get("codec_name")).and_then(|c| c.as_str()).unwrap_or_default().to_string()
+    };
+    
+    let video = first_stream("video");
+    let resolution = video
+        .and_then(|v| Some((v.get("width")?.as_u64()?, v.get("height")?.as_u64()?)))
+        .map(|(width, height)| format!("{}x{}", width, height))
+        .unwrap_or_default();
+    let runtime_secs = root.get("format")
+        .and_then(|f| f.get("duration"))
+        .and_then(|d| d.as_str())
+        .and_then(|d| d.parse::<f64>().ok())
+        .map_or(0, |secs| secs.round() as u32);
+    
+    Some(ProbedFile {
+        resolution,
+        video_codec: codec_name(video),
+        audio_codec: codec_name(first_stream("audio")),
+        runtime_secs,
+    })
+}
+
+// Run ffprobe on a video file (blocking). None if the file is missing, ffprobe isn't
+// installed, or it couldn't read the file.
+fn probe_video_file(path: &str) -> Option<ProbedFile> {
+    if path.is_empty() || !Path::new(path).is_file() {
+        return None;
+    }
+    let output = Command::new("ffprobe")
+        .args(["-v", "quiet", "-print_format", "json", "-show_format", "-show_streams", path])
+        .stdin(Stdio::null())
+        .output();
+    match output {
+        Ok(output) if output.status.success() => parse_ffprobe_output(&String::from_utf8_lossy(&output.stdout)),
+        Ok(_) => None,
+        Err(e) => {
+            static WARNED: std::sync::Once = std::sync::Once::new();
+            WARNED.call_once(|| log_warn!("Warning: Could not run ffprobe ({}); install FFmpeg or turn off ffprobe in Settings", e));
+            None
+        }
+    }
+}
+
+// Probe a movie's primary file, if enabled in Settings (blocking)
+fn probe_movie_file(movie: &mut Movie, use_ffprobe: bool) {
+    if !use_ffprobe {
+        return;
+    }
+    if let Some(probed) = probe_video_file(movie.primary_file()) {
+        probed.apply_to(movie);
+    }
+}
+
+// A video file found by a scan: (search title, year from the name if any, path, media type)
+type ScanEntry = (String, Option<u16>, String, MediaType);
+
+// What a scan looks up: (search title, year, files, media type). A film has its one file,
+// a TV show every episode file found for it.
+type ScanGroup = (String, Option<u16>, Vec<String>, MediaType);
+
+// What MovieDatabase::reconcile found when comparing the library with a rescan
+#[derive(Debug, Default)]
+struct ReconcileReport {
+    new_files: Vec<String>,  // On disk but not in the library
+    missing_ids: Vec<u32>,  // Movies with none of their files left, to offer for pruning
+    missing_copies: Vec<(u32, String)>,  // Vanished extra copies of movies that still have a file
+    unchanged: usize,  // Movies with all their files in place
+}
+
+// Turn a video file path into a scan entry, or None if it isn't a video. Episode files
+// ("Show.S01E02.mkv") are entered under the show's title as TV.
+fn video_file_entry(path: &Path, video_extensions: &[String]) -> Option<ScanEntry> {
+    let ext_str = path.extension()?.to_string_lossy().to_lowercase();
+    if !video_extensions.contains(&ext_str) {
+        return None;
+    }
+
+    let title = path.file_stem()?.to_string_lossy().to_string();
+    let file_path_str = path.to_string_lossy().to_string();
+    let name = title.replace(['.', '_'], " ");
+
+    if let Some(show) = episode_show_name(&name) {
+        let (show_title, year) = split_title_year(&show);
+        return Some((show_title, year, file_path_str, MediaType::Tv));
+    }
+    let (clean_title, year) = clean_movie_title(&title);
+
+    Some((clean_title, year, file_path_str, MediaType::Movie))
+}
+
+// An SxxEyy episode marker such as "S01E02", "s1e10" or "S02E03E04"
+fn is_episode_marker(word: &str) -> bool {
+    let word = word.trim_matches(|c: char| "()[]{}-".contains(c)).to_lowercase();
+    let Some((season, episode)) = word.strip_prefix('s').and_then(|rest| rest.split_once('e')) else {
+        return false;
+    };
+    let episode_digits = episode.chars().take_while(|c| c.is_ascii_digit()).count();
+    (1..=2).contains(&season.len()) && season.chars().all(|c| c.is_ascii_digit()) && (1..=3).contains(&episode_digits)
+}
+
+// The show part of an episode name, "Show Name S01E02 Episode Title" -> "Show Name",
+// or None when there is no episode marker after some title text
+fn episode_show_name(name: &str) -> Option<String> {
+    let words: Vec<&str> = name.split_whitespace().collect();
+    let marker = words.iter().position(|word| is_episode_marker(word))?;
+    let show = words[..marker].join(" ")
+        .trim_end_matches(|c: char| c == '-' || c.is_whitespace())
+        .to_string();
+    (!show.is_empty()).then_some(show)
+}
+
+// Gather each TV show's episodes into one group (named after its first episode by path)
+// so a series is looked up and added once, with all of its files
+fn collapse_episodes(mut entries: Vec<ScanEntry>) -> Vec<ScanGroup> {
+    entries.sort_by(|a, b| a.2.cmp(&b.2));
+    let mut groups: Vec<ScanGroup> = Vec::new();
+    let mut show_groups: HashMap<String, usize> = HashMap::new();
+    for (title, year, path, media_type) in entries {
+        if media_type == MediaType::Tv {
+            if let Some(&index) = show_groups.get(&title.to_lowercase()) {
+                groups[index].2.push(path);
+                continue;
+            }
+            show_groups.insert(title.to_lowercase(), groups.len());
+        }
+        groups.push((title, year, vec![path], media_type));
+    }
+    groups
+}
+
+// Which video files a directory scan leaves out (samples, trailers and other junk)
+#[derive(Clone, Default)]
+struct ScanSkipRules {
+    min_size_bytes: u64,
+    ignore_patterns: Vec<String>,  // lowercase
+    exclude_dirs: Vec<String>,  // lowercase folder name patterns
+}
+
+impl ScanSkipRules {
+    fn from_config(config: &Config) -> Self {
+        ScanSkipRules {
+            min_size_bytes: config.min_file_size_mb * 1024 * 1024,
+            ignore_patterns: config.scan_ignore_patterns.iter()
+                .map(|p| p.trim().to_lowercase())
+                .filter(|p| !p.is_empty())
+                .collect(),
+            exclude_dirs: config.scan_exclude_patterns.iter()
+                .map(|p| p.trim().to_lowercase())
+                .filter(|p| !p.is_empty())
+                .collect(),
+        }
+    }
+
+    // Whether a folder matches one of the exclude patterns, compared with its whole name
+    fn skips_dir(&self, dir: &Path) -> bool {
+        let name = dir.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+        self.exclude_dirs.iter().any(|pattern| wildcard_matches(pattern, &name))
+    }
+
+    fn skips(&self, path: &Path, size: u64) -> bool {
+        if size < self.min_size_bytes {
+            return true;
+        }
+        let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+        self.ignore_patterns.iter().any(|pattern| name.contains(pattern.as_str()))
+    }
+}
+
+// Match `text` against `pattern`, where * is any run of characters and ? any one character
+fn wildcard_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    // Position after the last * and the text position it is currently matched up to
+    let mut star: Option<(usize, usize)> = None;
+    let (mut p, mut t) = (0, 0);
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            p += 1;
+            star = Some((p, t));
+        } else if let Some((star_p, star_t)) = star {
+            // Let the last * swallow one more character and retry
+            p = star_p;
+            t = star_t + 1;
+            star = Some((star_p, t));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+// Helper function to recursively scan directories for video files, leaving out excluded
+// folders and the files the skip rules reject
+fn scan_directory_recursive(
+    dir: &Path,
+    video_extensions: &[String],
+    rules: &ScanSkipRules,
+    files: &mut Vec<ScanEntry>,
+) {
+    if let Ok(entries) = read_dir(dir) {
+        for entry in entries.flatten() {
+            let entry_path = entry.path();
+
+            if entry_path.is_dir() {
+                // Recursively scan subdirectories, except excluded ones (extras, samples)
+                if !rules.skips_dir(&entry_path) {
+                    scan_directory_recursive(&entry_path, video_extensions, rules, files);
+                }
+            } else if entry_path.is_file() {
+                if let Some(file_entry) = video_file_entry(&entry_path, video_extensions) {
+                    let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                    if !rules.skips(&entry_path, size) {
+                        files.push(file_entry);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Everything a background scan needs (Rc can't be sent, so this is extracted up front)
+struct ScanJob {
+    directories: Vec<String>,
+    files: Vec<String>,
+    existing_paths: std::collections::HashSet<String>,
+    api_key: String,
+    posters_dir: String,
+    year_cutoff: i32,
+    min_vote_count: u32,
+    max_concurrent_requests: usize,  // Shared by every lookup in the scan, whatever the batch size
+    use_ffprobe: bool,
+    omdb_api_key: String,  // Empty disables the OMDb fallback
+    video_extensions: Vec<String>,  // Applied to directory scans and individually chosen files
+    skip_rules: ScanSkipRules,  // Applied to directory scans only, not individually chosen files
+}
+
+impl ScanJob {
+    // A scan of `directories` and `files` into `db`, with the scan settings from `config`
+    fn from_config(config: &Config, db: &MovieDatabase, directories: Vec<String>, files: Vec<String>) -> Self {
+        ScanJob {
+            directories,
+            files,
+            existing_paths: db.existing_file_paths(),
+            api_key: db.tmdb_api_key.clone(),
+            posters_dir: db.posters_dir.clone(),
+            year_cutoff: config.year_cutoff,
+            min_vote_count: config.min_vote_count,
+            max_concurrent_requests: config.max_concurrent_requests,
+            use_ffprobe: config.use_ffprobe,
+            omdb_api_key: config.omdb_api_key.clone(),
+            video_extensions: config.video_extensions.clone(),
+            skip_rules: ScanSkipRules::from_config(config),
+        }
+    }
+}
+
+// Shown when a scan, refresh or add is asked for while another one is still running
+const BUSY_MESSAGE: &str = "Wait for the scan or refresh that is running to finish first";
+
+// Set when the user quits mid-scan; workers stop after their current batch
+static SCAN_CANCELLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    // Cancel flags of scans whose results are still being applied on the main thread
+    static ACTIVE_SCANS: RefCell<Vec<Arc<AtomicBool>>> = const { RefCell::new(Vec::new()) };
+    // Cancel button and progress bar in the main window; shown only while scans run
+    static SCAN_CONTROLS: RefCell<Option<(Button, gtk::ProgressBar)>> = const { RefCell::new(None) };
+}
+
+// Marks a scan as active for as long as it is held. `cancelled` is shared with the
+// worker, which stops issuing requests once it is set.
+struct ActiveScan {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ActiveScan {
+    fn start() -> Self {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        ACTIVE_SCANS.with(|scans| scans.borrow_mut().push(cancelled.clone()));
+        Self::update_controls();
+        ActiveScan { cancelled }
+    }
+
+    fn any_running() -> bool {
+        ACTIVE_SCANS.with(|scans| !scans.borrow().is_empty())
+    }
+
+    fn was_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn cancel_all() {
+        ACTIVE_SCANS.with(|scans| {
+            for cancelled in scans.borrow().iter() {
+                cancelled.store(true, Ordering::Relaxed);
+            }
+        });
+        Self::update_controls();
+    }
+
+    fn set_controls(cancel_button: &Button, progress_bar: &gtk::ProgressBar) {
+        SCAN_CONTROLS.with(|controls| *controls.borrow_mut() = Some((cancel_button.clone(), progress_bar.clone())));
+        Self::update_controls();
+    }
+
+    // Apply a worker's "processed/total" progress message to the progress bar
+    fn show_progress(progress: &str) {
+        let Some((processed, total)) = parse_scan_progress(progress) else {
+            return;
+        };
+        SCAN_CONTROLS.with(|controls| {
+            if let Some((_, progress_bar)) = controls.borrow().as_ref() {
+                progress_bar.set_fraction(processed as f64 / total.max(1) as f64);
+                progress_bar.set_text(Some(&format!("{} / {}", processed, total)));
+            }
+        });
+    }
+
+    // Show the Cancel button and progress bar while scans run; the button is greyed out
+    // once they have all been cancelled
+    fn update_controls() {
+        let (running, cancellable) = ACTIVE_SCANS.with(|scans| {
+            let scans = scans.borrow();
+            (!scans.is_empty(), scans.iter().any(|cancelled| !cancelled.load(Ordering::Relaxed)))
+        });
+        SCAN_CONTROLS.with(|controls| {
+            if let Some((button, progress_bar)) = controls.borrow().as_ref() {
+                button.set_visible(running);
+                button.set_sensitive(cancellable);
+                progress_bar.set_visible(running);
+                if !running {
+                    progress_bar.set_fraction(0.0);
+                    progress_bar.set_text(None);
+                }
+            }
+        });
+    }
+}
+
+impl Drop for ActiveScan {
+    fn drop(&mut self) {
+        ACTIVE_SCANS.with(|scans| scans.borrow_mut().retain(|cancelled| !Arc::ptr_eq(cancelled, &self.cancelled)));
+        Self::update_controls();
+    }
+}
+
+// Parse a "processed/total" progress message from the scan worker
+fn parse_scan_progress(progress: &str) -> Option<(usize, usize)> {
+    let (processed, total) = progress.split_once('/')?;
+    Some((processed.trim().parse().ok()?, total.trim().parse().ok()?))
+}
+
+// A scan running on a worker thread: its messages, and the guard that keeps it active
+struct ScanHandle {
+    receiver: async_channel::Receiver<(String, String, Option<Movie>)>,
+    active: ActiveScan,
+}
+
+// Disables a button and shows a spinner with `busy_label` while a background operation
+// runs. The original label comes back when the guard is dropped, on success or failure.
+struct ButtonBusy {
+    button: Button,
+    label: String,
+}
+
+impl ButtonBusy {
+    fn start(button: &Button, busy_label: &str) -> Self {
+        let label = button.label().map(|l| l.to_string()).unwrap_or_default();
+        let content = Box::new(Orientation::Horizontal, 6);
+        let spinner = gtk::Spinner::new();
+        spinner.start();
+        content.append(&spinner);
+        content.append(&Label::new(Some(busy_label)));
+        button.set_child(Some(&content));
+        button.set_sensitive(false);
+        ButtonBusy { button: button.clone(), label }
+    }
+}
+
+impl Drop for ButtonBusy {
+    // The button's sensitivity is left to set_busy, which re-enables it once the work is done
+    fn drop(&mut self) {
+        self.button.set_label(&self.label);
+    }
+}
+
+// Collect video files from the job's directories (recursively) and individual files, then
+// fetch metadata for the new ones in parallel batches on a background thread.
+// Messages are ("status" | "add" | "progress" | "complete", status text or "processed/total",
+// movie to add).
+fn spawn_scan_worker(job: ScanJob) -> ScanHandle {
+    let (sender, receiver) = async_channel::unbounded::<(String, String, Option<Movie>)>();
+    let active = ActiveScan::start();
+    let cancelled = active.cancelled.clone();
+
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(run_scan_job(job, &cancelled, sender));
+    });
+
+    ScanHandle { receiver, active }
+}
+
+// The scan itself, shared by the GUI worker thread and the headless `--scan` mode.
+// Setting `cancelled` (or quitting) stops it before the next batch of lookups.
+async fn run_scan_job(job: ScanJob, cancelled: &AtomicBool, sender: async_channel::Sender<(String, String, Option<Movie>)>) {
+    let ScanJob { directories, files, existing_paths, api_key, posters_dir, year_cutoff, min_vote_count, max_concurrent_requests, use_ffprobe, omdb_api_key, video_extensions, skip_rules } = job;
+    let cache = RefCell::new(ScanCache::default());
+
+    // Collect all video files first (recursively)
+    let mut files_to_process = Vec::new();
+
+    for scan_dir in &directories {
+        let _ = sender.send_blocking(("status".to_string(), format!("Scanning: {} (including subdirectories)...", scan_dir), None));
+        scan_directory_recursive(Path::new(scan_dir), &video_extensions, &skip_rules, &mut files_to_process);
+    }
+
+    files_to_process.extend(files.iter().filter_map(|file| video_file_entry(Path::new(file), &video_extensions)));
+    let files_to_process = collapse_episodes(files_to_process);
+
+    // Filter out files that already exist in database (using pre-extracted paths). A show
+    // with new episodes is still looked up, and the new ones are attached to it when added.
+    let new_files: Vec<_> = files_to_process.into_iter()
+        .filter_map(|(title, year, file_paths, media_type)| {
+            let file_paths: Vec<String> = file_paths.into_iter().filter(|path| !existing_paths.contains(path)).collect();
+            (!file_paths.is_empty()).then_some((title, year, file_paths, media_type))
+        })
+        .collect();
+
+    if new_files.is_empty() {
+        let _ = sender.send_blocking(("status".to_string(), "No new movies found - all files already in database".to_string(), None));
+        let _ = sender.send_blocking(("complete".to_string(), String::new(), None));
+        return;
+    }
+
+    let _ = sender.send_blocking(("status".to_string(), format!("Found {} new video files (skipped {} existing), fetching metadata in parallel...", new_files.len(), existing_paths.len()), None));
+
+    let total = new_files.len();
+    let processed = std::cell::Cell::new(0);
+    let _ = sender.send_blocking(("progress".to_string(), format!("0/{}", total), None));
+
+    // Process files in parallel batches of 10
+    let client = reqwest::Client::new();
+    let limiter = tmdb_limiter(max_concurrent_requests);
+    let batch_size = 10;
+
+    for batch in new_files.chunks(batch_size) {
+        if SCAN_CANCELLED.load(Ordering::Relaxed) || cancelled.load(Ordering::Relaxed) {
+            let _ = sender.send_blocking(("status".to_string(), "Scan cancelled".to_string(), None));
+            break;
+        }
+
+        let futures: Vec<_> = batch.iter()
+            .map(|(clean_title, year, file_paths, media_type)| {
+                let api_key = api_key.clone();
+                let omdb_api_key = omdb_api_key.clone();
+                let title = clean_title.clone();
+                let year = *year;
+                let media_type = *media_type;
+                let file_path = file_paths[0].clone();
+                let file_paths = file_paths.clone();
+                let client = client.clone();
+                let sender = sender.clone();
+                let posters_dir = posters_dir.clone();
+                let cache = &cache;
+                let limiter = &limiter;
+                let processed = &processed;
+
+                async move {
+                    // Batches already in flight skip the lookups they haven't started yet
+                    if cancelled.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    let _ = sender.send_blocking(("status".to_string(), format!("Fetching: {}", title), None));
+
+                    // Reuse this scan's earlier lookups for repeated titles
+                    let cache_key = format!("{:?}|{}|{:?}", media_type, title.trim().to_lowercase(), year);
+                    let cached_match = cache.borrow().matches.get(&cache_key).copied();
+                    let movie_id = match cached_match {
+                        Some(movie_id) => movie_id,
+                        None => {
+                            let found = match media_type {
+                                MediaType::Movie => search_tmdb_match(&client, limiter, &api_key, &title, year, year_cutoff, min_vote_count).await,
+                                MediaType::Tv => search_tmdb_tv_match(&client, limiter, &api_key, &title, year, min_vote_count).await,
                             };
-                            format!("    • {}{}{}", entry.date, rating, comments)
-                        })
-                        .collect::<Vec<_>>()
-                        .join("\n")
-                } else {
-                    String::from("Not yet watched")
-                };
-                
-                let details_text = format!(
-                    "<b>{}</b> ({})\n\n\
-                    <b>Director:</b> {}\n\
-                    <b>Genre:</b> {}\n\
-                    <b>Rating:</b> ⭐ {:.1}/10\n\
-                    <b>Runtime:</b> {} minutes\n\
-                    <b>IMDb ID:</b> {}\n\n\
-                    <b>Description:</b>\n{}\n\n\
-                    <b>Cast:</b>\n    • {}\n\n\
-                    <b>Watch History:</b>\n{}\n\n\
-                    <b>File:</b> {}",
-                    escaped_title, movie.year,
-                    escaped_director,
-                    escaped_genre,
-                    movie.rating,
-                    movie.runtime,
-                    imdb_display,
-                    escaped_description,
-                    cast_display,
-                    watch_log_display,
-                    escaped_file
-                );
-                
-                info_label.set_markup(&details_text);
-                details_box.append(&info_label);
-                
-                // Cast photos section (if available)
-                if !movie.cast_details.is_empty() {
-                    let cast_label = gtk::Label::new(None);
-                    cast_label.set_markup("<b>Cast Photos:</b>");
-                    cast_label.set_xalign(0.0);
-                    cast_label.set_margin_top(12);
-                    details_box.append(&cast_label);
-                    
-                    let cast_flow = gtk::FlowBox::new();
-                    cast_flow.set_selection_mode(gtk::SelectionMode::None);
-                    cast_flow.set_column_spacing(12);
-                    cast_flow.set_row_spacing(12);
-                    cast_flow.set_margin_top(8);
-                    cast_flow.set_homogeneous(true);
-                    cast_flow.set_max_children_per_line(4);
-                    
-                    for cast_member in &movie.cast_details {
-                        let member_box = gtk::Box::new(gtk::Orientation::Vertical, 8);
-                        member_box.set_size_request(120, 200);
-                        
-                        // Photo
-                        let photo_box = gtk::Box::new(gtk::Orientation::Vertical, 0);
-                        photo_box.set_size_request(100, 150);
-                        photo_box.set_halign(gtk::Align::Center);
-                        
-                        if !cast_member.profile_path.is_empty() {
-                            // Try to load cached photo
-                            let cache_dir = std::path::Path::new(&db_clone2.borrow().posters_dir).parent().unwrap().join("cast_photos");
-                            
-                            // Extract filename the same way we save it
-                            let photo_filename = cast_member.profile_path
-                                .trim_start_matches("https://image.tmdb.org/t/p/w185")
-                                .trim_start_matches('/')
-                                .replace('/', "_");
-                            let cached_path = cache_dir.join(&photo_filename);
-                            
-                            eprintln!("Looking for cast photo: {:?} (exists: {})", cached_path, cached_path.exists());
-                            
-                            if cached_path.exists() {
-                                if let Ok(pixbuf) = gtk::gdk_pixbuf::Pixbuf::from_file(&cached_path) {
-                                    if let Some(scaled) = pixbuf.scale_simple(100, 150, gtk::gdk_pixbuf::InterpType::Bilinear) {
-                                        let photo = gtk::Picture::for_pixbuf(&scaled);
-                                        photo_box.append(&photo);
-                                    }
-                                }
-                            } else {
-                                // Placeholder if not cached
-                                let placeholder = gtk::Label::new(Some("👤"));
-                                placeholder.set_markup("<span size='xx-large'>👤</span>");
-                                photo_box.append(&placeholder);
+                            if let Some(movie_id) = found {
+                                cache.borrow_mut().matches.insert(cache_key, movie_id);
                             }
-                        } else {
-                            let placeholder = gtk::Label::new(Some("👤"));
-                            placeholder.set_markup("<span size='xx-large'>👤</span>");
-                            photo_box.append(&placeholder);
+                            found.flatten()
                         }
-                        
-                        member_box.append(&photo_box);
-                        
-                        // Name
-                        let name_label = gtk::Label::new(Some(&cast_member.name));
-                        name_label.set_xalign(0.5);
-                        name_label.set_wrap(true);
-                        name_label.set_max_width_chars(15);
-                        name_label.set_markup(&format!("<b>{}</b>", escape_markup(&cast_member.name)));
-                        member_box.append(&name_label);
-                        
-                        // Character
-                        if !cast_member.character.is_empty() {
-                            let char_label = gtk::Label::new(Some(&cast_member.character));
-                            char_label.set_xalign(0.5);
-                            char_label.set_wrap(true);
-                            char_label.set_max_width_chars(15);
-                            char_label.set_markup(&format!("<i><small>{}</small></i>", escape_markup(&cast_member.character)));
-                            member_box.append(&char_label);
+                    };
+                    let cached_movie = movie_id.and_then(|id| cache.borrow().cached_movie(media_type, id, &file_path));
+                    let fetched = match (movie_id, cached_movie) {
+                        (_, Some(movie)) => Some(movie),
+                        (Some(movie_id), None) => {
+                            let movie = fetch_movie_details_async(&client, limiter, &api_key, media_type, movie_id, file_path.clone(), posters_dir).await;
+                            if let Some(movie) = &movie {
+                                cache.borrow_mut().movies.insert((media_type, movie_id), movie.clone());
+                            }
+                            movie
                         }
-                        
-                        cast_flow.append(&member_box);
+                        (None, None) => None,
+                    };
+                    let fetched = match fetched {
+                        None if media_type == MediaType::Movie => {
+                            fetch_omdb_movie_async(&client, limiter, &omdb_api_key, &title, year, file_path.clone()).await
+                        }
+                        fetched => fetched,
+                    };
+
+                    let (message, mut movie) = match fetched {
+                        Some(movie) if movie.source == "omdb" => (format!("✓ Found on OMDb: {}", title), movie),
+                        Some(movie) => (format!("✓ Found: {}", title), movie),
+                        None => {
+                            // Create basic entry without metadata
+                            let movie = Movie {
+                                title: title.clone(),
+                                year: year.unwrap_or(0),
+                                director: String::from("Unknown"),
+                                genre: vec![String::from("Uncategorized")],
+                                description: String::from("Metadata not found"),
+                                file_paths: file_paths_from(file_path),
+                                media_type,
+                                ..Default::default()
+                            };
+                            (format!("⚠ Added without metadata: {}", title), movie)
+                        }
+                    };
+                    movie.file_paths = file_paths;
+                    // ffprobe blocks, so keep it off the runtime thread the lookups share
+                    let movie = if use_ffprobe {
+                        tokio::task::spawn_blocking(move || {
+                            let mut movie = movie;
+                            probe_movie_file(&mut movie, true);
+                            movie
+                        }).await.ok()
+                    } else {
+                        Some(movie)
+                    };
+                    let _ = sender.send_blocking(("add".to_string(), message, movie));
+                    processed.set(processed.get() + 1);
+                    let _ = sender.send_blocking(("progress".to_string(), format!("{}/{}", processed.get(), total), None));
+                }
+            })
+            .collect();
+
+        // Wait for this batch to complete
+        futures::future::join_all(futures).await;
+    }
+
+    let _ = sender.send_blocking(("complete".to_string(), String::new(), None));
+}
+
+// A library entry to refresh from TMDB; tmdb_id 0 means the title has to be looked up again
+struct RefreshTarget {
+    id: u32,
+    title: String,
+    year: u16,
+    tmdb_id: u32,
+    media_type: MediaType,
+    file_path: String,
+}
+
+// Everything a background metadata refresh needs, extracted up front like ScanJob
+struct RefreshJob {
+    targets: Vec<RefreshTarget>,
+    api_key: String,
+    posters_dir: String,
+    year_cutoff: i32,
+    min_vote_count: u32,
+    max_concurrent_requests: usize,
+}
+
+// Refresh metadata on a background thread with the scan's bounded concurrency and Cancel
+// button. Messages are those of spawn_scan_worker, except refreshed movies arrive as
+// "refresh" with the id of the entry they replace.
+fn spawn_refresh_worker(job: RefreshJob) -> ScanHandle {
+    let (sender, receiver) = async_channel::unbounded::<(String, String, Option<Movie>)>();
+    let active = ActiveScan::start();
+    let cancelled = active.cancelled.clone();
+
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(run_refresh_job(job, &cancelled, sender));
+    });
+
+    ScanHandle { receiver, active }
+}
+
+async fn run_refresh_job(job: RefreshJob, cancelled: &AtomicBool, sender: async_channel::Sender<(String, String, Option<Movie>)>) {
+    let RefreshJob { targets, api_key, posters_dir, year_cutoff, min_vote_count, max_concurrent_requests } = job;
+    let total = targets.len();
+    let processed = std::cell::Cell::new(0);
+    let failed = std::cell::Cell::new(0);
+    let _ = sender.send_blocking(("progress".to_string(), format!("0/{}", total), None));
+
+    let client = reqwest::Client::new();
+    let limiter = tmdb_limiter(max_concurrent_requests);
+    let batch_size = 10;
+
+    for batch in targets.chunks(batch_size) {
+        if SCAN_CANCELLED.load(Ordering::Relaxed) || cancelled.load(Ordering::Relaxed) {
+            break;
+        }
+
+        let futures: Vec<_> = batch.iter()
+            .map(|target| {
+                let client = client.clone();
+                let sender = sender.clone();
+                let api_key = &api_key;
+                let posters_dir = posters_dir.clone();
+                let limiter = &limiter;
+                let processed = &processed;
+                let failed = &failed;
+
+                async move {
+                    if cancelled.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    let _ = sender.send_blocking(("status".to_string(), format!("Refreshing: {}", target.title), None));
+
+                    // Stored ids skip the search; entries that never matched get another try
+                    let tmdb_id = if target.tmdb_id != 0 {
+                        Some(target.tmdb_id)
+                    } else {
+                        let year = Some(target.year).filter(|year| *year > 0);
+                        let found = match target.media_type {
+                            MediaType::Movie => search_tmdb_match(&client, limiter, api_key, &target.title, year, year_cutoff, min_vote_count).await,
+                            MediaType::Tv => search_tmdb_tv_match(&client, limiter, api_key, &target.title, year, min_vote_count).await,
+                        };
+                        found.flatten()
+                    };
+                    let fetched = match tmdb_id {
+                        Some(tmdb_id) => fetch_movie_details_async(&client, limiter, api_key, target.media_type, tmdb_id, target.file_path.clone(), posters_dir).await,
+                        None => None,
+                    };
+
+                    match fetched {
+                        Some(mut movie) => {
+                            movie.id = target.id;
+                            let _ = sender.send_blocking(("refresh".to_string(), format!("✓ Refreshed: {}", target.title), Some(movie)));
+                        }
+                        None => {
+                            failed.set(failed.get() + 1);
+                            let _ = sender.send_blocking(("status".to_string(), format!("⚠ Could not refresh: {}", target.title), None));
+                        }
+                    }
+                    processed.set(processed.get() + 1);
+                    let _ = sender.send_blocking(("progress".to_string(), format!("{}/{}", processed.get(), total), None));
+                }
+            })
+            .collect();
+
+        futures::future::join_all(futures).await;
+    }
+
+    let refreshed = processed.get() - failed.get();
+    let summary = if processed.get() < total {
+        format!("Refresh cancelled - {} movies updated, {} not reached", refreshed, total - processed.get())
+    } else {
+        format!("Refresh complete! {} succeeded, {} failed", refreshed, failed.get())
+    };
+    let _ = sender.send_blocking(("status".to_string(), summary, None));
+    let _ = sender.send_blocking(("complete".to_string(), String::new(), None));
+}
+
+// Apply scan worker messages on the main thread, calling `refresh_view` to rebuild the list
+// with the current filters once the scan completes. `on_done` runs once the worker has
+// finished, e.g. to clear the busy flag.
+fn handle_scan_messages(
+    scan: ScanHandle,
+    scanned_directories: Vec<String>,
+    db: Rc<RefCell<MovieDatabase>>,
+    status_bar: Label,
+    refresh_view: Rc<dyn Fn()>,
+    on_done: impl FnOnce() + 'static,
+) {
+    glib::spawn_future_local(async move {
+        while let Ok((msg_type, status, movie_opt)) = scan.receiver.recv().await {
+            match msg_type.as_str() {
+                "status" => {
+                    status_bar.set_text(&status);
+                }
+                "progress" => ActiveScan::show_progress(&status),
+                "add" => {
+                    if let Some(movie) = movie_opt {
+                        db.borrow_mut().add_movie(movie);
                     }
-                    
-                    details_box.append(&cast_flow);
-                } else if movie.cast_details.is_empty() && !movie.cast.is_empty() {
-                    // Show message if cast photos aren't available yet
-                    let cast_note = gtk::Label::new(None);
-                    cast_note.set_markup("<i><small>Cast photos not available. Refresh metadata to download.</small></i>");
-                    cast_note.set_xalign(0.0);
-                    cast_note.set_margin_top(8);
-                    details_box.append(&cast_note);
+                    status_bar.set_text(&status);
                 }
-                
-                // Close button
-                let close_btn = gtk::Button::with_label("Close");
-                close_btn.set_halign(gtk::Align::Center);
-                let dialog_clone = details_dialog.clone();
-                close_btn.connect_clicked(move |_| {
-                    dialog_clone.close();
-                });
-                details_box.append(&close_btn);
-                
-                scroll.set_child(Some(&details_box));
-                details_dialog.set_child(Some(&scroll));
-                details_dialog.present();
-            }
-            menu_clone_details.popdown();
-        });
-        
-        // Delete action
-        let delete_action = gtk::gio::SimpleAction::new("delete", None);
-        let db_clone3 = db_clone.clone();
-        let menu_clone2 = menu.clone();
-        let movie_title_clone2 = movie_title.clone();
-        let row_clone2 = row_clone.clone();
-        delete_action.connect_activate(move |_, _| {
-            // Delete the movie from database
-            let mut db_mut = db_clone3.borrow_mut();
-            if db_mut.delete_movie(movie_id) {
-                eprintln!("Deleted movie metadata: {}", movie_title_clone2);
-                drop(db_mut);
-                
-                // Remove the row from UI
-                if let Some(parent) = row_clone2.parent() {
-                    if let Some(list_box) = parent.downcast_ref::<ListBox>() {
-                        list_box.remove(&row_clone2);
+                "complete" => {
+                    refresh_view();
+                    if scan.active.was_cancelled() {
+                        status_bar.set_text("Scan cancelled - movies found so far were kept");
+                    } else {
+                        record_scan_completed(&scanned_directories);
+                        status_bar.set_text("Scan complete!");
                     }
+                    break;
                 }
-            } else {
-                eprintln!("Failed to delete movie - not found");
+                _ => {}
             }
-            menu_clone2.popdown();
-        });
-        
-        actions.add_action(&play_action);
-        actions.add_action(&details_action);
-        actions.add_action(&delete_action);
-        menu.insert_action_group("movie", Some(&actions));
-        
-        menu.popup();
+        }
+        on_done();
     });
-    
-    row.add_controller(gesture);
-    
-    row
 }
 
-fn create_movie_grid_item(movie: &Movie, poster_cache: &Rc<RefCell<HashMap<u32, Pixbuf>>>) -> gtk::FlowBoxChild {
-    let child = gtk::FlowBoxChild::new();
-    child.set_widget_name(&movie.id.to_string());
+// Let the user search TMDB for a different match for one file. `on_chosen` gets the
+// fully fetched movie (with `file_path` set) once a result is picked.
+fn show_tmdb_chooser(
+    parent: &Window,
+    initial_query: &str,
+    file_path: String,
+    api_key: String,
+    posters_dir: String,
+    on_chosen: impl Fn(Movie) + 'static,
+) {
+    let dialog = Window::builder()
+        .title("Choose Different Match")
+        .modal(true)
+        .transient_for(parent)
+        .default_width(450)
+        .default_height(400)
+        .build();
     
-    let vbox = Box::new(Orientation::Vertical, 8);
-    vbox.set_size_request(180, 320);
+    let content = Box::new(Orientation::Vertical, 8);
+    content.set_margin_start(12);
+    content.set_margin_end(12);
+    content.set_margin_top(12);
+    content.set_margin_bottom(12);
     
-    // Poster with overlay
-    let poster_container = gtk::Overlay::new();
-    poster_container.set_size_request(160, 240);
+    let search_row = Box::new(Orientation::Horizontal, 8);
+    let query_entry = Entry::new();
+    query_entry.set_text(initial_query);
+    query_entry.set_hexpand(true);
+    let search_btn = Button::with_label("Search");
+    search_row.append(&query_entry);
+    search_row.append(&search_btn);
+    content.append(&search_row);
     
-    let poster_box = Box::new(Orientation::Vertical, 0);
-    poster_box.set_size_request(160, 240);
+    let results_list = ListBox::new();
+    let scroll = ScrolledWindow::new();
+    scroll.set_vexpand(true);
+    scroll.set_child(Some(&results_list));
+    content.append(&scroll);
     
-    if !movie.poster_path.is_empty() && Path::new(&movie.poster_path).exists() {
-        // For grid view, always load from disk at proper size (160×240)
-        // Don't scale up the small list thumbnails - that causes blurriness!
-        if let Ok(pixbuf) = Pixbuf::from_file_at_scale(&movie.poster_path, 160, 240, true) {
-            let picture = Picture::for_pixbuf(&pixbuf);
-            picture.set_can_shrink(true);
-            poster_box.append(&picture);
-            
-            // Also ensure we have a thumbnail cached for list view
-            if poster_cache.borrow().get(&movie.id).is_none() {
-                if let Some(thumbnail) = pixbuf.scale_simple(60, 90, gtk::gdk_pixbuf::InterpType::Bilinear) {
-                    poster_cache.borrow_mut().insert(movie.id, thumbnail);
-                }
+    let button_box = Box::new(Orientation::Horizontal, 8);
+    button_box.set_halign(Align::End);
+    let cancel_btn = Button::with_label("Cancel");
+    let use_btn = Button::with_label("Use Selected");
+    button_box.append(&cancel_btn);
+    button_box.append(&use_btn);
+    content.append(&button_box);
+    dialog.set_child(Some(&content));
+    
+    let dialog_clone = dialog.clone();
+    cancel_btn.connect_clicked(move |_| {
+        dialog_clone.close();
+    });
+    
+    let run_search = {
+        let results_list = results_list.clone();
+        let query_entry = query_entry.clone();
+        let api_key = api_key.clone();
+        move || {
+            while let Some(child) = results_list.first_child() {
+                results_list.remove(&child);
             }
+            let query = query_entry.text().to_string();
+            let api_key = api_key.clone();
+            let (sender, receiver) = async_channel::bounded::<Vec<TMDBMovie>>(1);
+            std::thread::spawn(move || {
+                let _ = sender.send_blocking(search_tmdb_blocking(&api_key, &query));
+            });
+            let results_list = results_list.clone();
+            glib::spawn_future_local(async move {
+                let Ok(results) = receiver.recv().await else {
+                    return;
+                };
+                for result in results {
+                    let year = result.release_date.as_deref()
+                        .and_then(|d| d.split('-').next())
+                        .filter(|y| !y.is_empty())
+                        .unwrap_or("?");
+                    let row = gtk::ListBoxRow::new();
+                    row.set_widget_name(&result.id.to_string());
+                    let label = Label::new(Some(&format!("{} ({})", result.title, year)));
+                    label.set_xalign(0.0);
+                    label.set_margin_start(6);
+                    label.set_margin_top(4);
+                    label.set_margin_bottom(4);
+                    row.set_child(Some(&label));
+                    results_list.append(&row);
+                }
+            });
         }
-    } else {
-        let placeholder = Label::new(Some("🎬"));
-        placeholder.set_markup("<span size='70000'>🎬</span>");
-        poster_box.append(&placeholder);
-    }
+    };
+    let run_search = Rc::new(run_search);
+    run_search();
+    let run_search_clone = run_search.clone();
+    search_btn.connect_clicked(move |_| run_search_clone());
+    query_entry.connect_activate(move |_| run_search());
     
-    poster_container.set_child(Some(&poster_box));
+    let dialog_clone = dialog.clone();
+    let on_chosen = Rc::new(on_chosen);
+    use_btn.connect_clicked(move |button| {
+        let Some(tmdb_id) = results_list.selected_row().and_then(|row| row.widget_name().parse::<u32>().ok()) else {
+            return;
+        };
+        button.set_sensitive(false);
+        
+        let file_path = file_path.clone();
+        let api_key = api_key.clone();
+        let posters_dir = posters_dir.clone();
+        let (sender, receiver) = async_channel::bounded::<Option<Movie>>(1);
+        std::thread::spawn(move || {
+            let client = reqwest::blocking::Client::new();
+            let _ = sender.send_blocking(fetch_movie_by_tmdb_id(&client, tmdb_id, &api_key, file_path, &posters_dir));
+        });
+        
+        let dialog_clone = dialog_clone.clone();
+        let on_chosen = on_chosen.clone();
+        glib::spawn_future_local(async move {
+            if let Ok(Some(movie)) = receiver.recv().await {
+                on_chosen(movie);
+            }
+            dialog_clone.close();
+        });
+    });
     
-    // Add "SEEN" badge if movie has been watched
-    if !movie.watch_log.is_empty() {
-        let seen_label = Label::new(Some("SEEN"));
-        seen_label.set_markup("<span foreground='red' weight='bold' size='x-large'>SEEN</span>");
-        seen_label.set_halign(Align::Center);
-        seen_label.set_valign(Align::Center);
-        poster_container.add_overlay(&seen_label);
+    dialog.present();
+}
+
+// List each group of duplicates with a radio button per movie. The movie picked in each
+// group is kept and the rest are deleted; `on_deleted` gets the number removed.
+fn show_duplicates_dialog(parent: &Window, db: &Rc<RefCell<MovieDatabase>>, on_deleted: impl Fn(usize) + 'static) {
+    let groups: Vec<Vec<Movie>> = {
+        let db = db.borrow();
+        db.find_duplicates().iter()
+            .map(|ids| ids.iter().filter_map(|id| db.movies.get(id).cloned()).collect())
+            .collect()
+    };
+    
+    let dialog = Window::builder()
+        .title(format!("Duplicate Movies ({} groups)", groups.len()))
+        .modal(true)
+        .transient_for(parent)
+        .default_width(650)
+        .default_height(550)
+        .build();
+    
+    let content = Box::new(Orientation::Vertical, 12);
+    content.set_margin_start(12);
+    content.set_margin_end(12);
+    content.set_margin_top(12);
+    content.set_margin_bottom(12);
+    
+    let help = Label::new(Some("Choose the copy to keep in each group. The others are removed from the database; files on disk are not touched."));
+    help.set_xalign(0.0);
+    help.set_wrap(true);
+    content.append(&help);
+    
+    let groups_box = Box::new(Orientation::Vertical, 12);
+    // (movie id, keep button) for every listed movie
+    let mut choices: Vec<(u32, gtk::CheckButton)> = Vec::new();
+    for group in &groups {
+        let frame = gtk::Frame::new(Some(&format!("{} ({})", group[0].title, group[0].year)));
+        let group_box = Box::new(Orientation::Vertical, 4);
+        group_box.set_margin_start(8);
+        group_box.set_margin_end(8);
+        group_box.set_margin_top(6);
+        group_box.set_margin_bottom(6);
+        
+        // Keep the first copy whose file still exists, or else the oldest entry
+        let keep_id = group.iter()
+            .find(|m| !m.existing_files().is_empty())
+            .unwrap_or(&group[0])
+            .id;
+        let mut first_button: Option<gtk::CheckButton> = None;
+        for movie in group {
+            let file_text = if movie.file_paths.is_empty() {
+                String::from("No file")
+            } else {
+                movie.file_paths.join(", ")
+            };
+            let button = gtk::CheckButton::with_label(&format!("{} ({}) - {}", movie.title, movie.year, file_text));
+            button.set_group(first_button.as_ref());
+            button.set_active(movie.id == keep_id);
+            first_button.get_or_insert_with(|| button.clone());
+            group_box.append(&button);
+            choices.push((movie.id, button));
+        }
+        
+        frame.set_child(Some(&group_box));
+        groups_box.append(&frame);
     }
     
-    vbox.append(&poster_container);
+    let scroll = ScrolledWindow::new();
+    scroll.set_vexpand(true);
+    scroll.set_child(Some(&groups_box));
+    content.append(&scroll);
     
-    // Title and year
-    let title_label = Label::new(Some(&format!("{} ({})", movie.title, movie.year)));
-    title_label.set_wrap(true);
-    title_label.set_wrap_mode(gtk::pango::WrapMode::WordChar);
-    title_label.set_max_width_chars(20);
-    title_label.set_justify(gtk::Justification::Center);
-    let escaped_title = escape_markup(&movie.title);
-    title_label.set_markup(&format!("<b>{}</b>\n<small>({})</small>", escaped_title, movie.year));
+    let button_box = Box::new(Orientation::Horizontal, 8);
+    button_box.set_halign(Align::End);
+    let cancel_btn = Button::with_label("Cancel");
+    let delete_btn = Button::with_label("Delete Duplicates");
+    delete_btn.add_css_class("destructive-action");
+    button_box.append(&cancel_btn);
+    button_box.append(&delete_btn);
+    content.append(&button_box);
+    dialog.set_child(Some(&content));
     
-    // Rating
-    let rating_label = Label::new(Some(&format!("⭐ {:.1}/10", movie.rating)));
-    rating_label.set_opacity(0.8);
+    let dialog_clone = dialog.clone();
+    cancel_btn.connect_clicked(move |_| {
+        dialog_clone.close();
+    });
     
-    vbox.append(&title_label);
-    vbox.append(&rating_label);
+    let dialog_clone = dialog.clone();
+    let db = db.clone();
+    let on_deleted = Rc::new(on_deleted);
+    delete_btn.connect_clicked(move |_| {
+        let to_delete: Vec<u32> = choices.iter()
+            .filter(|(_, button)| !button.is_active())
+            .map(|(id, _)| *id)
+            .collect();
+        let confirmed = confirm(
+            &dialog_clone,
+            "Delete Duplicates",
+            &format!("Remove {} duplicate movies from the database?", to_delete.len()),
+            "Delete",
+        );
+        let dialog_clone2 = dialog_clone.clone();
+        let db = db.clone();
+        let on_deleted = on_deleted.clone();
+        glib::spawn_future_local(async move {
+            if confirmed.await {
+                let removed = db.borrow_mut().delete_many(&to_delete);
+                let poster_cache = db.borrow().poster_cache.clone();
+                for id in &to_delete {
+                    poster_cache.borrow_mut().remove(id);
+                }
+                dialog_clone2.close();
+                on_deleted(removed);
+            }
+        });
+    });
     
-    child.set_child(Some(&vbox));
-    child
+    dialog.present();
 }
 
-fn show_api_key_dialog(window: &ApplicationWindow) -> Option<String> {
-    // Try to load existing config first
-    if let Some(config) = load_config() {
-        if !config.tmdb_api_key.is_empty() {
-            println!("Loaded API key from config");
-            return Some(config.tmdb_api_key);
-        }
-    }
-    
+// Review proposed scan matches before anything is added. Each row can be accepted,
+// skipped, or re-matched; `on_commit` receives the accepted movies and
+// `on_discard` the posters fetched for the rest.
+fn show_scan_review_dialog(
+    parent: &Window,
+    proposals: Vec<Movie>,
+    api_key: String,
+    posters_dir: String,
+    on_commit: impl Fn(Vec<Movie>) + 'static,
+    on_discard: impl Fn(Vec<String>) + 'static,
+) {
     let dialog = Window::builder()
-        .title("TMDB API Key Required")
+        .title(format!("Review Scan Matches ({})", proposals.len()))
         .modal(true)
-        .transient_for(window)
-        .default_width(500)
-        .default_height(220)
+        .transient_for(parent)
+        .default_width(750)
+        .default_height(600)
         .build();
-
+    
     let content = Box::new(Orientation::Vertical, 12);
     content.set_margin_start(12);
     content.set_margin_end(12);
     content.set_margin_top(12);
     content.set_margin_bottom(12);
-
-    let info_label = Label::new(Some(
-        "To fetch movie metadata, you need a TMDB API key.\n\
-        Get one free at: https://www.themoviedb.org/settings/api\n\n\
-        Enter your API key below (it will be saved for future use):"
+    
+    let help = Label::new(Some("Uncheck wrong matches to skip them, or choose a different match. Only checked movies are added."));
+    help.set_xalign(0.0);
+    help.set_wrap(true);
+    content.append(&help);
+    
+    // (proposed movie, accepted)
+    let entries: Rc<RefCell<Vec<(Movie, bool)>>> = Rc::new(RefCell::new(
+        proposals.into_iter().map(|m| { let accept = m.tmdb_id != 0; (m, accept) }).collect()
     ));
-    info_label.set_wrap(true);
-
-    let api_entry = Entry::new();
-    api_entry.set_placeholder_text(Some("Enter TMDB API key"));
-    api_entry.set_visibility(false);  // Hide the key like a password
-
+    // Posters of matches swapped out with Choose Different
+    let replaced_posters: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(Vec::new()));
+    let committed = Rc::new(std::cell::Cell::new(false));
+    
+    let review_list = ListBox::new();
+    review_list.set_selection_mode(gtk::SelectionMode::None);
+    let count = entries.borrow().len();
+    for idx in 0..count {
+        let (movie, accepted) = entries.borrow()[idx].clone();
+        let row_box = Box::new(Orientation::Horizontal, 8);
+        row_box.set_margin_start(6);
+        row_box.set_margin_end(6);
+        row_box.set_margin_top(4);
+        row_box.set_margin_bottom(4);
+        
+        let accept_check = gtk::CheckButton::new();
+        accept_check.set_active(accepted);
+        row_box.append(&accept_check);
+        
+        let poster = Picture::new();
+        poster.set_size_request(40, 60);
+        poster.set_can_shrink(true);
+        row_box.append(&poster);
+        
+        let info_label = Label::new(None);
+        info_label.set_xalign(0.0);
+        info_label.set_hexpand(true);
+        info_label.set_wrap(true);
+        row_box.append(&info_label);
+        
+        // Show a proposal in this row
+        let show_proposal = {
+            let poster = poster.clone();
+            let info_label = info_label.clone();
+            move |movie: &Movie| {
+                let file_name = Path::new(movie.primary_file()).file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let match_text = if movie.tmdb_id != 0 {
+                    format!("<b>{}</b> ({})", escape_markup(&movie.title), movie.year)
+                } else {
+                    format!("<b>{}</b> - <i>no match found</i>", escape_markup(&movie.title))
+                };
+                info_label.set_markup(&format!("{}\n<small>{}</small>", match_text, escape_markup(&file_name)));
+                let thumbnail = Pixbuf::from_file_at_scale(&movie.poster_path, 40, 60, true).ok();
+                poster.set_pixbuf(thumbnail.as_ref());
+            }
+        };
+        show_proposal(&movie);
+        
+        let entries_clone = entries.clone();
+        accept_check.connect_toggled(move |check| {
+            entries_clone.borrow_mut()[idx].1 = check.is_active();
+        });
+        
+        let choose_btn = Button::with_label("Choose Different...");
+        let dialog_clone = dialog.clone();
+        let entries_clone = entries.clone();
+        let api_key = api_key.clone();
+        let posters_dir = posters_dir.clone();
+        let accept_check_clone = accept_check.clone();
+        let replaced_posters = replaced_posters.clone();
+        choose_btn.connect_clicked(move |_| {
+            let (query, file_path) = {
+                let entries = entries_clone.borrow();
+                let movie = &entries[idx].0;
+                let query = Path::new(movie.primary_file()).file_stem()
+                    .map(|stem| stem.to_string_lossy().replace(['.', '_'], " "))
+                    .unwrap_or_else(|| movie.title.clone());
+                (query, movie.primary_file().to_string())
+            };
+            let entries_clone = entries_clone.clone();
+            let show_proposal = show_proposal.clone();
+            let accept_check = accept_check_clone.clone();
+            let replaced_posters = replaced_posters.clone();
+            show_tmdb_chooser(dialog_clone.upcast_ref(), &query, file_path, api_key.clone(), posters_dir.clone(), move |movie| {
+                show_proposal(&movie);
+                let replaced = std::mem::replace(&mut entries_clone.borrow_mut()[idx], (movie, true));
+                replaced_posters.borrow_mut().push(replaced.0.poster_path);
+                accept_check.set_active(true);
+            });
+        });
+        row_box.append(&choose_btn);
+        
+        review_list.append(&row_box);
+    }
+    
+    let scroll = ScrolledWindow::new();
+    scroll.set_vexpand(true);
+    scroll.set_child(Some(&review_list));
+    content.append(&scroll);
+    
     let button_box = Box::new(Orientation::Horizontal, 8);
-    button_box.set_halign(gtk::Align::End);
-    let ok_btn = Button::with_label("OK");
-    button_box.append(&ok_btn);
-
-    content.append(&info_label);
-    content.append(&api_entry);
+    button_box.set_halign(Align::End);
+    let cancel_btn = Button::with_label("Discard All");
+    let commit_btn = Button::with_label("Add Checked Movies");
+    button_box.append(&cancel_btn);
+    button_box.append(&commit_btn);
     content.append(&button_box);
-
     dialog.set_child(Some(&content));
-
-    let api_key = Rc::new(RefCell::new(String::new()));
-    let api_key_clone = api_key.clone();
+    
+    let dialog_clone = dialog.clone();
+    cancel_btn.connect_clicked(move |_| {
+        dialog_clone.close();
+    });
+    
     let dialog_clone = dialog.clone();
+    let entries_clone = entries.clone();
+    let committed_clone = committed.clone();
+    commit_btn.connect_clicked(move |_| {
+        let accepted: Vec<Movie> = entries_clone.borrow().iter()
+            .filter(|(_, accepted)| *accepted)
+            .map(|(movie, _)| movie.clone())
+            .collect();
+        on_commit(accepted);
+        committed_clone.set(true);
+        dialog_clone.close();
+    });
+    
+    // Posters were downloaded for every proposal; hand back those of the ones not added
+    dialog.connect_close_request(move |_| {
+        let mut unused = std::mem::take(&mut *replaced_posters.borrow_mut());
+        unused.extend(entries.borrow_mut().drain(..)
+            .filter(|(_, accepted)| !(committed.get() && *accepted))
+            .map(|(movie, _)| movie.poster_path));
+        on_discard(unused);
+        glib::Propagation::Proceed
+    });
+    
+    dialog.present();
+}
+
+// Edit dialog field validation. Year 0 (or blank) means unknown.
+fn parse_edit_year(text: &str) -> Result<u16, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(0);
+    }
+    let max_year = chrono::Datelike::year(&chrono::Local::now()) + 5;
+    match text.parse::<u16>() {
+        Ok(0) => Ok(0),
+        Ok(year) if (1870..=max_year).contains(&(year as i32)) => Ok(year),
+        _ => Err(format!("Year must be between 1870 and {} (or 0 if unknown)", max_year)),
+    }
+}
+
+fn parse_edit_rating(text: &str) -> Result<f32, String> {
+    match text.trim().parse::<f32>() {
+        Ok(rating) if (0.0..=10.0).contains(&rating) => Ok(rating),
+        _ => Err("Rating must be a number from 0 to 10".to_string()),
+    }
+}
+
+// Personal rating is optional - blank clears it
+fn parse_edit_my_rating(text: &str) -> Result<Option<f32>, String> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    parse_edit_rating(text).map(Some)
+}
+
+fn parse_edit_runtime(text: &str) -> Result<u16, String> {
+    match text.trim().parse::<u16>() {
+        Ok(runtime) => Ok(runtime),
+        Err(_) => Err("Runtime must be a whole number of minutes (0 if unknown)".to_string()),
+    }
+}
+
+// An Edit dialog entry with an inline error line underneath. Invalid input gets a red
+// border, the message, and a revert icon that restores `original`.
+fn validated_entry(original: &str) -> (Box, Entry, Label) {
+    let field_box = Box::new(Orientation::Vertical, 2);
+    let entry = Entry::new();
+    entry.set_text(original);
+    let error_label = Label::new(None);
+    error_label.set_xalign(0.0);
+    error_label.set_wrap(true);
+    error_label.set_visible(false);
+    field_box.append(&entry);
+    field_box.append(&error_label);
     
-    ok_btn.connect_clicked(move |_| {
-        let key = api_entry.text().to_string();
-        if !key.is_empty() {
-            // Save the API key to config, preserving existing settings
-            let mut config = load_config().unwrap_or_default();
-            config.tmdb_api_key = key.clone();
-            
-            if let Err(e) = save_config(&config) {
-                eprintln!("Warning: Could not save config: {}", e);
-            } else {
-                println!("API key saved to config");
-            }
-            *api_key_clone.borrow_mut() = key;
+    let original = original.to_string();
+    entry.connect_icon_press(move |entry, position| {
+        if position == gtk::EntryIconPosition::Secondary {
+            entry.set_text(&original);
         }
-        dialog_clone.close();
     });
-
-    dialog.present();
-    
-    while dialog.is_visible() {
-        gtk::glib::MainContext::default().iteration(true);
-    }
+    let error_label_clone = error_label.clone();
+    entry.connect_changed(move |entry| {
+        set_field_error(entry, &error_label_clone, None);
+    });
     
-    let key = api_key.borrow().clone();
-    if key.is_empty() {
-        None
-    } else {
-        Some(key)
-    }
+    (field_box, entry, error_label)
 }
 
-// Helper function to recursively scan directories for video files
-fn scan_directory_recursive(
-    dir: &Path,
-    video_extensions: &[&str],
-    files: &mut Vec<(String, String)>,
-) {
-    if let Ok(entries) = read_dir(dir) {
-        for entry in entries.flatten() {
-            let entry_path = entry.path();
-            
-            if entry_path.is_dir() {
-                // Recursively scan subdirectories
-                scan_directory_recursive(&entry_path, video_extensions, files);
-            } else if entry_path.is_file() {
-                if let Some(ext) = entry_path.extension() {
-                    let ext_str = ext.to_string_lossy().to_lowercase();
-                    if video_extensions.contains(&ext_str.as_str()) {
-                        if let Some(file_name) = entry_path.file_stem() {
-                            let title = file_name.to_string_lossy().to_string();
-                            let file_path_str = entry_path.to_string_lossy().to_string();
-                            
-                            let clean_title = title
-                                .replace('.', " ")
-                                .replace('_', " ")
-                                .trim()
-                                .to_string();
-                            
-                            files.push((clean_title, file_path_str));
-                        }
-                    }
-                }
-            }
+fn set_field_error(entry: &Entry, error_label: &Label, error: Option<&str>) {
+    match error {
+        Some(message) => {
+            entry.add_css_class("error");
+            entry.set_secondary_icon_name(Some("edit-undo-symbolic"));
+            entry.set_secondary_icon_tooltip_text(Some("Revert to the saved value"));
+            error_label.set_markup(&format!("<small><span foreground='#c01c28'>{}</span></small>", escape_markup(message)));
+            error_label.set_visible(true);
+        }
+        None => {
+            entry.remove_css_class("error");
+            entry.set_secondary_icon_name(None);
+            error_label.set_visible(false);
         }
     }
 }
 
+// One row of the cast editor in Edit Metadata
+struct CastEditRow {
+    row: Box,
+    name: Entry,
+    character: Entry,
+    original_name: String,
+    profile_path: String,
+    local_photo_path: String,
+}
+
+// Add an actor/character row to the cast editor. Removing it drops it from `rows` too.
+fn add_cast_edit_row(rows_box: &Box, rows: &Rc<RefCell<Vec<CastEditRow>>>, member: &CastMember) {
+    let row = Box::new(Orientation::Horizontal, 6);
+    let name = Entry::new();
+    name.set_text(&member.name);
+    name.set_placeholder_text(Some("Actor"));
+    name.set_hexpand(true);
+    let character = Entry::new();
+    character.set_text(&member.character);
+    character.set_placeholder_text(Some("Character"));
+    character.set_hexpand(true);
+    let remove_button = Button::from_icon_name("list-remove-symbolic");
+    remove_button.set_tooltip_text(Some("Remove from cast"));
+    row.append(&name);
+    row.append(&character);
+    row.append(&remove_button);
+    rows_box.append(&row);
+
+    // Weak, as the rows own this button's handler
+    let rows_weak = Rc::downgrade(rows);
+    let rows_box = rows_box.clone();
+    let row_clone = row.clone();
+    remove_button.connect_clicked(move |_| {
+        rows_box.remove(&row_clone);
+        if let Some(rows) = rows_weak.upgrade() {
+            rows.borrow_mut().retain(|r| r.row != row_clone);
+        }
+    });
+
+    rows.borrow_mut().push(CastEditRow {
+        row,
+        name,
+        character,
+        original_name: member.name.clone(),
+        profile_path: member.profile_path.clone(),
+        local_photo_path: member.local_photo_path.clone(),
+    });
+}
+
+// Cast from the editor rows, skipping rows without a name. A row keeps its TMDB photo
+// only while the actor name is unchanged.
+fn cast_from_edit_rows(rows: &[CastEditRow]) -> Vec<CastMember> {
+    rows.iter()
+        .filter_map(|row| {
+            let name = row.name.text().trim().to_string();
+            if name.is_empty() {
+                return None;
+            }
+            let (profile_path, local_photo_path) = if name == row.original_name {
+                (row.profile_path.clone(), row.local_photo_path.clone())
+            } else {
+                (String::new(), String::new())
+            };
+            Some(CastMember { character: row.character.text().trim().to_string(), name, profile_path, local_photo_path })
+        })
+        .collect()
+}
+
 fn build_ui(app: &Application) {
+    let startup_config = load_config().unwrap_or_default();
     let window = ApplicationWindow::builder()
         .application(app)
         .title("Mark's Movie Database (MMDB)")
-        .default_width(1000)
-        .default_height(700)
-        .maximized(true)
+        .default_width(startup_config.window_width.max(400))
+        .default_height(startup_config.window_height.max(300))
+        .maximized(startup_config.window_maximized)
         .build();
 
-    let api_key = match show_api_key_dialog(&window) {
+    apply_theme(&startup_config.theme);
+    LIST_THUMB_SIZE.with(|size| size.set(startup_config.list_thumb_size()));
+    set_tmdb_language(&startup_config.tmdb_language);
+
+    let overrides = cli_overrides();
+    let api_key = match overrides.api_key.clone().or_else(|| show_api_key_dialog(&window)) {
         Some(key) => key,
         None => {
             eprintln!("No API key provided. Exiting.");
@@ -1294,12 +6585,13 @@ fn build_ui(app: &Application) {
     };
 
     // Create data directory in home folder for consistent storage
-    let data_dir = dirs::home_dir()
-        .expect("Could not find home directory")
-        .join(".movie_database");
+    let data_dir = get_data_dir();
     std::fs::create_dir_all(&data_dir).expect("Could not create data directory");
+    if overrides.data_file.is_none() {
+        migrate_cwd_data(&data_dir);
+    }
     
-    let db_path = data_dir.join("movies.db").to_string_lossy().to_string();
+    let db_path = resolve_data_file(&overrides, &data_dir);
     let posters_dir = data_dir.join("posters").to_string_lossy().to_string();
     std::fs::create_dir_all(&posters_dir).expect("Could not create posters directory");
 
@@ -1320,27 +6612,114 @@ fn build_ui(app: &Application) {
     title_label.set_markup("<span size='x-large' weight='bold'>📽️ Mark's Movie Database</span>");
     
     let scan_button = Button::with_label("📁 Scan Directory");
+    let cancel_scan_button = Button::with_label("⏹ Cancel Scan");
+    cancel_scan_button.set_tooltip_text(Some("Stop the running scan; movies found so far are kept"));
+    cancel_scan_button.set_visible(false);
     let add_button = Button::with_label("➕ Add Movie");
     let refresh_button = Button::with_label("🔄 Refresh Metadata");
-    let refresh_all_button = Button::with_label("🔄 Refresh All");
-    refresh_all_button.set_tooltip_text(Some("Refresh metadata and posters for ALL movies"));
     let edit_button = Button::with_label("✏️ Edit Metadata");
     let select_version_button = Button::with_label("🎞️ Wrong Movie?");
-    let stats_button = Button::with_label("📊 Statistics");
-    let settings_button = Button::with_label("⚙️ Settings");
     
     header.append(&title_label);
     header.append(&Box::new(Orientation::Horizontal, 0));
     header.set_hexpand(true);
     title_label.set_hexpand(true);
-    header.append(&stats_button);
-    header.append(&settings_button);
-    header.append(&refresh_all_button);
+    // Secondary actions live in the app menu; the handlers are attached to the "win." actions below
+    let preview_scan_action = gtk::gio::SimpleAction::new("preview-scan", None);
+    let stats_action = gtk::gio::SimpleAction::new("statistics", None);
+    let refresh_all_action = gtk::gio::SimpleAction::new("refresh-all", None);
+    let refresh_shown_action = gtk::gio::SimpleAction::new("refresh-shown", None);
+    let missing_posters_action = gtk::gio::SimpleAction::new("missing-posters", None);
+    let clean_posters_action = gtk::gio::SimpleAction::new("clean-posters", None);
+    let prune_missing_action = gtk::gio::SimpleAction::new("prune-missing", None);
+    let find_duplicates_action = gtk::gio::SimpleAction::new("find-duplicates", None);
+    let settings_action = gtk::gio::SimpleAction::new("settings", None);
+    let diagnostics_action = gtk::gio::SimpleAction::new("diagnostics", None);
+    let export_backup_action = gtk::gio::SimpleAction::new("export-backup", None);
+    let import_backup_action = gtk::gio::SimpleAction::new("import-backup", None);
+    let rescan_local_action = gtk::gio::SimpleAction::new("rescan-local", None);
+    let rescan_library_action = gtk::gio::SimpleAction::new("rescan-library", None);
+    let mark_shown_watched_action = gtk::gio::SimpleAction::new("mark-shown-watched", None);
+    for action in [&rescan_library_action, &rescan_local_action, &mark_shown_watched_action, &preview_scan_action, &stats_action, &refresh_all_action, &refresh_shown_action, &missing_posters_action, &clean_posters_action, &prune_missing_action, &find_duplicates_action, &settings_action, &diagnostics_action, &export_backup_action, &import_backup_action] {
+        window.add_action(action);
+    }
+    
+    // Movie row context menu entries that run the toolbar buttons on the selected movie
+    for (name, button) in [("refresh-selected", &refresh_button), ("edit-selected", &edit_button), ("wrong-movie-selected", &select_version_button)] {
+        let action = gtk::gio::SimpleAction::new(name, None);
+        let button = button.clone();
+        // emit_clicked ignores sensitivity, so respect set_busy's disabling here
+        action.connect_activate(move |_, _| {
+            if button.is_sensitive() {
+                button.emit_clicked();
+            }
+        });
+        window.add_action(&action);
+    }
+    
+    let app_menu = gtk::gio::Menu::new();
+    let library_section = gtk::gio::Menu::new();
+    library_section.append(Some("🔍 Preview Scan..."), Some("win.preview-scan"));
+    library_section.append(Some("🔁 Rescan Library..."), Some("win.rescan-library"));
+    library_section.append(Some("📝 Rescan Local Files"), Some("win.rescan-local"));
+    library_section.append(Some("✅ Mark Shown as Watched"), Some("win.mark-shown-watched"));
+    library_section.append(Some("📊 Statistics"), Some("win.statistics"));
+    library_section.append(Some("🔄 Refresh All Metadata"), Some("win.refresh-all"));
+    library_section.append(Some("🔄 Refresh Shown Metadata"), Some("win.refresh-shown"));
+    library_section.append(Some("🖼️ Fix Missing Posters..."), Some("win.missing-posters"));
+    library_section.append(Some("🧽 Clean Poster Cache..."), Some("win.clean-posters"));
+    library_section.append(Some("🧹 Prune Missing Files"), Some("win.prune-missing"));
+    library_section.append(Some("👯 Find Duplicates..."), Some("win.find-duplicates"));
+    app_menu.append_section(None, &library_section);
+    let backup_section = gtk::gio::Menu::new();
+    backup_section.append(Some("💾 Export Backup..."), Some("win.export-backup"));
+    backup_section.append(Some("📥 Import Backup..."), Some("win.import-backup"));
+    app_menu.append_section(None, &backup_section);
+    let app_section = gtk::gio::Menu::new();
+    app_section.append(Some("⚙️ Settings"), Some("win.settings"));
+    app_section.append(Some("🩺 Diagnostics"), Some("win.diagnostics"));
+    app_menu.append_section(None, &app_section);
+    
+    let menu_button = gtk::MenuButton::new();
+    menu_button.set_icon_name("open-menu-symbolic");
+    menu_button.set_tooltip_text(Some("More actions"));
+    menu_button.set_menu_model(Some(&app_menu));
+    
+    // Spins while scans, refreshes or adds run in the background
+    let busy_spinner = gtk::Spinner::new();
+    busy_spinner.set_tooltip_text(Some("Working…"));
+    busy_spinner.set_visible(false);
+    
+    // Set while a scan, refresh or add runs in the background. Handlers check it before
+    // starting one, so a second can't start on top of the first and race it for the
+    // database. set_busy also greys out the buttons and menu actions that start them.
+    let busy = Rc::new(std::cell::Cell::new(false));
+    let set_busy: Rc<dyn Fn(bool)> = {
+        let busy = busy.clone();
+        let buttons = [scan_button.clone(), add_button.clone(), refresh_button.clone(), select_version_button.clone()];
+        let actions = [preview_scan_action.clone(), rescan_library_action.clone(), refresh_all_action.clone(), refresh_shown_action.clone(), clean_posters_action.clone(), import_backup_action.clone()];
+        let spinner = busy_spinner.clone();
+        Rc::new(move |on| {
+            busy.set(on);
+            for button in &buttons {
+                button.set_sensitive(!on);
+            }
+            for action in &actions {
+                action.set_enabled(!on);
+            }
+            spinner.set_visible(on);
+            spinner.set_spinning(on);
+        })
+    };
+    
+    header.append(&busy_spinner);
     header.append(&edit_button);
     header.append(&select_version_button);
     header.append(&refresh_button);
     header.append(&scan_button);
+    header.append(&cancel_scan_button);
     header.append(&add_button);
+    header.append(&menu_button);
 
     main_box.append(&header);
     main_box.append(&Separator::new(Orientation::Horizontal));
@@ -1358,8 +6737,28 @@ fn build_ui(app: &Application) {
     
     let loading_spinner = gtk::Spinner::new();
     loading_spinner.set_visible(false); // Hidden by default
+
+    // Scan and refresh progress; hidden while idle
+    let progress_bar = gtk::ProgressBar::new();
+    progress_bar.set_show_text(true);
+    progress_bar.set_valign(gtk::Align::Center);
+    progress_bar.set_width_request(220);
+    progress_bar.set_visible(false);
+    ActiveScan::set_controls(&cancel_scan_button, &progress_bar);
+    
+    let undo_delete_button = Button::with_label("↶ Undo Delete");
+    undo_delete_button.set_visible(false);
+    UNDO_DELETE_BUTTON.with(|button| *button.borrow_mut() = Some(undo_delete_button.clone()));
+    
+    let save_retry_button = Button::with_label("⚠️ Not Saved - Retry");
+    save_retry_button.add_css_class("destructive-action");
+    save_retry_button.set_visible(false);
+    SAVE_RETRY_BUTTON.with(|button| *button.borrow_mut() = Some(save_retry_button.clone()));
     
     status_bar_box.append(&status_bar);
+    status_bar_box.append(&save_retry_button);
+    status_bar_box.append(&undo_delete_button);
+    status_bar_box.append(&progress_bar);
     status_bar_box.append(&loading_spinner);
     main_box.append(&status_bar_box);
 
@@ -1372,18 +6771,187 @@ fn build_ui(app: &Application) {
     let search_entry = SearchEntry::new();
     search_entry.set_placeholder_text(Some("Search movies..."));
     search_entry.set_hexpand(true);
-
-    let genres = StringList::new(&["All", "Action", "Comedy", "Drama", "Film Noir", "Horror", "Sci-Fi", "Thriller", "Romance"]);
-    let genre_dropdown = DropDown::new(Some(genres), None::<gtk::Expression>);
-    genre_dropdown.set_selected(0);
-
-    let sort_options = StringList::new(&["Title (A-Z)", "Year (Newest)", "Year (Oldest)", "Rating (High-Low)", "Rating (Low-High)", "Date Added (Newest)", "Date Added (Oldest)"]);
+    search_entry.set_search_delay(0);  // Debounced by SEARCH_DEBOUNCE_MS instead
+
+    let search_scope_labels: Vec<&str> = SEARCH_SCOPES.iter().map(|(label, _)| *label).collect();
+    let search_scope_dropdown = DropDown::new(Some(StringList::new(&search_scope_labels)), None::<gtk::Expression>);
+    search_scope_dropdown.set_selected(0);
+    search_scope_dropdown.set_tooltip_text(Some("Search titles only, or also directors, cast and plot descriptions"));
+
+    // Genre filter: a popover of checkboxes for the genres in the library
+    let selected_genres: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(Vec::new()));
+    let genre_checks_box = Box::new(Orientation::Vertical, 2);
+    let genre_checks_scroll = ScrolledWindow::new();
+    genre_checks_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    genre_checks_scroll.set_propagate_natural_height(true);
+    genre_checks_scroll.set_max_content_height(360);
+    genre_checks_scroll.set_child(Some(&genre_checks_box));
+    let match_all_genres_check = gtk::CheckButton::with_label("Match all selected");
+    match_all_genres_check.set_tooltip_text(Some("Only show movies that have every checked genre"));
+    let clear_genres_button = Button::with_label("Clear");
+    let genre_popover_box = Box::new(Orientation::Vertical, 6);
+    genre_popover_box.set_margin_start(6);
+    genre_popover_box.set_margin_end(6);
+    genre_popover_box.set_margin_top(6);
+    genre_popover_box.set_margin_bottom(6);
+    genre_popover_box.append(&genre_checks_scroll);
+    genre_popover_box.append(&Separator::new(Orientation::Horizontal));
+    genre_popover_box.append(&match_all_genres_check);
+    genre_popover_box.append(&clear_genres_button);
+    let genre_popover = gtk::Popover::new();
+    genre_popover.set_child(Some(&genre_popover_box));
+    let genre_button = gtk::MenuButton::new();
+    genre_button.set_label(&genre_filter_label(&[], false));
+    genre_button.set_popover(Some(&genre_popover));
+    genre_button.set_tooltip_text(Some("Filter by one or more genres"));
+
+    // Tag filter: same idea, for the user's own tags
+    let selected_tags: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(Vec::new()));
+    let tag_checks_box = Box::new(Orientation::Vertical, 2);
+    let tag_checks_scroll = ScrolledWindow::new();
+    tag_checks_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    tag_checks_scroll.set_propagate_natural_height(true);
+    tag_checks_scroll.set_max_content_height(360);
+    tag_checks_scroll.set_child(Some(&tag_checks_box));
+    let clear_tags_button = Button::with_label("Clear");
+    let tag_popover_box = Box::new(Orientation::Vertical, 6);
+    tag_popover_box.set_margin_start(6);
+    tag_popover_box.set_margin_end(6);
+    tag_popover_box.set_margin_top(6);
+    tag_popover_box.set_margin_bottom(6);
+    tag_popover_box.append(&tag_checks_scroll);
+    tag_popover_box.append(&Separator::new(Orientation::Horizontal));
+    tag_popover_box.append(&clear_tags_button);
+    let tag_popover = gtk::Popover::new();
+    tag_popover.set_child(Some(&tag_popover_box));
+    let tag_button = gtk::MenuButton::new();
+    tag_button.set_label(&tag_filter_label(&[]));
+    tag_button.set_popover(Some(&tag_popover));
+    tag_button.set_tooltip_text(Some("Only show movies with every checked tag"));
+
+    // Collection filter: a popover listing the library's TMDB collections, to show one series
+    let selected_collection: Rc<RefCell<Option<(u32, String)>>> = Rc::new(RefCell::new(None));
+    let collection_list_box = Box::new(Orientation::Vertical, 2);
+    let collection_list_scroll = ScrolledWindow::new();
+    collection_list_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    collection_list_scroll.set_propagate_natural_height(true);
+    collection_list_scroll.set_max_content_height(360);
+    collection_list_scroll.set_child(Some(&collection_list_box));
+    let clear_collection_button = Button::with_label("All collections");
+    let collection_popover_box = Box::new(Orientation::Vertical, 6);
+    collection_popover_box.set_margin_start(6);
+    collection_popover_box.set_margin_end(6);
+    collection_popover_box.set_margin_top(6);
+    collection_popover_box.set_margin_bottom(6);
+    collection_popover_box.append(&collection_list_scroll);
+    collection_popover_box.append(&Separator::new(Orientation::Horizontal));
+    collection_popover_box.append(&clear_collection_button);
+    let collection_popover = gtk::Popover::new();
+    collection_popover.set_child(Some(&collection_popover_box));
+    let collection_button = gtk::MenuButton::new();
+    collection_button.set_label(&collection_filter_label(None));
+    collection_button.set_popover(Some(&collection_popover));
+    collection_button.set_tooltip_text(Some("Only show the films of one TMDB collection (e.g. a franchise)"));
+
+    // Age rating filter: checkboxes for the ratings in the library, any of which may match
+    let selected_certifications: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(Vec::new()));
+    let certification_checks_box = Box::new(Orientation::Vertical, 2);
+    let clear_certifications_button = Button::with_label("Clear");
+    let certification_popover_box = Box::new(Orientation::Vertical, 6);
+    certification_popover_box.set_margin_start(6);
+    certification_popover_box.set_margin_end(6);
+    certification_popover_box.set_margin_top(6);
+    certification_popover_box.set_margin_bottom(6);
+    certification_popover_box.append(&certification_checks_box);
+    certification_popover_box.append(&Separator::new(Orientation::Horizontal));
+    certification_popover_box.append(&clear_certifications_button);
+    let certification_popover = gtk::Popover::new();
+    certification_popover.set_child(Some(&certification_popover_box));
+    let certification_button = gtk::MenuButton::new();
+    certification_button.set_label(&certification_filter_label(&[]));
+    certification_button.set_popover(Some(&certification_popover));
+    certification_button.set_tooltip_text(Some("Only show movies with one of the checked age ratings (e.g. G and PG for kids)"));
+
+    let sort_options = StringList::new(&SORT_OPTIONS);
     let sort_dropdown = DropDown::new(Some(sort_options), None::<gtk::Expression>);
     sort_dropdown.set_selected(0);
 
+    let runtime_labels: Vec<&str> = RUNTIME_LIMITS.iter().map(|(label, _)| *label).collect();
+    let runtime_dropdown = DropDown::new(Some(StringList::new(&runtime_labels)), None::<gtk::Expression>);
+    runtime_dropdown.set_selected(0);
+    runtime_dropdown.set_tooltip_text(Some("Only show movies that fit in the time you have"));
+
+    // Year and rating filter: a popover with the year range and a minimum rating slider
+    let max_year = max_filter_year();
+    let year_from_spin = gtk::SpinButton::with_range(MIN_FILTER_YEAR as f64, max_year as f64, 1.0);
+    year_from_spin.set_value(MIN_FILTER_YEAR as f64);
+    let year_to_spin = gtk::SpinButton::with_range(MIN_FILTER_YEAR as f64, max_year as f64, 1.0);
+    year_to_spin.set_value(max_year as f64);
+    let min_rating_scale = gtk::Scale::with_range(Orientation::Horizontal, 0.0, 10.0, 0.5);
+    min_rating_scale.set_digits(1);
+    min_rating_scale.set_draw_value(true);
+    min_rating_scale.set_width_request(200);
+    for mark in [0.0, 5.0, 7.0, 8.0, 10.0] {
+        min_rating_scale.add_mark(mark, gtk::PositionType::Bottom, None);
+    }
+    let clear_year_rating_button = Button::with_label("Clear");
+    let year_range_box = Box::new(Orientation::Horizontal, 6);
+    year_range_box.append(&Label::new(Some("From")));
+    year_range_box.append(&year_from_spin);
+    year_range_box.append(&Label::new(Some("to")));
+    year_range_box.append(&year_to_spin);
+    let min_rating_label = Label::new(Some("Minimum rating:"));
+    min_rating_label.set_xalign(0.0);
+    let year_rating_popover_box = Box::new(Orientation::Vertical, 6);
+    year_rating_popover_box.set_margin_start(6);
+    year_rating_popover_box.set_margin_end(6);
+    year_rating_popover_box.set_margin_top(6);
+    year_rating_popover_box.set_margin_bottom(6);
+    year_rating_popover_box.append(&year_range_box);
+    year_rating_popover_box.append(&min_rating_label);
+    year_rating_popover_box.append(&min_rating_scale);
+    year_rating_popover_box.append(&Separator::new(Orientation::Horizontal));
+    year_rating_popover_box.append(&clear_year_rating_button);
+    let year_rating_popover = gtk::Popover::new();
+    year_rating_popover.set_child(Some(&year_rating_popover_box));
+    let year_rating_button = gtk::MenuButton::new();
+    year_rating_button.set_label(&year_rating_filter_label(None, 0.0));
+    year_rating_button.set_popover(Some(&year_rating_popover));
+    year_rating_button.set_tooltip_text(Some("Only show movies from these years with at least this TMDB rating"));
+
+    let watched_labels: Vec<&str> = WATCHED_FILTERS.iter().map(|(label, _)| *label).collect();
+    let watched_dropdown = DropDown::new(Some(StringList::new(&watched_labels)), None::<gtk::Expression>);
+    watched_dropdown.set_selected(0);
+    watched_dropdown.set_tooltip_text(Some("Filter by watched status"));
+
+    let favorites_check = gtk::CheckButton::with_label("♥ Favorites");
+    favorites_check.set_tooltip_text(Some("Only show favorite movies"));
+
+    let missing_files_check = gtk::CheckButton::with_label("⚠️ Missing files");
+    missing_files_check.set_tooltip_text(Some("Only show movies whose video file is missing or not associated"));
+
+    let unknown_runtime_check = gtk::CheckButton::with_label("Include unknown");
+    unknown_runtime_check.set_tooltip_text(Some("Keep movies without a known runtime when a length limit is set"));
+
     search_box.append(&search_entry);
+    search_box.append(&search_scope_dropdown);
     search_box.append(&Label::new(Some("Genre:")));
-    search_box.append(&genre_dropdown);
+    search_box.append(&genre_button);
+    search_box.append(&Label::new(Some("Tags:")));
+    search_box.append(&tag_button);
+    search_box.append(&Label::new(Some("Collection:")));
+    search_box.append(&collection_button);
+    search_box.append(&Label::new(Some("Rated:")));
+    search_box.append(&certification_button);
+    search_box.append(&Label::new(Some("Years:")));
+    search_box.append(&year_rating_button);
+    search_box.append(&Label::new(Some("Watched:")));
+    search_box.append(&watched_dropdown);
+    search_box.append(&Label::new(Some("Length:")));
+    search_box.append(&runtime_dropdown);
+    search_box.append(&unknown_runtime_check);
+    search_box.append(&favorites_check);
+    search_box.append(&missing_files_check);
     search_box.append(&Label::new(Some("Sort:")));
     search_box.append(&sort_dropdown);
     search_box.append(&Label::new(Some("View:")));
@@ -1393,15 +6961,54 @@ fn build_ui(app: &Application) {
     view_toggle.set_tooltip_text(Some("Switch between list and grid view"));
     search_box.append(&view_toggle);
     
+    let surprise_button = Button::with_label("🎲 Surprise Me");
+    surprise_button.set_tooltip_text(Some("Pick a random movie from the ones the filters show"));
+    search_box.append(&surprise_button);
+    
     main_box.append(&search_box);
 
+    // Bulk actions for a multi-row selection (Ctrl/Shift+click); hidden otherwise
+    let bulk_bar = Box::new(Orientation::Horizontal, 8);
+    bulk_bar.set_margin_start(12);
+    bulk_bar.set_margin_end(12);
+    bulk_bar.set_margin_bottom(6);
+    bulk_bar.set_visible(false);
+    let bulk_label = Label::new(None);
+    bulk_label.set_xalign(0.0);
+    bulk_label.set_hexpand(true);
+    let bulk_delete_button = Button::with_label("🗑️ Delete Selected");
+    let bulk_refresh_button = Button::with_label("🔄 Refresh Selected");
+    let bulk_tag_entry = Entry::new();
+    bulk_tag_entry.set_placeholder_text(Some("Tags, comma-separated"));
+    let bulk_tag_add_button = Button::with_label("Add");
+    let bulk_tag_box = Box::new(Orientation::Horizontal, 6);
+    bulk_tag_box.set_margin_start(6);
+    bulk_tag_box.set_margin_end(6);
+    bulk_tag_box.set_margin_top(6);
+    bulk_tag_box.set_margin_bottom(6);
+    bulk_tag_box.append(&bulk_tag_entry);
+    bulk_tag_box.append(&bulk_tag_add_button);
+    let bulk_tag_popover = gtk::Popover::new();
+    bulk_tag_popover.set_child(Some(&bulk_tag_box));
+    let bulk_tag_button = gtk::MenuButton::new();
+    bulk_tag_button.set_label("🏷️ Add Tag...");
+    bulk_tag_button.set_popover(Some(&bulk_tag_popover));
+    let bulk_clear_button = Button::with_label("Clear Selection");
+    bulk_bar.append(&bulk_label);
+    bulk_bar.append(&bulk_tag_button);
+    bulk_bar.append(&bulk_refresh_button);
+    bulk_bar.append(&bulk_delete_button);
+    bulk_bar.append(&bulk_clear_button);
+    main_box.append(&bulk_bar);
+
     let scrolled = ScrolledWindow::new();
     scrolled.set_vexpand(true);
     scrolled.set_hexpand(true);
     
     // List view (default)
     let list_box = ListBox::new();
-    list_box.set_selection_mode(gtk::SelectionMode::Single);
+    list_box.set_selection_mode(gtk::SelectionMode::Multiple);
+    list_box.set_activate_on_single_click(false);  // Double-click (or Enter) plays the movie
     
     // Grid view (alternative)
     let grid_flow = gtk::FlowBox::new();
@@ -1459,20 +7066,50 @@ fn build_ui(app: &Application) {
     let details_label = Label::new(Some("Select a movie to view details"));
     details_label.set_xalign(0.0);
     details_label.set_wrap(true);
+    open_links_externally(&details_label);
     details_box.append(&details_label);
+    
+    // Full crew of the selected movie by department, collapsed by default
+    let crew_label = Label::new(None);
+    crew_label.set_xalign(0.0);
+    crew_label.set_wrap(true);
+    crew_label.set_selectable(true);
+    let crew_expander = gtk::Expander::new(Some("Crew"));
+    crew_expander.set_child(Some(&crew_label));
+    crew_expander.set_visible(false);
+    details_box.append(&crew_expander);
 
     let action_box = Box::new(Orientation::Horizontal, 8);
     let play_button = Button::with_label("▶️ Play in VLC");
     let show_cast_button = Button::with_label("⭐ Show Cast");
     let watch_log_button = Button::with_label("📝 Watch Log");
+    let favorite_button = Button::with_label(favorite_button_label(false));
+    favorite_button.set_tooltip_text(Some("Add the selected movie to your favorites, or remove it"));
+    let watched_button = Button::with_label("✓ Mark Watched");
+    watched_button.set_tooltip_text(Some("Mark the selected movie watched or unwatched"));
+    let trailer_button = Button::with_label("🎬 Trailer");
+    trailer_button.set_tooltip_text(Some("Watch the trailer on YouTube"));
+    trailer_button.set_sensitive(false);
     let associate_file_button = Button::with_label("📎 Associate File");
+    let open_folder_button = Button::with_label("📂 Open Folder");
+    open_folder_button.set_tooltip_text(Some("Show the video file in your file manager"));
+    open_folder_button.set_sensitive(false);
     let delete_button = Button::with_label("🗑️ Delete");
     action_box.append(&play_button);
     action_box.append(&show_cast_button);
     action_box.append(&watch_log_button);
+    action_box.append(&favorite_button);
+    action_box.append(&watched_button);
+    action_box.append(&trailer_button);
     action_box.append(&associate_file_button);
+    action_box.append(&open_folder_button);
     action_box.append(&delete_button);
     details_box.append(&action_box);
+    
+    // External site links for the selected movie, rebuilt whenever the selection changes
+    let links_row = Box::new(Orientation::Horizontal, 8);
+    links_row.set_visible(false);
+    details_box.append(&links_row);
 
     details_main_box.append(&details_box);
     details_frame.set_child(Some(&details_main_box));
@@ -1500,40 +7137,82 @@ fn build_ui(app: &Application) {
 
     window.set_child(Some(&main_box));
 
-    // View toggle state and handler
-    let is_grid_view = Rc::new(RefCell::new(false));
-    let is_grid_view_clone = is_grid_view.clone();
-    let scrolled_clone = scrolled.clone();
-    let list_box_clone_toggle = list_box.clone();
-    let grid_flow_clone = grid_flow.clone();
-    let db_clone_toggle = db.clone();
-    let poster_cache_clone_toggle = poster_cache.clone();
-    
-    view_toggle.connect_clicked(move |button| {
-        let mut is_grid = is_grid_view_clone.borrow_mut();
-        *is_grid = !*is_grid;
-        
-        if *is_grid {
-            // Switch to grid view
-            button.set_label("🎞️ Grid");
-            scrolled_clone.set_child(Some(&grid_flow_clone));
-            
-            // Populate grid with current movies
-            while let Some(child) = grid_flow_clone.first_child() {
-                grid_flow_clone.remove(&child);
-            }
-            let movies = db_clone_toggle.borrow().list_all();
-            for movie in &movies {
-                let item = create_movie_grid_item(&movie, &poster_cache_clone_toggle);
-                grid_flow_clone.append(&item);
-            }
-        } else {
-            // Switch to list view
-            button.set_label("📋 List");
-            scrolled_clone.set_child(Some(&list_box_clone_toggle));
+    // Remember the window size for the next launch. The default size tracks the
+    // unmaximized size, so a maximized window keeps the size it restores to.
+    window.connect_close_request(|window| {
+        let (width, height) = window.default_size();
+        let mut config = load_config().unwrap_or_default();
+        config.window_width = width;
+        config.window_height = height;
+        config.window_maximized = window.is_maximized();
+        if let Err(e) = save_config(&config) {
+            log_warn!("Warning: Failed to save window size: {}", e);
+        }
+        glib::Propagation::Proceed
+    });
+
+    // Closing mid-scan - offer to keep scanning, or stop and quit once fetched movies are saved
+    let db_clone = db.clone();
+    let status_bar_clone = status_bar.clone();
+    window.connect_close_request(move |window| {
+        if !ActiveScan::any_running() {
+            return glib::Propagation::Proceed;
+        }
+        if SCAN_CANCELLED.load(Ordering::Relaxed) {
+            // Already stopping
+            return glib::Propagation::Stop;
         }
+
+        let confirmed = confirm(
+            window,
+            "A scan is still running",
+            "Quitting now stops the scan after the movies currently being fetched. Everything fetched so far will be saved.",
+            "Stop Scan and Quit",
+        );
+        let window = window.clone();
+        let db_clone2 = db_clone.clone();
+        let status_bar_clone2 = status_bar_clone.clone();
+        glib::spawn_future_local(async move {
+            if !confirmed.await {
+                return;
+            }
+            SCAN_CANCELLED.store(true, Ordering::Relaxed);
+            status_bar_clone2.set_text("Stopping scan and saving fetched movies...");
+
+            glib::timeout_add_local(std::time::Duration::from_millis(100), move || {
+                if ActiveScan::any_running() {
+                    return glib::ControlFlow::Continue;
+                }
+                if let Err(e) = db_clone2.borrow().save_to_file() {
+                    log_warn!("Warning: Failed to save database before quitting: {}", e);
+                }
+                window.destroy();
+                glib::ControlFlow::Break
+            });
+        });
+        glib::Propagation::Stop
     });
 
+    // View toggle state, restored from the last session
+    let is_grid_view = Rc::new(RefCell::new(startup_config.view_mode == "grid"));
+    if *is_grid_view.borrow() {
+        view_toggle.set_label("🎞️ Grid");
+        scrolled.set_child(Some(&grid_flow));
+    }
+
+    // Sort and genre filter, restored from the last session before any change handlers are connected
+    let startup_sort = SORT_OPTIONS.iter().position(|option| *option == startup_config.last_sort).unwrap_or(0);
+    sort_dropdown.set_selected(startup_sort as u32);
+    *selected_genres.borrow_mut() = startup_config.last_genres.clone();
+    match_all_genres_check.set_active(startup_config.last_match_all_genres);
+    genre_button.set_label(&genre_filter_label(&startup_config.last_genres, startup_config.last_match_all_genres));
+    let startup_filters = ListFilters {
+        genres: startup_config.last_genres.clone(),
+        match_all_genres: startup_config.last_match_all_genres,
+        sort_by: SORT_OPTIONS[startup_sort].to_string(),
+        ..Default::default()
+    };
+
     // Show window first for fast startup
     window.present();
     
@@ -1541,11 +7220,13 @@ fn build_ui(app: &Application) {
     let db_clone = db.clone();
     let db_clone2 = db.clone();
     let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let is_grid = *is_grid_view.borrow();
     let poster_cache_clone = poster_cache.clone();
     let status_bar_clone = status_bar.clone();
     let loading_spinner_clone = loading_spinner.clone();
     glib::idle_add_local_once(move || {
-        let movies = db_clone.borrow().list_all();
+        let movies = db_clone.borrow().filtered_movies(&startup_filters);
         let total = movies.len();
         let batch_size = 50; // Add 50 movies at a time
         
@@ -1560,6 +7241,7 @@ fn build_ui(app: &Application) {
         
         let db_clone_batch = db_clone2.clone();
         let list_box_batch = list_box_clone.clone();
+        let grid_flow_batch = grid_flow_clone.clone();
         let poster_cache_batch = poster_cache_clone.clone();
         let movies_batch = movies.clone();
         let current_index_batch = current_index.clone();
@@ -1574,8 +7256,12 @@ fn build_ui(app: &Application) {
             // Add this batch of movies
             for i in start..end {
                 let movie = &movies_batch[i];
-                let row = create_movie_row_with_context(movie, &poster_cache_batch, &db_clone_batch);
-                list_box_batch.append(&row);
+                if is_grid {
+                    grid_flow_batch.append(&create_movie_grid_item(movie, &poster_cache_batch));
+                } else {
+                    let row = create_movie_row_with_context(movie, &poster_cache_batch, &db_clone_batch);
+                    list_box_batch.append(&row);
+                }
             }
             
             *idx = end;
@@ -1590,7 +7276,17 @@ fn build_ui(app: &Application) {
                 // Done loading - hide spinner
                 loading_spinner_batch.stop();
                 loading_spinner_batch.set_visible(false);
-                status_bar_batch.set_text(&format!("Ready - {} movies loaded", total));
+                let rejected = db_clone_batch.borrow().rejected_records;
+                if let Some(e) = db_clone_batch.borrow().load_error.clone() {
+                    status_bar_batch.set_text(&format!("⚠️ Couldn't load {}: {} - changes won't be saved", db_clone_batch.borrow().data_file, e));
+                } else if rejected > 0 {
+                    status_bar_batch.set_text(&format!(
+                        "Ready - {} movies loaded. {} unreadable record{} skipped and saved to {}.rejected",
+                        total, rejected, if rejected == 1 { "" } else { "s" }, db_clone_batch.borrow().data_file
+                    ));
+                } else {
+                    status_bar_batch.set_text(&format!("Ready - {} movies loaded", total));
+                }
                 glib::ControlFlow::Break
             }
         });
@@ -1604,6 +7300,8 @@ fn build_ui(app: &Application) {
         let status_bar_clone = status_bar.clone();
         let window_clone = window.clone();
         let poster_cache_clone = poster_cache.clone();
+        let busy_clone = busy.clone();
+        let set_busy_clone = set_busy.clone();
         
         // Ask user if they want to scan
         let dialog = gtk::AlertDialog::builder()
@@ -1618,145 +7316,49 @@ fn build_ui(app: &Application) {
             .default_button(1)
             .build();
         
-        let scan_dirs = config.scan_directories.clone();
-        let api_key = db_clone.borrow().tmdb_api_key.clone();
-        let posters_dir = db_clone.borrow().posters_dir.clone();
-        let year_cutoff = config.year_cutoff;
+        let config = config.clone();
         
         dialog.choose(Some(&window_clone), None::<&gtk::gio::Cancellable>, move |response| {
             if let Ok(1) = response {
                 // User chose "Scan Now"
+                if busy_clone.get() {
+                    status_bar_clone.set_text(BUSY_MESSAGE);
+                    return;
+                }
+                set_busy_clone(true);
                 status_bar_clone.set_text("Auto-scanning configured directories...");
                 
                 // Spawn auto-scan in background
-                let (sender, receiver) = async_channel::unbounded::<(String, String, Option<Movie>)>();
-                
-                let api_key_clone = api_key.clone();
-                let scan_dirs_clone = scan_dirs.clone();
-                let year_cutoff_clone = year_cutoff;
-                
-                // Extract existing file paths before spawning thread (Rc can't be sent between threads)
-                let existing_paths: std::collections::HashSet<String> = db_clone.borrow()
-                    .movies
-                    .values()
-                    .map(|m| m.file_path.clone())
-                    .collect();
-                
-                std::thread::spawn(move || {
-                    // Use tokio runtime for async operations
-                    let runtime = tokio::runtime::Builder::new_current_thread()
-                        .enable_all()
-                        .build()
-                        .unwrap();
-                    
-                    runtime.block_on(async {
-                        // Collect all video files first (recursively)
-                        let mut files_to_process = Vec::new();
-                        let video_extensions = vec!["mp4", "mkv", "avi", "mov", "wmv", "flv", "webm", "m4v"];
-                        
-                        for scan_dir in &scan_dirs_clone {
-                            let _ = sender.send_blocking(("status".to_string(), format!("Scanning: {} (including subdirectories)...", scan_dir), None));
-                            
-                            let path = Path::new(scan_dir);
-                            scan_directory_recursive(path, &video_extensions, &mut files_to_process);
-                        }
-                        
-                        // Filter out files that already exist in database (using pre-extracted paths)
-                        
-                        let new_files: Vec<_> = files_to_process.into_iter()
-                            .filter(|(_, file_path)| !existing_paths.contains(file_path))
-                            .collect();
-                        
-                        if new_files.is_empty() {
-                            let _ = sender.send_blocking(("status".to_string(), "No new movies found - all files already in database".to_string(), None));
-                            let _ = sender.send_blocking(("complete".to_string(), String::new(), None));
-                            return;
-                        }
-                        
-                        let _ = sender.send_blocking(("status".to_string(), format!("Found {} new video files (skipped {} existing), fetching metadata in parallel...", new_files.len(), existing_paths.len()), None));
-                        
-                        // Process files in parallel batches of 10
-                        let client = reqwest::Client::new();
-                        let batch_size = 10;
-                        
-                        for batch in new_files.chunks(batch_size) {
-                            let futures: Vec<_> = batch.iter()
-                                .map(|(clean_title, file_path_str)| {
-                                    let api_key = api_key_clone.clone();
-                                    let title = clean_title.clone();
-                                    let file_path = file_path_str.clone();
-                                    let client = client.clone();
-                                    let sender = sender.clone();
-                                    let posters_dir = posters_dir.clone();
-                                    
-                                    async move {
-                                        let _ = sender.send_blocking(("status".to_string(), format!("Fetching: {}", title), None));
-                                        
-                                        match fetch_movie_metadata_async(&client, &api_key, &title, file_path.clone(), posters_dir.clone(), year_cutoff_clone).await {
-                                            Some(movie) => {
-                                                let _ = sender.send_blocking(("add".to_string(), format!("✓ Found: {}", title), Some(movie)));
-                                            }
-                                            None => {
-                                                // Create basic entry without metadata
-                                                let movie = Movie {
-                                                    id: 0,
-                                                    title: title.clone(),
-                                                    year: 0,
-                                                    director: String::from("Unknown"),
-                                                    genre: vec![String::from("Uncategorized")],
-                                                    rating: 0.0,
-                                                    runtime: 0,
-                                                    description: String::from("Metadata not found"),
-                                                    cast: vec![],
-                                                    cast_details: vec![],
-                                                    file_path,
-                                                    poster_url: String::new(),
-                                                    tmdb_id: 0,
-                                                    imdb_id: String::new(),
-                                                    poster_path: String::new(),
-                                                    watch_log: Vec::new(),
-                                                };
-                                                let _ = sender.send_blocking(("add".to_string(), format!("⚠ Added without metadata: {}", title), Some(movie)));
-                                            }
-                                        }
-                                    }
-                                })
-                                .collect();
-                            
-                            // Wait for this batch to complete
-                            futures::future::join_all(futures).await;
-                        }
-                        
-                        let _ = sender.send_blocking(("complete".to_string(), String::new(), None));
-                    });
-                });
+                let scan = spawn_scan_worker(ScanJob::from_config(&config, &db_clone.borrow(), config.scan_directories.clone(), Vec::new()));
         
         // Handle messages on main thread
         glib::spawn_future_local(async move {
             let mut new_movies_count = 0;
-            while let Ok((msg_type, status, movie_opt)) = receiver.recv().await {
+            while let Ok((msg_type, status, movie_opt)) = scan.receiver.recv().await {
                 match msg_type.as_str() {
                     "status" => {
                         status_bar_clone.set_text(&status);
                     }
+                    "progress" => ActiveScan::show_progress(&status),
                     "add" => {
                         if let Some(movie) = movie_opt {
-                            // Check if movie already exists
-                            let exists = db_clone.borrow().movies.values()
-                                .any(|m| m.file_path == movie.file_path);
-                            
-                            if !exists {
-                                db_clone.borrow_mut().add_movie(movie.clone());
+                            let added = db_clone.borrow_mut().add_movie(movie.clone());
+                            if let Some(id) = added {
                                 new_movies_count += 1;
                                 
                                 // Add to UI
-                                let row = create_movie_row(&movie, &poster_cache_clone);
+                                let row = create_movie_row(&Movie { id, ..movie }, &poster_cache_clone);
                                 list_box_clone.append(&row);
                             }
                         }
                         status_bar_clone.set_text(&status);
                     }
                     "complete" => {
+                        if scan.active.was_cancelled() {
+                            status_bar_clone.set_text(&format!("Auto-scan cancelled - added {} new movies", new_movies_count));
+                            break;
+                        }
+                        record_scan_completed(&config.scan_directories);
                         if new_movies_count > 0 {
                             status_bar_clone.set_text(&format!("Auto-scan complete! Added {} new movies", new_movies_count));
                         } else {
@@ -1767,6 +7369,7 @@ fn build_ui(app: &Application) {
                     _ => {}
                 }
             }
+            set_busy_clone(false);
         });
         } else {
             // User chose "Skip"
@@ -1781,9 +7384,7 @@ fn build_ui(app: &Application) {
         grid_flow: &gtk::FlowBox,
         is_grid_view: bool,
         db: &Rc<RefCell<MovieDatabase>>,
-        search_query: &str,
-        genre_filter: &str,
-        sort_by: &str,
+        filters: &ListFilters,
         poster_cache: &Rc<RefCell<HashMap<u32, Pixbuf>>>,
     ) {
         // Clear existing items from both views
@@ -1794,50 +7395,7 @@ fn build_ui(app: &Application) {
             grid_flow.remove(&child);
         }
 
-        // Create cache key from current filters
-        let cache_key = format!("{}|{}|{}", search_query, genre_filter, sort_by);
-        
-        // Check cache first
-        let results = if let Some(cached) = db.borrow().get_cached_results(&cache_key) {
-            cached
-        } else {
-            // Cache miss - compute results
-            let mut results = if search_query.is_empty() {
-                db.borrow().search_by_genre(genre_filter)
-            } else {
-                db.borrow().search_by_title(search_query)
-            };
-            
-            // Apply sorting
-            match sort_by {
-                "Title (A-Z)" => {
-                    results.sort_by(|a, b| a.title.cmp(&b.title));
-                }
-                "Year (Newest)" => {
-                    results.sort_by(|a, b| b.year.cmp(&a.year));
-                }
-                "Year (Oldest)" => {
-                    results.sort_by(|a, b| a.year.cmp(&b.year));
-                }
-                "Rating (High-Low)" => {
-                    results.sort_by(|a, b| b.rating.partial_cmp(&a.rating).unwrap_or(std::cmp::Ordering::Equal));
-                }
-                "Rating (Low-High)" => {
-                    results.sort_by(|a, b| a.rating.partial_cmp(&b.rating).unwrap_or(std::cmp::Ordering::Equal));
-                }
-                "Date Added (Newest)" => {
-                    results.sort_by(|a, b| b.id.cmp(&a.id));
-                }
-                "Date Added (Oldest)" => {
-                    results.sort_by(|a, b| a.id.cmp(&b.id));
-                }
-                _ => {}
-            }
-            
-            // Cache the results
-            db.borrow().cache_results(cache_key, results.clone());
-            results
-        };
+        let results = db.borrow().filtered_movies(filters);
 
         // Populate the active view with batching for responsiveness
         let batch_size = 50;
@@ -1856,106 +7414,601 @@ fn build_ui(app: &Application) {
                     }
                 }
             }
-        } else {
-            for (idx, chunk) in results.chunks(batch_size).enumerate() {
-                for movie in chunk {
-                    let row = create_movie_row_with_context(movie, poster_cache, db);
-                    list_box.append(&row);
-                }
-                
-                // Force UI update between batches for large collections
-                if idx < results.len() / batch_size {
-                    while gtk::glib::MainContext::default().pending() {
-                        gtk::glib::MainContext::default().iteration(false);
+        } else {
+            for (idx, chunk) in results.chunks(batch_size).enumerate() {
+                for movie in chunk {
+                    let row = create_movie_row_with_context(movie, poster_cache, db);
+                    list_box.append(&row);
+                }
+                
+                // Force UI update between batches for large collections
+                if idx < results.len() / batch_size {
+                    while gtk::glib::MainContext::default().pending() {
+                        gtk::glib::MainContext::default().iteration(false);
+                    }
+                }
+            }
+        }
+    }
+
+    // Read the current state of all filter controls
+    let search_entry_filters = search_entry.clone();
+    let search_scope_dropdown_filters = search_scope_dropdown.clone();
+    let selected_genres_filters = selected_genres.clone();
+    let match_all_genres_check_filters = match_all_genres_check.clone();
+    let selected_tags_filters = selected_tags.clone();
+    let sort_dropdown_filters = sort_dropdown.clone();
+    let runtime_dropdown_filters = runtime_dropdown.clone();
+    let unknown_runtime_check_filters = unknown_runtime_check.clone();
+    let year_from_spin_filters = year_from_spin.clone();
+    let year_to_spin_filters = year_to_spin.clone();
+    let min_rating_scale_filters = min_rating_scale.clone();
+    let favorites_check_filters = favorites_check.clone();
+    let watched_dropdown_filters = watched_dropdown.clone();
+    let missing_files_check_filters = missing_files_check.clone();
+    let selected_collection_filters = selected_collection.clone();
+    let selected_certifications_filters = selected_certifications.clone();
+    let current_filters: Rc<dyn Fn() -> ListFilters> = Rc::new(move || {
+        ListFilters {
+            search_query: search_entry_filters.text().to_string(),
+            search_everything: SEARCH_SCOPES.get(search_scope_dropdown_filters.selected() as usize).is_some_and(|(_, everything)| *everything),
+            genres: selected_genres_filters.borrow().clone(),
+            match_all_genres: match_all_genres_check_filters.is_active(),
+            tags: selected_tags_filters.borrow().clone(),
+            collection_id: selected_collection_filters.borrow().as_ref().map(|(id, _)| *id),
+            certifications: selected_certifications_filters.borrow().clone(),
+            sort_by: SORT_OPTIONS.get(sort_dropdown_filters.selected() as usize).unwrap_or(&"Title (A-Z)").to_string(),
+            max_runtime: RUNTIME_LIMITS.get(runtime_dropdown_filters.selected() as usize).and_then(|(_, max)| *max),
+            include_unknown_runtime: unknown_runtime_check_filters.is_active(),
+            year_range: year_range_filter(year_from_spin_filters.value_as_int() as u16, year_to_spin_filters.value_as_int() as u16),
+            min_rating: min_rating_scale_filters.value() as f32,
+            favorites_only: favorites_check_filters.is_active(),
+            watched: WATCHED_FILTERS.get(watched_dropdown_filters.selected() as usize).and_then(|(_, watched)| *watched),
+            missing_files_only: missing_files_check_filters.is_active(),
+        }
+    });
+
+    // Search as you type, once typing pauses for SEARCH_DEBOUNCE_MS so large libraries don't
+    // rebuild the list on every keystroke. Enter searches straight away.
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let db_clone = db.clone();
+    let current_filters_clone = current_filters.clone();
+    let poster_cache_clone = poster_cache.clone();
+    let is_grid_view_clone = is_grid_view.clone();
+    // Rebuild the list or grid with the current filters and sort
+    let refresh_view: Rc<dyn Fn()> = Rc::new(move || {
+        let is_grid = *is_grid_view_clone.borrow();
+        refresh_movie_list(&list_box_clone, &grid_flow_clone, is_grid, &db_clone, &current_filters_clone(), &poster_cache_clone);
+    });
+    let run_search = refresh_view.clone();
+    // Lets dialogs opened from a list row, which can't reach the filters, redraw the view
+    let refresh_view_action = gtk::gio::SimpleAction::new("refresh-view", None);
+    let refresh_view_clone = refresh_view.clone();
+    refresh_view_action.connect_activate(move |_, _| refresh_view_clone());
+    window.add_action(&refresh_view_action);
+    let pending_search: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
+
+    let run_search_clone = run_search.clone();
+    let pending_search_clone = pending_search.clone();
+    search_entry.connect_search_changed(move |_| {
+        if let Some(source) = pending_search_clone.borrow_mut().take() {
+            source.remove();
+        }
+        let run_search = run_search_clone.clone();
+        let pending_search = pending_search_clone.clone();
+        let source = glib::timeout_add_local_once(std::time::Duration::from_millis(SEARCH_DEBOUNCE_MS), move || {
+            // Already fired, so forget the id rather than removing it
+            pending_search.borrow_mut().take();
+            run_search();
+        });
+        *pending_search_clone.borrow_mut() = Some(source);
+    });
+
+    let run_search_clone = run_search.clone();
+    search_entry.connect_activate(move |_| {
+        if let Some(source) = pending_search.borrow_mut().take() {
+            source.remove();
+        }
+        run_search_clone();
+    });
+
+    // Search scope
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let db_clone = db.clone();
+    let current_filters_clone = current_filters.clone();
+    let poster_cache_clone = poster_cache.clone();
+    let is_grid_view_clone = is_grid_view.clone();
+    search_scope_dropdown.connect_selected_notify(move |_| {
+        let is_grid = *is_grid_view_clone.borrow();
+        refresh_movie_list(&list_box_clone, &grid_flow_clone, is_grid, &db_clone, &current_filters_clone(), &poster_cache_clone);
+    });
+
+    // Genre filter - relabel the button and refresh whenever the selection or match mode changes
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let db_clone = db.clone();
+    let current_filters_clone = current_filters.clone();
+    let poster_cache_clone = poster_cache.clone();
+    let is_grid_view_clone = is_grid_view.clone();
+    let genre_button_clone = genre_button.clone();
+    let apply_genre_filter: Rc<dyn Fn()> = Rc::new(move || {
+        let filters = current_filters_clone();
+        genre_button_clone.set_label(&genre_filter_label(&filters.genres, filters.match_all_genres));
+        remember_list_filters(&filters);
+        let is_grid = *is_grid_view_clone.borrow();
+        refresh_movie_list(&list_box_clone, &grid_flow_clone, is_grid, &db_clone, &filters, &poster_cache_clone);
+    });
+
+    // The checkboxes are rebuilt each time the popover opens so they follow the library.
+    // Selected genres no longer in the library stay listed so they can be unchecked.
+    let db_clone = db.clone();
+    let selected_genres_clone = selected_genres.clone();
+    let genre_checks_box_clone = genre_checks_box.clone();
+    let apply_genre_filter_clone = apply_genre_filter.clone();
+    genre_popover.connect_show(move |_| {
+        while let Some(child) = genre_checks_box_clone.first_child() {
+            genre_checks_box_clone.remove(&child);
+        }
+        let mut genres = db_clone.borrow().all_genres();
+        for genre in selected_genres_clone.borrow().iter() {
+            if !genres.iter().any(|g| g.eq_ignore_ascii_case(genre)) {
+                genres.push(genre.clone());
+            }
+        }
+        for genre in genres {
+            let check = gtk::CheckButton::with_label(&genre);
+            check.set_active(selected_genres_clone.borrow().iter().any(|g| g.eq_ignore_ascii_case(&genre)));
+            let selected_genres = selected_genres_clone.clone();
+            let apply_genre_filter = apply_genre_filter_clone.clone();
+            check.connect_toggled(move |check| {
+                {
+                    let mut selected = selected_genres.borrow_mut();
+                    let was_selected = selected.iter().any(|g| g.eq_ignore_ascii_case(&genre));
+                    if was_selected == check.is_active() {
+                        return;
+                    }
+                    selected.retain(|g| !g.eq_ignore_ascii_case(&genre));
+                    if check.is_active() {
+                        selected.push(genre.clone());
+                    }
+                }
+                apply_genre_filter();
+            });
+            genre_checks_box_clone.append(&check);
+        }
+        if genre_checks_box_clone.first_child().is_none() {
+            genre_checks_box_clone.append(&Label::new(Some("No genres in the library yet")));
+        }
+    });
+
+    let apply_genre_filter_clone = apply_genre_filter.clone();
+    match_all_genres_check.connect_toggled(move |_| apply_genre_filter_clone());
+
+    let selected_genres_clone = selected_genres.clone();
+    let genre_checks_box_clone = genre_checks_box.clone();
+    clear_genres_button.connect_clicked(move |_| {
+        selected_genres_clone.borrow_mut().clear();
+        // The selection is already empty, so unchecking the boxes doesn't refresh once per box
+        let mut child = genre_checks_box_clone.first_child();
+        while let Some(widget) = child {
+            if let Some(check) = widget.downcast_ref::<gtk::CheckButton>() {
+                check.set_active(false);
+            }
+            child = widget.next_sibling();
+        }
+        apply_genre_filter();
+    });
+
+    // Tag filter
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let db_clone = db.clone();
+    let current_filters_clone = current_filters.clone();
+    let poster_cache_clone = poster_cache.clone();
+    let is_grid_view_clone = is_grid_view.clone();
+    let tag_button_clone = tag_button.clone();
+    let apply_tag_filter: Rc<dyn Fn()> = Rc::new(move || {
+        let filters = current_filters_clone();
+        tag_button_clone.set_label(&tag_filter_label(&filters.tags));
+        let is_grid = *is_grid_view_clone.borrow();
+        refresh_movie_list(&list_box_clone, &grid_flow_clone, is_grid, &db_clone, &filters, &poster_cache_clone);
+    });
+
+    let db_clone = db.clone();
+    let selected_tags_clone = selected_tags.clone();
+    let tag_checks_box_clone = tag_checks_box.clone();
+    let apply_tag_filter_clone = apply_tag_filter.clone();
+    tag_popover.connect_show(move |_| {
+        while let Some(child) = tag_checks_box_clone.first_child() {
+            tag_checks_box_clone.remove(&child);
+        }
+        let mut tags = db_clone.borrow().all_tags();
+        for tag in selected_tags_clone.borrow().iter() {
+            if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                tags.push(tag.clone());
+            }
+        }
+        for tag in tags {
+            let check = gtk::CheckButton::with_label(&tag);
+            check.set_active(selected_tags_clone.borrow().iter().any(|t| t.eq_ignore_ascii_case(&tag)));
+            let selected_tags = selected_tags_clone.clone();
+            let apply_tag_filter = apply_tag_filter_clone.clone();
+            check.connect_toggled(move |check| {
+                {
+                    let mut selected = selected_tags.borrow_mut();
+                    let was_selected = selected.iter().any(|t| t.eq_ignore_ascii_case(&tag));
+                    if was_selected == check.is_active() {
+                        return;
+                    }
+                    selected.retain(|t| !t.eq_ignore_ascii_case(&tag));
+                    if check.is_active() {
+                        selected.push(tag.clone());
                     }
                 }
+                apply_tag_filter();
+            });
+            tag_checks_box_clone.append(&check);
+        }
+        if tag_checks_box_clone.first_child().is_none() {
+            tag_checks_box_clone.append(&Label::new(Some("No tags yet - add them in Edit Metadata")));
+        }
+    });
+
+    let selected_tags_clone = selected_tags.clone();
+    let tag_checks_box_clone = tag_checks_box.clone();
+    clear_tags_button.connect_clicked(move |_| {
+        selected_tags_clone.borrow_mut().clear();
+        let mut child = tag_checks_box_clone.first_child();
+        while let Some(widget) = child {
+            if let Some(check) = widget.downcast_ref::<gtk::CheckButton>() {
+                check.set_active(false);
             }
+            child = widget.next_sibling();
         }
-    }
+        apply_tag_filter();
+    });
 
-    // Search functionality - only trigger on Enter key
+    // Collection filter
     let list_box_clone = list_box.clone();
     let grid_flow_clone = grid_flow.clone();
     let db_clone = db.clone();
-    let genre_dropdown_clone = genre_dropdown.clone();
-    let sort_dropdown_clone = sort_dropdown.clone();
+    let current_filters_clone = current_filters.clone();
     let poster_cache_clone = poster_cache.clone();
     let is_grid_view_clone = is_grid_view.clone();
-    search_entry.connect_activate(move |entry| {
-        let query = entry.text();
-        let selected_idx = genre_dropdown_clone.selected();
-        let genres = ["All", "Action", "Comedy", "Drama", "Film Noir", "Horror", "Sci-Fi", "Thriller", "Romance"];
-        let selected_genre = genres.get(selected_idx as usize).unwrap_or(&"All");
-        
-        let sort_idx = sort_dropdown_clone.selected();
-        let sorts = ["Title (A-Z)", "Year (Newest)", "Year (Oldest)", "Rating (High-Low)", "Rating (Low-High)", "Date Added (Newest)", "Date Added (Oldest)"];
-        let sort_by = sorts.get(sort_idx as usize).unwrap_or(&"Title (A-Z)");
-        
+    let collection_button_clone = collection_button.clone();
+    let selected_collection_clone = selected_collection.clone();
+    let collection_popover_clone = collection_popover.clone();
+    let apply_collection_filter: Rc<dyn Fn()> = Rc::new(move || {
+        collection_popover_clone.popdown();
+        collection_button_clone.set_label(&collection_filter_label(selected_collection_clone.borrow().as_ref()));
         let is_grid = *is_grid_view_clone.borrow();
-        refresh_movie_list(&list_box_clone, &grid_flow_clone, is_grid, &db_clone, &query.to_string(), selected_genre, sort_by, &poster_cache_clone);
+        refresh_movie_list(&list_box_clone, &grid_flow_clone, is_grid, &db_clone, &current_filters_clone(), &poster_cache_clone);
+    });
+
+    let db_clone = db.clone();
+    let collection_list_box_clone = collection_list_box.clone();
+    let selected_collection_clone = selected_collection.clone();
+    let apply_collection_filter_clone = apply_collection_filter.clone();
+    collection_popover.connect_show(move |_| {
+        while let Some(child) = collection_list_box_clone.first_child() {
+            collection_list_box_clone.remove(&child);
+        }
+        for (id, name, count) in db_clone.borrow().collections() {
+            let button = Button::with_label(&format!("{} ({})", name, count));
+            button.add_css_class("flat");
+            let selected_collection = selected_collection_clone.clone();
+            let apply_collection_filter = apply_collection_filter_clone.clone();
+            button.connect_clicked(move |_| {
+                *selected_collection.borrow_mut() = Some((id, name.clone()));
+                apply_collection_filter();
+            });
+            collection_list_box_clone.append(&button);
+        }
+        if collection_list_box_clone.first_child().is_none() {
+            collection_list_box_clone.append(&Label::new(Some("No collections yet - refresh metadata to look them up")));
+        }
     });
 
-    // Genre filter
+    let selected_collection_clone = selected_collection.clone();
+    clear_collection_button.connect_clicked(move |_| {
+        *selected_collection_clone.borrow_mut() = None;
+        apply_collection_filter();
+    });
+
+
+    // Age rating filter
     let list_box_clone = list_box.clone();
     let grid_flow_clone = grid_flow.clone();
     let db_clone = db.clone();
-    let search_entry_clone = search_entry.clone();
-    let sort_dropdown_clone = sort_dropdown.clone();
+    let current_filters_clone = current_filters.clone();
     let poster_cache_clone = poster_cache.clone();
     let is_grid_view_clone = is_grid_view.clone();
-    genre_dropdown.connect_selected_notify(move |dropdown| {
-        let selected_idx = dropdown.selected();
-        let genres = ["All", "Action", "Comedy", "Drama", "Film Noir", "Horror", "Sci-Fi", "Thriller", "Romance"];
-        let selected_genre = genres.get(selected_idx as usize).unwrap_or(&"All");
-        
-        let query = search_entry_clone.text().to_string();
-        let sort_idx = sort_dropdown_clone.selected();
-        let sorts = ["Title (A-Z)", "Year (Newest)", "Year (Oldest)", "Rating (High-Low)", "Rating (Low-High)", "Date Added (Newest)", "Date Added (Oldest)"];
-        let sort_by = sorts.get(sort_idx as usize).unwrap_or(&"Title (A-Z)");
-        
+    let certification_button_clone = certification_button.clone();
+    let apply_certification_filter: Rc<dyn Fn()> = Rc::new(move || {
+        let filters = current_filters_clone();
+        certification_button_clone.set_label(&certification_filter_label(&filters.certifications));
         let is_grid = *is_grid_view_clone.borrow();
-        refresh_movie_list(&list_box_clone, &grid_flow_clone, is_grid, &db_clone, &query, selected_genre, sort_by, &poster_cache_clone);
+        refresh_movie_list(&list_box_clone, &grid_flow_clone, is_grid, &db_clone, &filters, &poster_cache_clone);
+    });
+
+    let db_clone = db.clone();
+    let selected_certifications_clone = selected_certifications.clone();
+    let certification_checks_box_clone = certification_checks_box.clone();
+    let apply_certification_filter_clone = apply_certification_filter.clone();
+    certification_popover.connect_show(move |_| {
+        while let Some(child) = certification_checks_box_clone.first_child() {
+            certification_checks_box_clone.remove(&child);
+        }
+        let mut certifications = db_clone.borrow().all_certifications();
+        for certification in selected_certifications_clone.borrow().iter() {
+            if !certifications.contains(certification) {
+                certifications.push(certification.clone());
+            }
+        }
+        for certification in certifications {
+            let check = gtk::CheckButton::with_label(&certification);
+            check.set_active(selected_certifications_clone.borrow().contains(&certification));
+            let selected_certifications = selected_certifications_clone.clone();
+            let apply_certification_filter = apply_certification_filter_clone.clone();
+            check.connect_toggled(move |check| {
+                {
+                    let mut selected = selected_certifications.borrow_mut();
+                    if selected.contains(&certification) == check.is_active() {
+                        return;
+                    }
+                    selected.retain(|c| *c != certification);
+                    if check.is_active() {
+                        selected.push(certification.clone());
+                    }
+                }
+                apply_certification_filter();
+            });
+            certification_checks_box_clone.append(&check);
+        }
+        if certification_checks_box_clone.first_child().is_none() {
+            certification_checks_box_clone.append(&Label::new(Some("No age ratings yet - refresh metadata to look them up")));
+        }
+    });
+
+    let selected_certifications_clone = selected_certifications.clone();
+    let certification_checks_box_clone = certification_checks_box.clone();
+    clear_certifications_button.connect_clicked(move |_| {
+        selected_certifications_clone.borrow_mut().clear();
+        let mut child = certification_checks_box_clone.first_child();
+        while let Some(widget) = child {
+            if let Some(check) = widget.downcast_ref::<gtk::CheckButton>() {
+                check.set_active(false);
+            }
+            child = widget.next_sibling();
+        }
+        apply_certification_filter();
     });
     
     // Sort dropdown
     let list_box_clone = list_box.clone();
     let grid_flow_clone = grid_flow.clone();
     let db_clone = db.clone();
-    let search_entry_clone = search_entry.clone();
-    let genre_dropdown_clone = genre_dropdown.clone();
+    let current_filters_clone = current_filters.clone();
+    let poster_cache_clone = poster_cache.clone();
+    let is_grid_view_clone = is_grid_view.clone();
+    sort_dropdown.connect_selected_notify(move |_| {
+        let filters = current_filters_clone();
+        remember_list_filters(&filters);
+        let is_grid = *is_grid_view_clone.borrow();
+        refresh_movie_list(&list_box_clone, &grid_flow_clone, is_grid, &db_clone, &filters, &poster_cache_clone);
+    });
+
+    // Runtime filter ("fits my evening")
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let db_clone = db.clone();
+    let current_filters_clone = current_filters.clone();
+    let poster_cache_clone = poster_cache.clone();
+    let is_grid_view_clone = is_grid_view.clone();
+    runtime_dropdown.connect_selected_notify(move |_| {
+        let is_grid = *is_grid_view_clone.borrow();
+        refresh_movie_list(&list_box_clone, &grid_flow_clone, is_grid, &db_clone, &current_filters_clone(), &poster_cache_clone);
+    });
+
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let db_clone = db.clone();
+    let current_filters_clone = current_filters.clone();
+    let poster_cache_clone = poster_cache.clone();
+    let is_grid_view_clone = is_grid_view.clone();
+    unknown_runtime_check.connect_toggled(move |_| {
+        let is_grid = *is_grid_view_clone.borrow();
+        refresh_movie_list(&list_box_clone, &grid_flow_clone, is_grid, &db_clone, &current_filters_clone(), &poster_cache_clone);
+    });
+
+    // Year and rating filter. The spin buttons push each other along so the range never inverts.
+    let year_from_spin_clone = year_from_spin.clone();
+    let year_to_spin_clone = year_to_spin.clone();
+    let min_rating_scale_clone = min_rating_scale.clone();
+    let year_rating_button_clone = year_rating_button.clone();
+    let run_search_clone = run_search.clone();
+    let apply_year_rating_filter: Rc<dyn Fn()> = Rc::new(move || {
+        let year_range = year_range_filter(year_from_spin_clone.value_as_int() as u16, year_to_spin_clone.value_as_int() as u16);
+        year_rating_button_clone.set_label(&year_rating_filter_label(year_range, min_rating_scale_clone.value() as f32));
+        run_search_clone();
+    });
+
+    let year_to_spin_clone = year_to_spin.clone();
+    let apply_year_rating_filter_clone = apply_year_rating_filter.clone();
+    year_from_spin.connect_value_changed(move |spin| {
+        if spin.value() > year_to_spin_clone.value() {
+            year_to_spin_clone.set_value(spin.value());
+        }
+        apply_year_rating_filter_clone();
+    });
+
+    let year_from_spin_clone = year_from_spin.clone();
+    let apply_year_rating_filter_clone = apply_year_rating_filter.clone();
+    year_to_spin.connect_value_changed(move |spin| {
+        if spin.value() < year_from_spin_clone.value() {
+            year_from_spin_clone.set_value(spin.value());
+        }
+        apply_year_rating_filter_clone();
+    });
+
+    let apply_year_rating_filter_clone = apply_year_rating_filter.clone();
+    min_rating_scale.connect_value_changed(move |_| {
+        apply_year_rating_filter_clone();
+    });
+
+    let year_from_spin_clone = year_from_spin.clone();
+    let year_to_spin_clone = year_to_spin.clone();
+    let min_rating_scale_clone = min_rating_scale.clone();
+    clear_year_rating_button.connect_clicked(move |_| {
+        year_from_spin_clone.set_value(MIN_FILTER_YEAR as f64);
+        year_to_spin_clone.set_value(max_filter_year() as f64);
+        min_rating_scale_clone.set_value(0.0);
+    });
+
+    // Watched status filter
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let db_clone = db.clone();
+    let current_filters_clone = current_filters.clone();
+    let poster_cache_clone = poster_cache.clone();
+    let is_grid_view_clone = is_grid_view.clone();
+    watched_dropdown.connect_selected_notify(move |_| {
+        let is_grid = *is_grid_view_clone.borrow();
+        refresh_movie_list(&list_box_clone, &grid_flow_clone, is_grid, &db_clone, &current_filters_clone(), &poster_cache_clone);
+    });
+
+    // Favorites filter
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let db_clone = db.clone();
+    let current_filters_clone = current_filters.clone();
+    let poster_cache_clone = poster_cache.clone();
+    let is_grid_view_clone = is_grid_view.clone();
+    favorites_check.connect_toggled(move |_| {
+        let is_grid = *is_grid_view_clone.borrow();
+        refresh_movie_list(&list_box_clone, &grid_flow_clone, is_grid, &db_clone, &current_filters_clone(), &poster_cache_clone);
+    });
+
+    // Missing files filter - show the last known result now, and again once files are rechecked
+    let db_clone = db.clone();
+    missing_files_check.connect_toggled(move |check| {
+        run_search();
+        if check.is_active() {
+            let run_search = run_search.clone();
+            check_missing_files(&db_clone, move || run_search());
+        }
+    });
+
+    // Check for missing files in the background at startup, then badge the affected rows
+    let db_clone = db.clone();
+    let list_box_clone = list_box.clone();
     let poster_cache_clone = poster_cache.clone();
+    check_missing_files(&db, move || {
+        let mut child = list_box_clone.first_child();
+        while let Some(widget) = child {
+            child = widget.next_sibling();
+            let Some(row) = widget.downcast_ref::<gtk::ListBoxRow>() else {
+                continue;
+            };
+            if let Ok(movie_id) = row.widget_name().parse::<u32>() {
+                refresh_row_contents(row, &db_clone, movie_id, &poster_cache_clone);
+            }
+        }
+    });
+
+    // View toggle - switch between list and poster grid, remembering the choice
     let is_grid_view_clone = is_grid_view.clone();
-    sort_dropdown.connect_selected_notify(move |dropdown| {
-        let sort_idx = dropdown.selected();
-        let sorts = ["Title (A-Z)", "Year (Newest)", "Year (Oldest)", "Rating (High-Low)", "Rating (Low-High)", "Date Added (Newest)", "Date Added (Oldest)"];
-        let sort_by = sorts.get(sort_idx as usize).unwrap_or(&"Title (A-Z)");
+    let scrolled_clone = scrolled.clone();
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let db_clone = db.clone();
+    let current_filters_clone = current_filters.clone();
+    let poster_cache_clone = poster_cache.clone();
+    view_toggle.connect_clicked(move |button| {
+        let is_grid = !*is_grid_view_clone.borrow();
+        *is_grid_view_clone.borrow_mut() = is_grid;
         
-        let query = search_entry_clone.text().to_string();
-        let selected_idx = genre_dropdown_clone.selected();
-        let genres = ["All", "Action", "Comedy", "Drama", "Film Noir", "Horror", "Sci-Fi", "Thriller", "Romance"];
-        let selected_genre = genres.get(selected_idx as usize).unwrap_or(&"All");
+        if is_grid {
+            button.set_label("🎞️ Grid");
+            scrolled_clone.set_child(Some(&grid_flow_clone));
+        } else {
+            button.set_label("📋 List");
+            scrolled_clone.set_child(Some(&list_box_clone));
+        }
+        refresh_movie_list(&list_box_clone, &grid_flow_clone, is_grid, &db_clone, &current_filters_clone(), &poster_cache_clone);
         
+        let mut config = load_config().unwrap_or_default();
+        config.view_mode = if is_grid { "grid" } else { "list" }.to_string();
+        if let Err(e) = save_config(&config) {
+            log_warn!("Warning: Failed to save view mode: {}", e);
+        }
+    });
+
+    // Surprise Me - select a random movie among the rows the filters left, so a random
+    // unwatched comedy is a filter and a click away
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let is_grid_view_clone = is_grid_view.clone();
+    let status_bar_clone = status_bar.clone();
+    surprise_button.connect_clicked(move |_| {
         let is_grid = *is_grid_view_clone.borrow();
-        refresh_movie_list(&list_box_clone, &grid_flow_clone, is_grid, &db_clone, &query, selected_genre, sort_by, &poster_cache_clone);
+        let mut shown = Vec::new();
+        let mut child = if is_grid { grid_flow_clone.first_child() } else { list_box_clone.first_child() };
+        while let Some(widget) = child {
+            child = widget.next_sibling();
+            if widget.widget_name().parse::<u32>().is_ok() {
+                shown.push(widget);
+            }
+        }
+        if shown.is_empty() {
+            status_bar_clone.set_text("No movies match the current filters");
+            return;
+        }
+        let picked = &shown[glib::random_int_range(0, shown.len() as i32) as usize];
+        if let Some(item) = picked.downcast_ref::<gtk::FlowBoxChild>() {
+            grid_flow_clone.unselect_all();
+            grid_flow_clone.select_child(item);
+            item.activate();
+        } else if let Some(row) = picked.downcast_ref::<gtk::ListBoxRow>() {
+            list_box_clone.unselect_all();
+            list_box_clone.select_row(Some(row));
+        }
+        // Focusing the pick scrolls it into view
+        picked.grab_focus();
+        status_bar_clone.set_text(&format!("🎲 Picked at random from {} movies", shown.len()));
     });
 
     // Movie selection
     let details_label_clone = details_label.clone();
     let poster_display_clone = poster_display.clone();
+    let trailer_button_clone = trailer_button.clone();
+    let open_folder_button_clone = open_folder_button.clone();
+    let watched_button_clone = watched_button.clone();
+    let favorite_button_clone = favorite_button.clone();
+    let links_row_clone = links_row.clone();
+    let crew_expander_clone = crew_expander.clone();
+    let crew_label_clone = crew_label.clone();
     let db_clone = db.clone();
     let selected_movie_id = Rc::new(RefCell::new(0u32));
     let selected_movie_id_clone = selected_movie_id.clone();
     
-    list_box.connect_row_selected(move |_, row| {
-        if let Some(row) = row {
-            // Get the movie ID from the row's widget name
-            let movie_id_str = row.widget_name();
-            if let Ok(movie_id) = movie_id_str.as_str().parse::<u32>() {
-                *selected_movie_id_clone.borrow_mut() = movie_id;
+    // One selected row shows that movie's details; several show a count and the bulk bar
+    let bulk_bar_clone = bulk_bar.clone();
+    let bulk_label_clone = bulk_label.clone();
+    list_box.connect_selected_rows_changed(move |list_box| {
+        let selected = selected_row_ids(list_box);
+        bulk_bar_clone.set_visible(selected.len() > 1);
+        bulk_label_clone.set_text(&format!("{} movies selected", selected.len()));
+        match selected.as_slice() {
+            [] => {}
+            [movie_id] => {
+                *selected_movie_id_clone.borrow_mut() = *movie_id;
                 
                 // Get the actual movie from the database by ID
                 let db = db_clone.borrow();
-                if let Some(movie) = db.movies.get(&movie_id) {
+                if let Some(movie) = db.movies.get(movie_id) {
                     // Update poster - load full resolution then scale for display
                     if !movie.poster_path.is_empty() && Path::new(&movie.poster_path).exists() {
                         if let Ok(pixbuf) = Pixbuf::from_file(&movie.poster_path) {
@@ -1967,55 +8020,41 @@ fn build_ui(app: &Application) {
                         poster_display_clone.set_pixbuf(None);
                     }
                     
-                    // Escape all text that goes into markup
-                    let escaped_title = escape_markup(&movie.title);
-                    let escaped_director = escape_markup(&movie.director);
-                    let escaped_genre = escape_markup(&movie.genre.join(", "));
-                    let escaped_description = escape_markup(&movie.description);
-                    let escaped_file = escape_markup(&movie.file_path);
-                    
-                    // Format cast members with better visual presentation
-                    let cast_display = if !movie.cast.is_empty() {
-                        let cast_list: Vec<String> = movie.cast.iter()
-                            .map(|name| escape_markup(name))
-                            .collect();
-                        cast_list.join("\n    • ")
-                    } else {
-                        String::from("Unknown")
-                    };
-                    
-                    // Format IMDb ID display (with clickable link if available)
-                    let imdb_display = if !movie.imdb_id.is_empty() {
-                        format!("{} (https://www.imdb.com/title/{})", movie.imdb_id, movie.imdb_id)
-                    } else {
-                        String::from("Not available")
-                    };
-                    
-                    let details = format!(
-                        "<b>{}</b> ({})\n\n\
-                        <b>Director:</b> {}\n\
-                        <b>Genre:</b> {}\n\
-                        <b>Rating:</b> ⭐ {:.1}/10\n\
-                        <b>Runtime:</b> {} minutes\n\n\
-                        <b>Starring:</b>\n    • {}\n\n\
-                        <b>Description:</b>\n{}\n\n\
-                        <b>File:</b> {}\n\
-                        <b>TMDB ID:</b> {}\n\
-                        <b>IMDb ID:</b> {}",
-                        escaped_title, movie.year, escaped_director,
-                        escaped_genre, movie.rating, movie.runtime,
-                        cast_display, escaped_description, escaped_file,
-                        movie.tmdb_id, imdb_display
-                    );
-                    details_label_clone.set_markup(&details);
+                    details_label_clone.set_markup(&details_pane_markup(movie));
+                    trailer_button_clone.set_sensitive(!movie.trailer_youtube_key.is_empty());
+                    open_folder_button_clone.set_sensitive(!movie.existing_files().is_empty());
+                    watched_button_clone.set_label(watched_button_label(movie.watched));
+                    favorite_button_clone.set_label(favorite_button_label(movie.favorite));
+                    fill_link_buttons(&links_row_clone, Some(movie));
+                    show_crew_in_pane(&crew_expander_clone, &crew_label_clone, Some(movie));
                 }
             }
+            _ => {
+                // The single-movie buttons have nothing to act on until one row is selected again
+                *selected_movie_id_clone.borrow_mut() = 0;
+                poster_display_clone.set_pixbuf(None);
+                details_label_clone.set_markup(&format!(
+                    "<b>{} movies selected</b>\n\nUse the bar above the list to tag, refresh or delete them together.",
+                    selected.len()
+                ));
+                trailer_button_clone.set_sensitive(false);
+                open_folder_button_clone.set_sensitive(false);
+                fill_link_buttons(&links_row_clone, None);
+                show_crew_in_pane(&crew_expander_clone, &crew_label_clone, None);
+            }
         }
     });
 
     // Grid view selection (same logic as list)
     let details_label_clone = details_label.clone();
     let poster_display_clone = poster_display.clone();
+    let trailer_button_clone = trailer_button.clone();
+    let open_folder_button_clone = open_folder_button.clone();
+    let watched_button_clone = watched_button.clone();
+    let favorite_button_clone = favorite_button.clone();
+    let links_row_clone = links_row.clone();
+    let crew_expander_clone = crew_expander.clone();
+    let crew_label_clone = crew_label.clone();
     let db_clone = db.clone();
     let selected_movie_id_clone = selected_movie_id.clone();
     
@@ -2039,75 +8078,121 @@ fn build_ui(app: &Application) {
                     poster_display_clone.set_pixbuf(None);
                 }
                 
-                // Escape all text that goes into markup
-                let escaped_title = escape_markup(&movie.title);
-                let escaped_director = escape_markup(&movie.director);
-                let escaped_genre = escape_markup(&movie.genre.join(", "));
-                let escaped_description = escape_markup(&movie.description);
-                let escaped_file = escape_markup(&movie.file_path);
-                
-                // Format cast members with better visual presentation
-                let cast_display = if !movie.cast.is_empty() {
-                    let cast_list: Vec<String> = movie.cast.iter()
-                        .map(|name| escape_markup(name))
-                        .collect();
-                    cast_list.join("\n    • ")
-                } else {
-                    String::from("Unknown")
-                };
-                
-                // Format IMDb ID display (with clickable link if available)
-                let imdb_display = if !movie.imdb_id.is_empty() {
-                    format!("{} (https://www.imdb.com/title/{})", movie.imdb_id, movie.imdb_id)
-                } else {
-                    String::from("Not available")
-                };
-                
-                let details = format!(
-                    "<b>{}</b> ({})\n\n\
-                    <b>Director:</b> {}\n\
-                    <b>Genre:</b> {}\n\
-                    <b>Rating:</b> ⭐ {:.1}/10\n\
-                    <b>Runtime:</b> {} minutes\n\n\
-                    <b>Starring:</b>\n    • {}\n\n\
-                    <b>Description:</b>\n{}\n\n\
-                    <b>File:</b> {}\n\
-                    <b>TMDB ID:</b> {}\n\
-                    <b>IMDb ID:</b> {}",
-                    escaped_title, movie.year, escaped_director,
-                    escaped_genre, movie.rating, movie.runtime,
-                    cast_display, escaped_description, escaped_file,
-                    movie.tmdb_id, imdb_display
-                );
-                details_label_clone.set_markup(&details);
+                details_label_clone.set_markup(&details_pane_markup(movie));
+                trailer_button_clone.set_sensitive(!movie.trailer_youtube_key.is_empty());
+                open_folder_button_clone.set_sensitive(!movie.existing_files().is_empty());
+                watched_button_clone.set_label(watched_button_label(movie.watched));
+                favorite_button_clone.set_label(favorite_button_label(movie.favorite));
+                fill_link_buttons(&links_row_clone, Some(movie));
+                show_crew_in_pane(&crew_expander_clone, &crew_label_clone, Some(movie));
+            }
+        }
+    });
+
+    // Clicking the details poster opens it full size. Posters are saved at TMDB's original
+    // resolution, so the local file is already the best copy there is.
+    poster_display.set_cursor_from_name(Some("pointer"));
+    poster_display.set_tooltip_text(Some("Click to view the poster full size"));
+    let poster_gesture = gtk::GestureClick::new();
+    let db_clone = db.clone();
+    let selected_movie_id_clone = selected_movie_id.clone();
+    let window_clone = window.clone();
+    poster_gesture.connect_released(move |_, _, _, _| {
+        let movie_id = *selected_movie_id_clone.borrow();
+        let Some((title, poster_path)) = db_clone.borrow().movies.get(&movie_id)
+            .filter(|m| has_usable_poster(m))
+            .map(|m| (format!("{} ({})", m.title, m.year), m.poster_path.clone()))
+        else {
+            return;
+        };
+        show_poster_preview(&window_clone, &title, &poster_path);
+    });
+    poster_display.add_controller(poster_gesture);
+
+    // Trailer button - open the TMDB trailer on YouTube in the browser
+    let db_clone = db.clone();
+    let selected_movie_id_clone = selected_movie_id.clone();
+    let window_clone = window.clone();
+    trailer_button.connect_clicked(move |_| {
+        let movie_id = *selected_movie_id_clone.borrow();
+        let Some(key) = db_clone.borrow().movies.get(&movie_id).map(|m| m.trailer_youtube_key.clone()) else {
+            return;
+        };
+        if key.is_empty() {
+            return;
+        }
+        gtk::UriLauncher::new(&youtube_watch_url(&key)).launch(Some(&window_clone), None::<&gtk::gio::Cancellable>, |result| {
+            if let Err(e) = result {
+                log_warn!("Warning: Failed to open trailer: {}", e);
             }
+        });
+    });
+
+    // Favorite button - flip the selected movie's favorite flag and update its ♥ in the list
+    let db_clone = db.clone();
+    let selected_movie_id_clone = selected_movie_id.clone();
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let poster_cache_clone = poster_cache.clone();
+    favorite_button.connect_clicked(move |button| {
+        let movie_id = *selected_movie_id_clone.borrow();
+        let Some(favorite) = db_clone.borrow_mut().toggle_favorite(movie_id) else {
+            return;
+        };
+        button.set_label(favorite_button_label(favorite));
+        refresh_movie_in_views(&list_box_clone, &grid_flow_clone, &db_clone, movie_id, &poster_cache_clone);
+    });
+
+    // Watched button - flip the selected movie's watched flag and redraw it in place
+    let db_clone = db.clone();
+    let selected_movie_id_clone = selected_movie_id.clone();
+    let details_label_clone = details_label.clone();
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let poster_cache_clone = poster_cache.clone();
+    watched_button.connect_clicked(move |button| {
+        let movie_id = *selected_movie_id_clone.borrow();
+        let Some(watched) = db_clone.borrow_mut().toggle_watched(movie_id) else {
+            return;
+        };
+        button.set_label(watched_button_label(watched));
+        if let Some(movie) = db_clone.borrow().movies.get(&movie_id) {
+            details_label_clone.set_markup(&details_pane_markup(movie));
         }
+        refresh_movie_in_views(&list_box_clone, &grid_flow_clone, &db_clone, movie_id, &poster_cache_clone);
     });
 
-    // Play button - launch VLC
+    // Play button - launch the configured player
     let db_clone = db.clone();
     let selected_movie_id_clone = selected_movie_id.clone();
     let status_bar_clone = status_bar.clone();
+    let window_clone = window.clone();
     play_button.connect_clicked(move |_| {
         let movie_id = *selected_movie_id_clone.borrow();
         if movie_id > 0 {
             let db = db_clone.borrow();
             if let Some(movie) = db.movies.get(&movie_id) {
-                let file_path = movie.file_path.clone();
+                let files = movie.existing_files();
                 let movie_title = movie.title.clone();
                 drop(db);  // Release borrow early
                 
-                if !file_path.is_empty() && Path::new(&file_path).exists() {
-                    // Try to launch VLC with suppressed output
-                    match Command::new("vlc")
-                        .arg(&file_path)
-                        .stdout(Stdio::null())
-                        .stderr(Stdio::null())
-                        .spawn()
-                    {
+                if files.is_empty() {
+                    status_bar_clone.set_text("No video file associated with this movie");
+                    return;
+                }
+                
+                // Ask which copy to play when there are several
+                let chosen = choose_file_to_play(&window_clone, &movie_title, files);
+                let db_clone = db_clone.clone();
+                let status_bar_clone = status_bar_clone.clone();
+                glib::spawn_future_local(async move {
+                    let Some(file_path) = chosen.await else {
+                        return;
+                    };
+                    match launch_player(&file_path) {
                         Ok(_) => {
                             status_bar_clone.set_text(&format!("Playing: {}", movie_title));
-                            
+                        
                             // Auto-log to watch history (asynchronously - don't block VLC launch)
                             let db_clone_async = db_clone.clone();
                             let movie_title_async = movie_title.clone();
@@ -2115,77 +8200,69 @@ fn build_ui(app: &Application) {
                             glib::spawn_future_local(async move {
                                 // Small delay to ensure VLC has launched
                                 glib::timeout_future(std::time::Duration::from_millis(100)).await;
-                                
+                            
                                 let mut db_mut = db_clone_async.borrow_mut();
-                                if let Some(movie) = db_mut.movies.get_mut(&movie_id) {
-                                    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
-                                    let entry = WatchLogEntry {
-                                        date: today,
-                                        rating: None,
-                                        comments: String::from("Watched"),
-                                    };
-                                    movie.watch_log.push(entry);
+                                if let Some(movie) = db_mut.movie_mut(movie_id) {
+                                    movie.record_play();
                                 }
+                                db_mut.invalidate_result_cache();
                                 if let Err(e) = db_mut.save_to_file() {
-                                    eprintln!("Warning: Failed to save watch log: {}", e);
+                                    log_warn!("Warning: Failed to save watch log: {}", e);
+                                    status_bar_async.set_text(&format!("Playing: {} (watch log not saved: {})", movie_title_async, e));
                                 } else {
                                     status_bar_async.set_text(&format!("Playing: {} (logged)", movie_title_async));
                                 }
                             });
                         }
-                        Err(_) => {
-                            // Try flatpak version
-                            match Command::new("flatpak")
-                                .args(["run", "org.videolan.VLC", &file_path])
-                                .stdout(Stdio::null())
-                                .stderr(Stdio::null())
-                                .spawn()
-                            {
-                                Ok(_) => {
-                                    status_bar_clone.set_text(&format!("Playing: {}", movie_title));
-                                    
-                                    // Auto-log to watch history (asynchronously)
-                                    let db_clone_async = db_clone.clone();
-                                    let movie_title_async = movie_title.clone();
-                                    let status_bar_async = status_bar_clone.clone();
-                                    glib::spawn_future_local(async move {
-                                        glib::timeout_future(std::time::Duration::from_millis(100)).await;
-                                        
-                                        let mut db_mut = db_clone_async.borrow_mut();
-                                        if let Some(movie) = db_mut.movies.get_mut(&movie_id) {
-                                            let today = chrono::Local::now().format("%Y-%m-%d").to_string();
-                                            let entry = WatchLogEntry {
-                                                date: today,
-                                                rating: None,
-                                                comments: String::from("Watched"),
-                                            };
-                                            movie.watch_log.push(entry);
-                                        }
-                                        if let Err(e) = db_mut.save_to_file() {
-                                            eprintln!("Warning: Failed to save watch log: {}", e);
-                                        } else {
-                                            status_bar_async.set_text(&format!("Playing: {} (logged)", movie_title_async));
-                                        }
-                                    });
-                                }
-                                Err(_) => {
-                                    status_bar_clone.set_text("VLC not found. Please install VLC.");
-                                }
-                            }
+                        Err(e) => {
+                            status_bar_clone.set_text(&e);
                         }
                     }
-                } else {
-                    status_bar_clone.set_text("No video file associated with this movie");
-                }
+                });
             }
         }
     });
 
+    // The row context menu selects the row, then plays it the same way
+    let play_selected_action = gtk::gio::SimpleAction::new("play-selected", None);
+    let play_button_clone = play_button.clone();
+    play_selected_action.connect_activate(move |_, _| play_button_clone.emit_clicked());
+    window.add_action(&play_selected_action);
+
+    // Double-clicking a row plays it; the first click has already selected it
+    let play_button_clone = play_button.clone();
+    list_box.connect_row_activated(move |list_box, row| {
+        list_box.select_row(Some(row));
+        play_button_clone.emit_clicked();
+    });
+
+    // Open Folder button - show the movie's file (the first copy still on disk) in the file manager
+    let db_clone = db.clone();
+    let selected_movie_id_clone = selected_movie_id.clone();
+    let status_bar_clone = status_bar.clone();
+    let window_clone = window.clone();
+    open_folder_button.connect_clicked(move |_| {
+        let movie_id = *selected_movie_id_clone.borrow();
+        let Some(file) = db_clone.borrow().movies.get(&movie_id).and_then(|m| m.existing_files().into_iter().next()) else {
+            status_bar_clone.set_text("No video file on disk for this movie - use Associate File to pick one");
+            return;
+        };
+        let status_bar_clone2 = status_bar_clone.clone();
+        gtk::FileLauncher::new(Some(&gtk::gio::File::for_path(&file)))
+            .open_containing_folder(Some(&window_clone), None::<&gtk::gio::Cancellable>, move |result| {
+                if let Err(e) = result {
+                    log_warn!("Warning: Failed to open folder of {}: {}", file, e);
+                    status_bar_clone2.set_text(&format!("Couldn't open the folder: {}", e));
+                }
+            });
+    });
+
     // Associate File button
     let db_clone = db.clone();
     let window_clone = window.clone();
     let selected_movie_id_clone = selected_movie_id.clone();
     let details_label_clone = details_label.clone();
+    let open_folder_button_clone = open_folder_button.clone();
     let list_box_clone = list_box.clone();
     let poster_cache_clone = poster_cache.clone();
     associate_file_button.connect_clicked(move |_| {
@@ -2203,6 +8280,8 @@ fn build_ui(app: &Application) {
         let details_label_clone2 = details_label_clone.clone();
         let list_box_clone2 = list_box_clone.clone();
         let poster_cache_clone2 = poster_cache_clone.clone();
+        let selected_movie_id_clone2 = selected_movie_id_clone.clone();
+        let open_folder_button_clone2 = open_folder_button_clone.clone();
         file_dialog.open(Some(&window_clone), gtk::gio::Cancellable::NONE, move |result| {
             if let Ok(file) = result {
                 if let Some(path) = file.path() {
@@ -2210,63 +8289,26 @@ fn build_ui(app: &Application) {
                     
                     // Update movie with new file path
                     let mut db = db_clone2.borrow_mut();
-                    if let Some(movie) = db.movies.get_mut(&movie_id) {
-                        movie.file_path = file_path.clone();
+                    if let Some(movie) = db.movie_mut(movie_id) {
+                        // Another copy (e.g. a 4K version) is added alongside the existing files.
+                        // Paths that no longer exist are dropped, so a moved file replaces its old entry.
+                        movie.file_paths.retain(|path| Path::new(path).exists());
+                        if !movie.file_paths.contains(&file_path) {
+                            movie.file_paths.push(file_path.clone());
+                        }
+                        update_local_file_info(movie);
                         drop(db); // Release borrow
+                        MISSING_FILES.with(|missing| missing.borrow_mut().remove(&movie_id));
+                        db_clone2.borrow().invalidate_result_cache();
                         if let Err(e) = db_clone2.borrow_mut().save_to_file() {
-                            eprintln!("Warning: Failed to save file association: {}", e);
+                            log_warn!("Warning: Failed to save file association: {}", e);
                         }
                         
                         // Refresh details display
                         let db = db_clone2.borrow();
                         if let Some(updated_movie) = db.movies.get(&movie_id) {
-                            let escaped_title = escape_markup(&updated_movie.title);
-                            let escaped_director = escape_markup(&updated_movie.director);
-                            let escaped_genre = escape_markup(&updated_movie.genre.join(", "));
-                            let escaped_description = escape_markup(&updated_movie.description);
-                            let escaped_file = escape_markup(&updated_movie.file_path);
-                            
-                            let cast_display = if !updated_movie.cast_details.is_empty() {
-                                let cast_list: Vec<String> = updated_movie.cast_details.iter()
-                                    .map(|cm| {
-                                        let name = escape_markup(&cm.name);
-                                        let character = escape_markup(&cm.character);
-                                        format!("{} ({})", name, character)
-                                    })
-                                    .collect();
-                                cast_list.join("\n    • ")
-                            } else if !updated_movie.cast.is_empty() {
-                                let cast_list: Vec<String> = updated_movie.cast.iter()
-                                    .map(|name| escape_markup(name))
-                                    .collect();
-                                cast_list.join("\n    • ")
-                            } else {
-                                String::from("Unknown")
-                            };
-                            
-                            let imdb_display = if !updated_movie.imdb_id.is_empty() {
-                                format!("{} (https://www.imdb.com/title/{})", updated_movie.imdb_id, updated_movie.imdb_id)
-                            } else {
-                                String::from("Not available")
-                            };
-                            
-                            let details = format!(
-                                "<b>{}</b> ({})\n\n\
-                                <b>Director:</b> {}\n\
-                                <b>Genre:</b> {}\n\
-                                <b>Rating:</b> ⭐ {:.1}/10\n\
-                                <b>Runtime:</b> {} minutes\n\n\
-                                <b>Starring:</b>\n    • {}\n\n\
-                                <b>Description:</b>\n{}\n\n\
-                                <b>File:</b> {}\n\
-                                <b>TMDB ID:</b> {}\n\
-                                <b>IMDb ID:</b> {}",
-                                escaped_title, updated_movie.year, escaped_director,
-                                escaped_genre, updated_movie.rating, updated_movie.runtime,
-                                cast_display, escaped_description, escaped_file,
-                                updated_movie.tmdb_id, imdb_display
-                            );
-                            details_label_clone2.set_markup(&details);
+                            details_label_clone2.set_markup(&details_pane_markup(updated_movie));
+                            open_folder_button_clone2.set_sensitive(true);
                         }
                         
                         // Refresh movie list
@@ -2278,6 +8320,35 @@ fn build_ui(app: &Application) {
                             let row = create_movie_row(movie, &poster_cache_clone2);
                             list_box_clone2.append(&row);
                         }
+                        
+                        // Read the technical details in the background, ffprobe can take a while
+                        if load_config().unwrap_or_default().use_ffprobe {
+                            let primary_file = db.movies.get(&movie_id).map(|m| m.primary_file().to_string()).unwrap_or_default();
+                            let (sender, receiver) = async_channel::bounded::<Option<ProbedFile>>(1);
+                            let probe_path = primary_file.clone();
+                            std::thread::spawn(move || {
+                                let _ = sender.send_blocking(probe_video_file(&probe_path));
+                            });
+                            let db_clone3 = db_clone2.clone();
+                            let details_label_clone3 = details_label_clone2.clone();
+                            let selected_movie_id_clone3 = selected_movie_id_clone2.clone();
+                            glib::spawn_future_local(async move {
+                                let Ok(Some(probed)) = receiver.recv().await else {
+                                    return;
+                                };
+                                let mut db = db_clone3.borrow_mut();
+                                let Some(movie) = db.movie_mut(movie_id).filter(|m| m.primary_file() == primary_file) else {
+                                    return;
+                                };
+                                probed.apply_to(movie);
+                                if *selected_movie_id_clone3.borrow() == movie_id {
+                                    details_label_clone3.set_markup(&details_pane_markup(movie));
+                                }
+                                if let Err(e) = db.save_to_file() {
+                                    log_warn!("Warning: Failed to save file info: {}", e);
+                                }
+                            });
+                        }
                     }
                 }
             }
@@ -2293,67 +8364,136 @@ fn build_ui(app: &Application) {
     delete_button.connect_clicked(move |_| {
         let movie_id = *selected_movie_id_clone.borrow();
         if movie_id > 0 {
-            let dialog = gtk::AlertDialog::builder()
-                .message("Delete Movie")
-                .detail("Are you sure you want to delete this movie?")
-                .buttons(vec!["Cancel", "Delete"])
-                .cancel_button(0)
-                .default_button(0)
-                .build();
+            let confirmed = confirm(
+                &window_clone,
+                "Delete Movie",
+                "Are you sure you want to delete this movie?",
+                "Delete",
+            );
 
             let db_clone2 = db_clone.clone();
             let list_box_clone2 = list_box_clone.clone();
             let poster_cache_clone2 = poster_cache_clone.clone();
-            dialog.choose(Some(&window_clone), None::<&gtk::gio::Cancellable>, move |response| {
-                if let Ok(1) = response {
-                    if db_clone2.borrow_mut().delete_movie(movie_id) {
-                        while let Some(child) = list_box_clone2.first_child() {
-                            list_box_clone2.remove(&child);
-                        }
-                        let movies = db_clone2.borrow().list_all();
-                        for movie in &movies {
-                            let row = create_movie_row(movie, &poster_cache_clone2);
-                            list_box_clone2.append(&row);
-                        }
+            glib::spawn_future_local(async move {
+                if confirmed.await && db_clone2.borrow_mut().delete_movie(movie_id) {
+                    if let Some(movie) = &db_clone2.borrow().last_deleted {
+                        offer_undo_delete(&movie.title);
+                    }
+                    while let Some(child) = list_box_clone2.first_child() {
+                        list_box_clone2.remove(&child);
+                    }
+                    let movies = db_clone2.borrow().list_all();
+                    for movie in &movies {
+                        let row = create_movie_row(movie, &poster_cache_clone2);
+                        list_box_clone2.append(&row);
                     }
                 }
             });
         }
     });
 
+    // Save retry - write the in-memory library again once the disk problem is fixed
+    let db_clone = db.clone();
+    let status_bar_clone = status_bar.clone();
+    save_retry_button.connect_clicked(move |_| {
+        match db_clone.borrow().save_to_file() {
+            Ok(()) => status_bar_clone.set_text("Library saved"),
+            Err(e) => status_bar_clone.set_text(&format!("Still can't save: {} {}", e, save_error_hint(&e))),
+        }
+    });
+
+    // Undo Delete - put back the last deleted movie
+    let db_clone = db.clone();
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let status_bar_clone = status_bar.clone();
+    let poster_cache_clone = poster_cache.clone();
+    let is_grid_view_clone = is_grid_view.clone();
+    let current_filters_clone = current_filters.clone();
+    undo_delete_button.connect_clicked(move |button| {
+        button.set_visible(false);
+        let restored = db_clone.borrow_mut().undo_delete();
+        match restored {
+            Some((_, title)) => {
+                let is_grid = *is_grid_view_clone.borrow();
+                refresh_movie_list(&list_box_clone, &grid_flow_clone, is_grid, &db_clone, &current_filters_clone(), &poster_cache_clone);
+                status_bar_clone.set_text(&format!("Restored: {}", title));
+            }
+            // Cleared by another change to the library since
+            None => status_bar_clone.set_text("Nothing to undo"),
+        }
+    });
+
     // Keyboard shortcuts
     let event_controller = gtk::EventControllerKey::new();
     let search_entry_shortcut = search_entry.clone();
     let selected_movie_id_shortcut = selected_movie_id.clone();
     let play_button_shortcut = play_button.clone();
     let delete_button_shortcut = delete_button.clone();
+    let refresh_button_shortcut = refresh_button.clone();
+    let edit_button_shortcut = edit_button.clone();
+    let undo_delete_button_shortcut = undo_delete_button.clone();
+    let bulk_delete_button_shortcut = bulk_delete_button.clone();
     
-    event_controller.connect_key_pressed(move |_, key, _code, modifier| {
+    event_controller.connect_key_pressed(move |controller, key, _code, modifier| {
         use gtk::gdk::Key;
         use gtk::gdk::ModifierType;
         
+        let control = modifier.contains(ModifierType::CONTROL_MASK);
+        
         // Ctrl+F: Focus search
-        if modifier.contains(ModifierType::CONTROL_MASK) && key == Key::f {
+        if control && key == Key::f {
             search_entry_shortcut.grab_focus();
             return gtk::glib::Propagation::Stop;
         }
         
-        // Delete: Delete selected movie
-        if key == Key::Delete {
-            let movie_id = *selected_movie_id_shortcut.borrow();
+        let movie_id = *selected_movie_id_shortcut.borrow();
+        
+        // Ctrl+R: Refresh metadata, Ctrl+E: Edit metadata
+        if control && (key == Key::r || key == Key::e) {
             if movie_id > 0 {
-                delete_button_shortcut.emit_clicked();
+                if key == Key::r {
+                    if refresh_button_shortcut.is_sensitive() {
+                        refresh_button_shortcut.emit_clicked();
+                    }
+                } else {
+                    edit_button_shortcut.emit_clicked();
+                }
+            }
+            return gtk::glib::Propagation::Stop;
+        }
+        
+        // The remaining keys belong to the search entry (or any other text field) while it has focus
+        let typing = controller.widget()
+            .downcast::<gtk::Window>().ok()
+            .and_then(|window| GtkWindowExt::focus(&window))
+            .is_some_and(|widget| widget.is::<gtk::Text>() || widget.is::<gtk::TextView>());
+        if typing {
+            return gtk::glib::Propagation::Proceed;
+        }
+        
+        // Ctrl+Z: Undo the last delete
+        if control && key == Key::z {
+            if undo_delete_button_shortcut.is_visible() {
+                undo_delete_button_shortcut.emit_clicked();
             }
             return gtk::glib::Propagation::Stop;
         }
         
-        // Space: Play selected movie
-        if key == Key::space {
-            let movie_id = *selected_movie_id_shortcut.borrow();
+        // Delete: Delete selected movie, or every selected movie
+        if key == Key::Delete {
             if movie_id > 0 {
-                play_button_shortcut.emit_clicked();
-                return gtk::glib::Propagation::Stop;
+                delete_button_shortcut.emit_clicked();
+            } else if bulk_delete_button_shortcut.is_visible() {
+                bulk_delete_button_shortcut.emit_clicked();
             }
+            return gtk::glib::Propagation::Stop;
+        }
+        
+        // Enter/Space: Play selected movie
+        if (key == Key::space || key == Key::Return || key == Key::KP_Enter) && movie_id > 0 {
+            play_button_shortcut.emit_clicked();
+            return gtk::glib::Propagation::Stop;
         }
         
         gtk::glib::Propagation::Proceed
@@ -2382,8 +8522,9 @@ fn build_ui(app: &Application) {
 
                 // Clone the cast details for background thread
                 let cast_details = movie.cast_details.clone();
-                let cast_details_for_ui = cast_details.clone(); // Clone for UI thread
+                let total = cast_details.len();
                 let movie_title = movie.title.clone();
+                let cast_photos_dir = cast_photos_dir(&db.posters_dir);
 
                 // Create cast dialog
                 let cast_dialog = Window::builder()
@@ -2410,47 +8551,38 @@ fn build_ui(app: &Application) {
                 cast_dialog.set_child(Some(&scroll));
                 cast_dialog.present();
 
-                // Download photos in background thread
-                let (sender, receiver) = async_channel::unbounded::<(String, String, String, Vec<u8>)>();
+                // Load photos in background thread, downloading only those not cached yet
+                let (sender, receiver) = async_channel::unbounded::<(usize, CastMember)>();
                 
                 std::thread::spawn(move || {
-                    for cast_member in &cast_details {
-                        if !cast_member.profile_path.is_empty() {
-                            if let Ok(response) = reqwest::blocking::get(&cast_member.profile_path) {
-                                if let Ok(bytes) = response.bytes() {
-                                    let _ = sender.send_blocking((
-                                        cast_member.name.clone(),
-                                        cast_member.character.clone(),
-                                        cast_member.profile_path.clone(),
-                                        bytes.to_vec()
-                                    ));
-                                    continue;
-                                }
-                            }
+                    let client = reqwest::blocking::Client::new();
+                    for (index, mut cast_member) in cast_details.into_iter().enumerate() {
+                        let cached = !cast_member.local_photo_path.is_empty() && Path::new(&cast_member.local_photo_path).exists();
+                        if !cached {
+                            cast_member.local_photo_path = download_cast_photo(&client, &cast_member.profile_path, &cast_photos_dir)
+                                .map(|path| path.to_string_lossy().to_string())
+                                .unwrap_or_default();
                         }
-                        // Send with empty bytes if no photo
-                        let _ = sender.send_blocking((
-                            cast_member.name.clone(),
-                            cast_member.character.clone(),
-                            String::new(),
-                            vec![]
-                        ));
+                        let _ = sender.send_blocking((index, cast_member));
                     }
                 });
 
                 // Update UI as photos arrive
                 let cast_box_clone = cast_box.clone();
+                let db_clone2 = db_clone.clone();
                 glib::spawn_future_local(async move {
                     // Remove loading message
                     while let Some(child) = cast_box_clone.first_child() {
                         cast_box_clone.remove(&child);
                     }
 
+                    let mut new_photo_paths = Vec::new();
                     let mut count = 0;
-                    let total = cast_details_for_ui.len();
                     
                     while count < total {
-                        if let Ok((name, character, _profile_path, photo_bytes)) = receiver.recv().await {
+                        if let Ok((index, cast_member)) = receiver.recv().await {
+                            let name = &cast_member.name;
+                            let character = &cast_member.character;
                             let member_box = Box::new(Orientation::Horizontal, 12);
                             member_box.set_margin_bottom(12);
 
@@ -2458,16 +8590,17 @@ fn build_ui(app: &Application) {
                             let photo_box = Box::new(Orientation::Vertical, 0);
                             photo_box.set_size_request(120, 180);
                             
-                            if !photo_bytes.is_empty() {
-                                let loader = gtk::gdk_pixbuf::PixbufLoader::new();
-                                let _ = loader.write(&photo_bytes);
-                                let _ = loader.close();
-                                if let Some(pixbuf) = loader.pixbuf() {
-                                    if let Some(scaled_pixbuf) = pixbuf.scale_simple(120, 180, gtk::gdk_pixbuf::InterpType::Bilinear) {
-                                        let picture = Picture::for_pixbuf(&scaled_pixbuf);
-                                        photo_box.append(&picture);
-                                    }
+                            let pixbuf = if cast_member.local_photo_path.is_empty() {
+                                None
+                            } else {
+                                Pixbuf::from_file(&cast_member.local_photo_path).ok()
+                            };
+                            if let Some(pixbuf) = pixbuf {
+                                if let Some(scaled_pixbuf) = pixbuf.scale_simple(120, 180, gtk::gdk_pixbuf::InterpType::Bilinear) {
+                                    let picture = Picture::for_pixbuf(&scaled_pixbuf);
+                                    photo_box.append(&picture);
                                 }
+                                new_photo_paths.push((index, cast_member.local_photo_path.clone()));
                             } else {
                                 // Placeholder
                                 let placeholder = Label::new(Some("👤"));
@@ -2481,13 +8614,13 @@ fn build_ui(app: &Application) {
                             let info_box = Box::new(Orientation::Vertical, 4);
                             info_box.set_valign(Align::Center);
                             
-                            let name_label = Label::new(Some(&name));
+                            let name_label = Label::new(Some(name));
                             name_label.set_xalign(0.0);
-                            name_label.set_markup(&format!("<b>{}</b>", escape_markup(&name)));
+                            name_label.set_markup(&format!("<b>{}</b>", escape_markup(name)));
                             
-                            let character_label = Label::new(Some(&character));
+                            let character_label = Label::new(Some(character));
                             character_label.set_xalign(0.0);
-                            character_label.set_markup(&format!("<i>as {}</i>", escape_markup(&character)));
+                            character_label.set_markup(&format!("<i>as {}</i>", escape_markup(character)));
                             
                             info_box.append(&name_label);
                             if !character.is_empty() {
@@ -2501,6 +8634,26 @@ fn build_ui(app: &Application) {
                             count += 1;
                         }
                     }
+
+                    // Remember where the photos are cached so the next open skips the downloads
+                    let mut db_mut = db_clone2.borrow_mut();
+                    let Some(movie) = db_mut.movie_mut(movie_id) else {
+                        return;
+                    };
+                    let mut changed = false;
+                    for (index, path) in new_photo_paths {
+                        if let Some(member) = movie.cast_details.get_mut(index) {
+                            if member.local_photo_path != path {
+                                member.local_photo_path = path;
+                                changed = true;
+                            }
+                        }
+                    }
+                    if changed {
+                        if let Err(e) = db_mut.save_to_file() {
+                            eprintln!("Failed to save cast photo paths: {}", e);
+                        }
+                    }
                 });
             }
         }
@@ -2668,10 +8821,11 @@ fn build_ui(app: &Application) {
                 
                 // Add to movie
                 let mut db = db_clone2.borrow_mut();
-                if let Some(movie) = db.movies.get_mut(&movie_id) {
+                if let Some(movie) = db.movie_mut(movie_id) {
                     movie.watch_log.push(entry.clone());
+                    movie.watched = true;
                     if let Err(e) = db.save_to_file() {
-                        eprintln!("Warning: Failed to save watch log entry: {}", e);
+                        log_warn!("Warning: Failed to save watch log entry: {}", e);
                     }
                 }
                 drop(db);
@@ -2721,349 +8875,406 @@ fn build_ui(app: &Application) {
         }
     });
 
-    // Scan directory
+    // Preview Scan - fetch matches for a folder and review them before anything is added
     let window_clone = window.clone();
     let db_clone = db.clone();
     let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
     let status_bar_clone = status_bar.clone();
     let poster_cache_clone = poster_cache.clone();
-    scan_button.connect_clicked(move |_| {
+    let is_grid_view_clone = is_grid_view.clone();
+    let current_filters_clone = current_filters.clone();
+    let busy_clone = busy.clone();
+    let set_busy_clone = set_busy.clone();
+    preview_scan_action.connect_activate(move |_, _| {
         let dialog = gtk::FileDialog::new();
-        dialog.set_title("Select Movie Directory");
+        dialog.set_title("Select Movie Directory to Preview");
+
+        let window_clone2 = window_clone.clone();
+        let db_clone2 = db_clone.clone();
+        let list_box_clone2 = list_box_clone.clone();
+        let grid_flow_clone2 = grid_flow_clone.clone();
+        let status_bar_clone2 = status_bar_clone.clone();
+        let poster_cache_clone2 = poster_cache_clone.clone();
+        let is_grid_view_clone2 = is_grid_view_clone.clone();
+        let current_filters_clone2 = current_filters_clone.clone();
+        let busy_clone2 = busy_clone.clone();
+        let set_busy_clone2 = set_busy_clone.clone();
+        dialog.select_folder(Some(&window_clone), None::<&gtk::gio::Cancellable>, move |result| {
+            let Some(path) = result.ok().and_then(|folder| folder.path()) else {
+                return;
+            };
+            if busy_clone2.get() {
+                status_bar_clone2.set_text(BUSY_MESSAGE);
+                return;
+            }
+            set_busy_clone2(true);
+            let path_str = path.to_string_lossy().to_string();
+            
+            let api_key = db_clone2.borrow().tmdb_api_key.clone();
+            let posters_dir = db_clone2.borrow().posters_dir.clone();
+            let config = load_config().unwrap_or_default();
+            let scan = spawn_scan_worker(ScanJob::from_config(&config, &db_clone2.borrow(), vec![path_str.clone()], Vec::new()));
+            
+            // Collect proposals instead of adding them
+            glib::spawn_future_local(async move {
+                let mut proposals = Vec::new();
+                while let Ok((msg_type, status, movie_opt)) = scan.receiver.recv().await {
+                    match msg_type.as_str() {
+                        "status" => status_bar_clone2.set_text(&status),
+                        "progress" => ActiveScan::show_progress(&status),
+                        "add" => {
+                            if let Some(movie) = movie_opt {
+                                status_bar_clone2.set_text(&format!("Matched {} files...", proposals.len() + 1));
+                                proposals.push(movie);
+                            }
+                        }
+                        "complete" => break,
+                        _ => {}
+                    }
+                }
+                set_busy_clone2(false);
+                
+                if proposals.is_empty() {
+                    status_bar_clone2.set_text(if scan.active.was_cancelled() {
+                        "Preview cancelled"
+                    } else {
+                        "Preview complete - no new movies found"
+                    });
+                    return;
+                }
+                status_bar_clone2.set_text(&format!("Review {} proposed matches", proposals.len()));
+                proposals.sort_by(|a, b| a.primary_file().cmp(b.primary_file()));
+                
+                let db_for_discard = db_clone2.clone();
+                show_scan_review_dialog(window_clone2.upcast_ref(), proposals, api_key, posters_dir, move |accepted| {
+                    let mut added = 0;
+                    for movie in accepted {
+                        if db_clone2.borrow_mut().add_movie(movie).is_some() {
+                            added += 1;
+                        }
+                    }
+                    record_scan_completed(std::slice::from_ref(&path_str));
+                    let is_grid = *is_grid_view_clone2.borrow();
+                    refresh_movie_list(&list_box_clone2, &grid_flow_clone2, is_grid, &db_clone2, &current_filters_clone2(), &poster_cache_clone2);
+                    status_bar_clone2.set_text(&format!("Added {} reviewed movies", added));
+                }, move |unused_posters| {
+                    db_for_discard.borrow().remove_unused_posters(&unused_posters);
+                });
+            });
+        });
+    });
+
+    // Export Backup - data file, settings and posters in one zip
+    let window_clone = window.clone();
+    let db_clone = db.clone();
+    let status_bar_clone = status_bar.clone();
+    export_backup_action.connect_activate(move |_, _| {
+        let file_dialog = gtk::FileDialog::builder()
+            .title("Export Backup")
+            .initial_name(format!("movie_db_backup_{}.zip", chrono::Local::now().format("%Y-%m-%d_%H%M")))
+            .modal(true)
+            .build();
+        
+        let db_clone2 = db_clone.clone();
+        let status_bar_clone2 = status_bar_clone.clone();
+        file_dialog.save(Some(&window_clone), gtk::gio::Cancellable::NONE, move |result| {
+            let Some(target) = result.ok().and_then(|file| file.path()) else {
+                return;
+            };
+            match db_clone2.borrow().export_archive(&target, load_config().as_ref()) {
+                Ok(posters) => status_bar_clone2.set_text(&format!("Backup saved to {} ({} posters)", target.display(), posters)),
+                Err(e) => {
+                    log_warn!("Warning: Backup export failed: {}", e);
+                    status_bar_clone2.set_text(&format!("Backup failed: {}", e));
+                }
+            }
+        });
+    });
 
+    // Import Backup - replace the library with the contents of a backup zip
+    let window_clone = window.clone();
+    let db_clone = db.clone();
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let status_bar_clone = status_bar.clone();
+    let poster_cache_clone = poster_cache.clone();
+    let is_grid_view_clone = is_grid_view.clone();
+    let current_filters_clone = current_filters.clone();
+    import_backup_action.connect_activate(move |_, _| {
+        let file_dialog = gtk::FileDialog::builder()
+            .title("Import Backup")
+            .modal(true)
+            .build();
+        
+        let window_clone2 = window_clone.clone();
         let db_clone2 = db_clone.clone();
         let list_box_clone2 = list_box_clone.clone();
+        let grid_flow_clone2 = grid_flow_clone.clone();
         let status_bar_clone2 = status_bar_clone.clone();
         let poster_cache_clone2 = poster_cache_clone.clone();
+        let is_grid_view_clone2 = is_grid_view_clone.clone();
+        let current_filters_clone2 = current_filters_clone.clone();
+        file_dialog.open(Some(&window_clone), gtk::gio::Cancellable::NONE, move |result| {
+            let Some(source) = result.ok().and_then(|file| file.path()) else {
+                return;
+            };
+            glib::spawn_future_local(async move {
+                let movie_count = db_clone2.borrow().movies.len();
+                let replace = confirm(
+                    &window_clone2,
+                    "Import backup?",
+                    &format!("This replaces your current library ({} movies), its posters and your settings with the backup. Your API key is kept.", movie_count),
+                    "Import",
+                ).await;
+                if !replace {
+                    return;
+                }
+                let result = db_clone2.borrow_mut().import_archive(&source)
+                    .and_then(|(count, config)| {
+                        let restored_settings = config.is_some();
+                        config.map_or(Ok(()), restore_backup_settings)?;
+                        Ok((count, restored_settings))
+                    });
+                match result {
+                    Ok((count, restored_settings)) => {
+                        let is_grid = *is_grid_view_clone2.borrow();
+                        refresh_movie_list(&list_box_clone2, &grid_flow_clone2, is_grid, &db_clone2, &current_filters_clone2(), &poster_cache_clone2);
+                        if restored_settings {
+                            status_bar_clone2.set_text(&format!("Restored {} movies and settings from backup", count));
+                        } else {
+                            status_bar_clone2.set_text(&format!("Restored {} movies from backup", count));
+                        }
+                    }
+                    Err(e) => {
+                        log_warn!("Warning: Backup import failed: {}", e);
+                        status_bar_clone2.set_text(&format!("Import failed: {}", e));
+                    }
+                }
+            });
+        });
+    });
+
+    // Cancel running scans
+    let status_bar_clone = status_bar.clone();
+    cancel_scan_button.connect_clicked(move |_| {
+        ActiveScan::cancel_all();
+        status_bar_clone.set_text("Cancelling scan - finishing the lookups already under way...");
+    });
+
+    // Scan directory
+    let window_clone = window.clone();
+    let db_clone = db.clone();
+    let status_bar_clone = status_bar.clone();
+    let refresh_view_clone = refresh_view.clone();
+    let busy_clone = busy.clone();
+    let set_busy_clone = set_busy.clone();
+    scan_button.connect_clicked(move |button| {
+        let dialog = gtk::FileDialog::new();
+        dialog.set_title("Select Movie Directory");
+
+        let scan_button_clone = button.clone();
+        let db_clone2 = db_clone.clone();
+        let status_bar_clone2 = status_bar_clone.clone();
+        let refresh_view_clone2 = refresh_view_clone.clone();
+        let busy_clone2 = busy_clone.clone();
+        let set_busy_clone2 = set_busy_clone.clone();
         dialog.select_folder(Some(&window_clone), None::<&gtk::gio::Cancellable>, move |result| {
             if let Ok(folder) = result {
                 if let Some(path) = folder.path() {
+                    if busy_clone2.get() {
+                        status_bar_clone2.set_text(BUSY_MESSAGE);
+                        return;
+                    }
+                    set_busy_clone2(true);
                     let path_str = path.to_string_lossy().to_string();
                     
                     let db_clone3 = db_clone2.clone();
-                    let list_box_clone3 = list_box_clone2.clone();
                     let status_bar_clone3 = status_bar_clone2.clone();
                     
-                    // Create async channel
-                    let (sender, receiver) = async_channel::unbounded::<(String, String, Option<Movie>)>();
-                    
-                    // Get API key, posters_dir, year_cutoff, and existing paths before spawning thread (Rc can't be sent)
-                    let api_key = db_clone3.borrow().tmdb_api_key.clone();
-                    let posters_dir = db_clone3.borrow().posters_dir.clone();
-                    let year_cutoff = load_config().map(|c| c.year_cutoff).unwrap_or(1966);
-                    let existing_paths: std::collections::HashSet<String> = db_clone3.borrow()
-                        .movies
-                        .values()
-                        .map(|m| m.file_path.clone())
-                        .collect();
+                    // Extract what the scan needs before spawning its thread (Rc can't be sent)
+                    let config = load_config().unwrap_or_default();
+                    let scan = spawn_scan_worker(ScanJob::from_config(&config, &db_clone3.borrow(), vec![path_str.clone()], Vec::new()));
                     
-                    // Spawn background thread with async runtime
-                    std::thread::spawn(move || {
-                        let runtime = tokio::runtime::Builder::new_current_thread()
-                            .enable_all()
-                            .build()
-                            .unwrap();
-                        
-                        runtime.block_on(async {
-                            // Collect all video files recursively
-                            let mut files_to_process = Vec::new();
-                            let video_extensions = vec!["mp4", "mkv", "avi", "mov", "wmv", "flv", "webm", "m4v"];
-                            
-                            let _ = sender.send_blocking(("status".to_string(), format!("Scanning: {} (including subdirectories)...", path_str), None));
-                            
-                            let path = Path::new(&path_str);
-                            scan_directory_recursive(path, &video_extensions, &mut files_to_process);
-                            
-                            // Filter out files that already exist in database (using pre-extracted paths)
-                            
-                            let new_files: Vec<_> = files_to_process.into_iter()
-                                .filter(|(_, file_path)| !existing_paths.contains(file_path))
-                                .collect();
-                            
-                            if new_files.is_empty() {
-                                let _ = sender.send_blocking(("status".to_string(), "No new movies found - all files already in database".to_string(), None));
-                                let _ = sender.send_blocking(("complete".to_string(), String::new(), None));
-                                return;
-                            }
-                            
-                            let _ = sender.send_blocking(("status".to_string(), format!("Found {} new video files (skipped {} existing), fetching metadata in parallel...", new_files.len(), existing_paths.len()), None));
-                            
-                            // Process files in parallel batches of 10
-                            let client = reqwest::Client::new();
-                            let batch_size = 10;
-                            
-                            for batch in new_files.chunks(batch_size) {
-                                let futures: Vec<_> = batch.iter()
-                                    .map(|(clean_title, file_path_str)| {
-                                        let api_key = api_key.clone();
-                                        let title = clean_title.clone();
-                                        let file_path = file_path_str.clone();
-                                        let client = client.clone();
-                                        let sender = sender.clone();
-                                        let posters_dir = posters_dir.clone();
-                                        
-                                        async move {
-                                            let _ = sender.send_blocking(("status".to_string(), format!("Fetching: {}", title), None));
-                                            
-                                            match fetch_movie_metadata_async(&client, &api_key, &title, file_path.clone(), posters_dir, year_cutoff).await {
-                                                Some(movie) => {
-                                                    let _ = sender.send_blocking(("add".to_string(), format!("✓ Found: {}", title), Some(movie)));
-                                                }
-                                                None => {
-                                                    let movie = Movie {
-                                                        id: 0,
-                                                        title: title.clone(),
-                                                        year: 0,
-                                                        director: String::from("Unknown"),
-                                                        genre: vec![String::from("Uncategorized")],
-                                                        rating: 0.0,
-                                                        runtime: 0,
-                                                        description: String::from("Metadata not found"),
-                                                        cast: vec![],
-                                                        cast_details: vec![],
-                                                        file_path,
-                                                        poster_url: String::new(),
-                                                        tmdb_id: 0,
-                                                        imdb_id: String::new(),
-                                                        poster_path: String::new(),
-                                                        watch_log: Vec::new(),
-                                                    };
-                                                    let _ = sender.send_blocking(("add".to_string(), format!("⚠ Added without metadata: {}", title), Some(movie)));
-                                                }
-                                            }
-                                        }
-                                    })
-                                    .collect();
-                                
-                                futures::future::join_all(futures).await;
-                            }
-                            
-                            let _ = sender.send_blocking(("complete".to_string(), String::new(), None));
-                        });
+                    let button_busy = ButtonBusy::start(&scan_button_clone, "Scanning…");
+                    let set_busy = set_busy_clone2.clone();
+                    handle_scan_messages(scan, vec![path_str], db_clone3, status_bar_clone3, refresh_view_clone2.clone(), move || {
+                        drop(button_busy);
+                        set_busy(false);
                     });
-                    
-                    // Handle messages on main thread using spawn_future_local
-                    glib::spawn_future_local(async move {
-                        while let Ok((msg_type, status, movie_opt)) = receiver.recv().await {
-                            match msg_type.as_str() {
-                                "status" => {
-                                    status_bar_clone3.set_text(&status);
-                                }
-                                "add" => {
-                                    if let Some(movie) = movie_opt {
-                                        // Check if movie already exists
-                                        let exists = db_clone3.borrow().movies.values()
-                                            .any(|m| m.file_path == movie.file_path);
-                                        
-                                        if !exists {
-                                            db_clone3.borrow_mut().add_movie(movie);
-                                        }
-                                    }
-                                    status_bar_clone3.set_text(&status);
-                                }
-                                "complete" => {
-                                    while let Some(child) = list_box_clone3.first_child() {
-                                        list_box_clone3.remove(&child);
-                                    }
-                                    let movies = db_clone3.borrow().list_all();
-                                    for movie in &movies {
-                                        let row = create_movie_row(movie, &poster_cache_clone2);
-                                        list_box_clone3.append(&row);
-                                    }
-                                    status_bar_clone3.set_text("Scan complete!");
-                                    break;
-                                }
-                                _ => {}
-                            }
+                }
+            }
+        });
+    });
+
+    // Batch metadata refresh for the given movies, shared by Refresh Metadata, Refresh All
+    // and Refresh Shown. The list is rebuilt once at the end; cancelling keeps the movies
+    // refreshed so far.
+    let db_clone = db.clone();
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let status_bar_clone = status_bar.clone();
+    let poster_cache_clone = poster_cache.clone();
+    let is_grid_view_clone = is_grid_view.clone();
+    let current_filters_clone = current_filters.clone();
+    let busy_clone = busy.clone();
+    let set_busy_clone = set_busy.clone();
+    let refresh_movies: Rc<dyn Fn(Vec<u32>)> = Rc::new(move |ids| {
+        if busy_clone.get() {
+            status_bar_clone.set_text(BUSY_MESSAGE);
+            return;
+        }
+        set_busy_clone(true);
+        let config = load_config().unwrap_or_default();
+        let job = {
+            let db = db_clone.borrow();
+            RefreshJob {
+                targets: db.refresh_targets(&ids),
+                api_key: db.tmdb_api_key.clone(),
+                posters_dir: db.posters_dir.clone(),
+                year_cutoff: config.year_cutoff,
+                min_vote_count: config.min_vote_count,
+                max_concurrent_requests: config.max_concurrent_requests,
+            }
+        };
+        status_bar_clone.set_text(&format!("Refreshing metadata for {} movies...", job.targets.len()));
+        let refresh = spawn_refresh_worker(job);
+        
+        let db_clone2 = db_clone.clone();
+        let list_box_clone2 = list_box_clone.clone();
+        let grid_flow_clone2 = grid_flow_clone.clone();
+        let status_bar_clone2 = status_bar_clone.clone();
+        let poster_cache_clone2 = poster_cache_clone.clone();
+        let is_grid_view_clone2 = is_grid_view_clone.clone();
+        let current_filters_clone2 = current_filters_clone.clone();
+        let set_busy_clone2 = set_busy_clone.clone();
+        glib::spawn_future_local(async move {
+            let _active = refresh.active;
+            while let Ok((msg_type, status, movie_opt)) = refresh.receiver.recv().await {
+                match msg_type.as_str() {
+                    "status" => status_bar_clone2.set_text(&status),
+                    "progress" => ActiveScan::show_progress(&status),
+                    "refresh" => {
+                        if let Some(movie) = movie_opt {
+                            db_clone2.borrow_mut().replace_with_refreshed(movie.id, movie);
                         }
-                    });
+                        status_bar_clone2.set_text(&status);
+                    }
+                    "complete" => {
+                        let is_grid = *is_grid_view_clone2.borrow();
+                        refresh_movie_list(&list_box_clone2, &grid_flow_clone2, is_grid, &db_clone2, &current_filters_clone2(), &poster_cache_clone2);
+                        break;
+                    }
+                    _ => {}
                 }
             }
+            set_busy_clone2(false);
+        });
+    });
+
+    // Refresh metadata - the selected movie or series, through the batch refresh so its
+    // stored TMDB id is used (entries that never matched are searched for again)
+    let window_clone = window.clone();
+    let db_clone = db.clone();
+    let selected_movie_id_clone = selected_movie_id.clone();
+    let refresh_movies_clone = refresh_movies.clone();
+    refresh_button.connect_clicked(move |_| {
+        let movie_id = *selected_movie_id_clone.borrow();
+        let Some(title) = db_clone.borrow().movies.get(&movie_id).map(|m| m.title.clone()) else {
+            return;
+        };
+        
+        let confirmed = confirm(
+            &window_clone,
+            "Refresh Metadata",
+            &format!(
+                "Replace the TMDB details of \"{}\" (title, year, director, genres, rating, runtime, description, cast and poster) with fresh data?\n\nFields you changed in Edit Metadata, your rating, favorite, watched status and watch history are kept.",
+                title
+            ),
+            "Refresh",
+        );
+        
+        let refresh_movies = refresh_movies_clone.clone();
+        glib::spawn_future_local(async move {
+            if confirmed.await {
+                refresh_movies(vec![movie_id]);
+            }
+        });
+    });
+
+    // Refresh All - refresh metadata for every movie and series in the library
+    let window_clone = window.clone();
+    let db_clone = db.clone();
+    let refresh_movies_clone = refresh_movies.clone();
+    refresh_all_action.connect_activate(move |_, _| {
+        let ids: Vec<u32> = db_clone.borrow().movies.keys().copied().collect();
+        let confirmed = confirm(
+            &window_clone,
+            "Refresh All Movies",
+            &format!("This will refresh metadata and download HD posters for all {} movies in your database.\n\nThis may take a while depending on your collection size. Continue?", ids.len()),
+            "Refresh All",
+        );
+        
+        let refresh_movies = refresh_movies_clone.clone();
+        glib::spawn_future_local(async move {
+            if confirmed.await {
+                refresh_movies(ids);
+            }
         });
     });
 
-    // Refresh metadata
+    // Refresh Shown - the same for just the movies the current search and filters list
+    let window_clone = window.clone();
     let db_clone = db.clone();
-    let list_box_clone = list_box.clone();
-    let selected_movie_id_clone = selected_movie_id.clone();
     let status_bar_clone = status_bar.clone();
-    let poster_cache_clone = poster_cache.clone();
-    let posters_dir_clone = db.borrow().posters_dir.clone();
-    refresh_button.connect_clicked(move |_| {
-        let movie_id = *selected_movie_id_clone.borrow();
-        if movie_id > 0 {
-            let db_clone2 = db_clone.clone();
-            let list_box_clone2 = list_box_clone.clone();
-            let status_bar_clone2 = status_bar_clone.clone();
-            let posters_dir = posters_dir_clone.clone();
-            let poster_cache_clone2 = poster_cache_clone.clone();
-            
-            // Get the data we need before spawning thread
-            let (title, file_path, api_key) = {
-                let db = db_clone2.borrow();
-                if let Some(movie) = db.movies.get(&movie_id) {
-                    (movie.title.clone(), movie.file_path.clone(), db.tmdb_api_key.clone())
-                } else {
-                    return;
-                }
-            };
-            
-            let (sender, receiver) = async_channel::unbounded::<Option<(u32, Movie)>>();
-            
-            // Update status immediately
-            status_bar_clone2.set_text(&format!("Refreshing: {}", title));
-            
-            std::thread::spawn(move || {
-                let client = reqwest::blocking::Client::new();
-                let search_url = format!(
-                    "https://api.themoviedb.org/3/search/movie?api_key={}&query={}",
-                    api_key,
-                    urlencoding::encode(&title)
-                );
-                
-                if let Ok(response) = client.get(&search_url).send() {
-                    if let Ok(search_response) = response.json::<TMDBSearchResponse>() {
-                        if !search_response.results.is_empty() {
-                            let tmdb_movie_id = search_response.results[0].id;
-                            let details_url = format!(
-                                "https://api.themoviedb.org/3/movie/{}?api_key={}&append_to_response=credits",
-                                tmdb_movie_id, api_key
-                            );
-                            
-                            if let Ok(details_response) = client.get(&details_url).send() {
-                                if let Ok(details) = details_response.json::<TMDBMovieDetails>() {
-                                    let year: u16 = details.release_date
-                                        .split('-')
-                                        .next()
-                                        .and_then(|y| y.parse().ok())
-                                        .unwrap_or(0);
-                                    
-                                    let director = details.credits.crew
-                                        .iter()
-                                        .find(|c| c.job == "Director")
-                                        .map(|c| c.name.clone())
-                                        .unwrap_or_else(|| "Unknown".to_string());
-                                    
-                                    let cast: Vec<String> = details.credits.cast
-                                        .iter()
-                                        .take(5)
-                                        .map(|c| c.name.clone())
-                                        .collect();
-                                    
-                                    let cast_details: Vec<CastMember> = details.credits.cast
-                                        .iter()
-                                        .take(5)
-                                        .map(|c| CastMember {
-                                            name: c.name.clone(),
-                                            character: c.character.clone(),
-                                            profile_path: c.profile_path.as_ref()
-                                                .map(|p| format!("https://image.tmdb.org/t/p/w185{}", p))
-                                                .unwrap_or_default(),
-                                        })
-                                        .collect();
-                                    
-                                    let genres: Vec<String> = details.genres
-                                        .iter()
-                                        .map(|g| g.name.clone())
-                                        .collect();
-                                    
-                                    let poster_url = details.poster_path
-                                        .map(|p| format!("https://image.tmdb.org/t/p/original{}", p))
-                                        .unwrap_or_default();
-                                    
-                                    let poster_path = if !poster_url.is_empty() {
-                                        download_poster(&poster_url, tmdb_movie_id, &posters_dir).unwrap_or_default()
-                                    } else {
-                                        String::new()
-                                    };
-                                    
-                                    // Fetch IMDb ID
-                                    let external_ids_url = format!(
-                                        "https://api.themoviedb.org/3/movie/{}/external_ids?api_key={}",
-                                        tmdb_movie_id, api_key
-                                    );
-                                    
-                                    let imdb_id = if let Ok(response) = reqwest::blocking::get(&external_ids_url) {
-                                        if let Ok(external_ids) = response.json::<TMDBExternalIds>() {
-                                            external_ids.imdb_id.unwrap_or_default()
-                                        } else {
-                                            String::new()
-                                        }
-                                    } else {
-                                        String::new()
-                                    };
-                                    
-                                    let movie = Movie {
-                                        id: 0,
-                                        title: details.title,
-                                        year,
-                                        director,
-                                        genre: if genres.is_empty() { vec!["Unknown".to_string()] } else { genres },
-                                        rating: details.vote_average,
-                                        runtime: details.runtime.unwrap_or(0),
-                                        description: details.overview,
-                                        cast,
-                                        cast_details,
-                                        file_path: file_path.clone(),
-                                        poster_url,
-                                        tmdb_id: tmdb_movie_id,
-                                        imdb_id,
-                                        poster_path,
-        watch_log: Vec::new(),
-                                    };
-                                    
-                                    let _ = sender.send_blocking(Some((movie_id, movie)));
-                                    return;
-                                }
-                            }
-                        }
-                    }
-                }
-                
-                let _ = sender.send_blocking(None);
-            });
-            
-            glib::spawn_future_local(async move {
-                if let Ok(movie_opt) = receiver.recv().await {
-                    if let Some((old_id, new_movie)) = movie_opt {
-                        db_clone2.borrow_mut().delete_movie(old_id);
-                        db_clone2.borrow_mut().add_movie(new_movie);
-                        
-                        while let Some(child) = list_box_clone2.first_child() {
-                            list_box_clone2.remove(&child);
-                        }
-                        let movies = db_clone2.borrow().list_all();
-                        for movie in &movies {
-                            let row = create_movie_row(movie, &poster_cache_clone2);
-                            list_box_clone2.append(&row);
-                        }
-                        status_bar_clone2.set_text("Metadata refreshed!");
-                    } else {
-                        status_bar_clone2.set_text("Failed to refresh metadata");
-                    }
-                }
-            });
+    let current_filters_clone = current_filters.clone();
+    let refresh_movies_clone = refresh_movies.clone();
+    refresh_shown_action.connect_activate(move |_, _| {
+        let shown: Vec<u32> = db_clone.borrow()
+            .filtered_movies(&current_filters_clone())
+            .iter()
+            .map(|m| m.id)
+            .collect();
+        if shown.is_empty() {
+            status_bar_clone.set_text("No movies shown to refresh");
+            return;
         }
+        
+        let confirmed = confirm(
+            &window_clone,
+            "Refresh Shown Movies",
+            &format!("Refresh metadata and posters for the {} movies in the current list?", shown.len()),
+            "Refresh",
+        );
+        
+        let refresh_movies = refresh_movies_clone.clone();
+        glib::spawn_future_local(async move {
+            if confirmed.await {
+                refresh_movies(shown);
+            }
+        });
     });
 
-    // Refresh All button - refresh metadata for ALL movies
+    // Bulk actions on the selected rows
     let window_clone = window.clone();
     let db_clone = db.clone();
     let list_box_clone = list_box.clone();
     let grid_flow_clone = grid_flow.clone();
     let status_bar_clone = status_bar.clone();
     let poster_cache_clone = poster_cache.clone();
-    let posters_dir_clone = db.borrow().posters_dir.clone();
     let is_grid_view_clone = is_grid_view.clone();
-    refresh_all_button.connect_clicked(move |_| {
-        // Confirm with user
-        let dialog = gtk::AlertDialog::builder()
-            .message("Refresh All Movies")
-            .detail("This will refresh metadata and download HD posters for ALL movies in your database.\n\nThis may take a while depending on your collection size. Continue?")
-            .buttons(vec!["Cancel", "Refresh All"])
-            .cancel_button(0)
-            .default_button(1)
-            .build();
+    let current_filters_clone = current_filters.clone();
+    bulk_delete_button.connect_clicked(move |_| {
+        let ids = selected_row_ids(&list_box_clone);
+        if ids.is_empty() {
+            return;
+        }
+        let confirmed = confirm(
+            &window_clone,
+            "Delete Selected Movies",
+            &format!("Delete the {} selected movies from the database?\n\nThe video files stay on disk. This can't be undone with Undo Delete.", ids.len()),
+            "Delete",
+        );
         
         let db_clone2 = db_clone.clone();
         let list_box_clone2 = list_box_clone.clone();
@@ -3071,237 +9282,54 @@ fn build_ui(app: &Application) {
         let status_bar_clone2 = status_bar_clone.clone();
         let poster_cache_clone2 = poster_cache_clone.clone();
         let is_grid_view_clone2 = is_grid_view_clone.clone();
-        let posters_dir = posters_dir_clone.clone();
-        
-        dialog.choose(Some(&window_clone), None::<&gtk::gio::Cancellable>, move |response| {
-            if let Ok(1) = response {
-                status_bar_clone2.set_text("Starting refresh of all movies...");
-                
-                // Get all movies
-                let movies: Vec<(u32, String, String)> = db_clone2.borrow()
-                    .movies
-                    .values()
-                    .map(|m| (m.id, m.title.clone(), m.file_path.clone()))
-                    .collect();
-                
-                let total_count = movies.len();
-                let api_key = db_clone2.borrow().tmdb_api_key.clone();
-                let year_cutoff = load_config().map(|c| c.year_cutoff).unwrap_or(1966);
-                
-                let (sender, receiver) = async_channel::unbounded::<(String, Option<(u32, Movie)>)>();
-                
-                // Spawn background thread to refresh all movies
-                std::thread::spawn(move || {
-                    let client = reqwest::blocking::Client::new();
-                    let mut success_count = 0;
-                    let mut fail_count = 0;
-                    
-                    // Create cast_photos directory at the start
-                    let cast_photos_dir = std::path::Path::new(&posters_dir).parent().unwrap().join("cast_photos");
-                    std::fs::create_dir_all(&cast_photos_dir).ok();
-                    eprintln!("[Refresh All] Will download cast photos to: {:?}", cast_photos_dir);
-                    
-                    for (i, (movie_id, title, file_path)) in movies.iter().enumerate() {
-                        let progress = format!("Refreshing {}/{}: {}", i + 1, total_count, title);
-                        let _ = sender.send_blocking((progress, None));
-                        
-                        // Search TMDB
-                        let search_url = format!(
-                            "https://api.themoviedb.org/3/search/movie?api_key={}&query={}",
-                            api_key,
-                            urlencoding::encode(title)
-                        );
-                        
-                        if let Ok(response) = client.get(&search_url).send() {
-                            if let Ok(search_response) = response.json::<TMDBSearchResponse>() {
-                                if !search_response.results.is_empty() {
-                                    // Prioritize movies before year_cutoff (same logic as fetch_movie_metadata_async)
-                                    let tmdb_movie_id = {
-                                        let before_cutoff = search_response.results.iter().find(|movie| {
-                                            if let Some(ref date) = movie.release_date {
-                                                if let Some(year_str) = date.split('-').next() {
-                                                    if let Ok(year) = year_str.parse::<i32>() {
-                                                        return year <= year_cutoff;
-                                                    }
-                                                }
-                                            }
-                                            false
-                                        });
-                                        
-                                        if let Some(movie) = before_cutoff {
-                                            movie.id
-                                        } else {
-                                            search_response.results[0].id
-                                        }
-                                    };
-                                    
-                                    let details_url = format!(
-                                        "https://api.themoviedb.org/3/movie/{}?api_key={}&append_to_response=credits",
-                                        tmdb_movie_id, api_key
-                                    );
-                                    
-                                    if let Ok(details_response) = client.get(&details_url).send() {
-                                        if let Ok(details) = details_response.json::<TMDBMovieDetails>() {
-                                            // Build movie (same as refresh single)
-                                            let year: u16 = details.release_date
-                                                .split('-')
-                                                .next()
-                                                .and_then(|y| y.parse().ok())
-                                                .unwrap_or(0);
-                                            
-                                            let director = details.credits.crew
-                                                .iter()
-                                                .find(|c| c.job == "Director")
-                                                .map(|c| c.name.clone())
-                                                .unwrap_or_else(|| "Unknown".to_string());
-                                            
-                                            let cast: Vec<String> = details.credits.cast
-                                                .iter()
-                                                .take(5)
-                                                .map(|c| c.name.clone())
-                                                .collect();
-                                            
-                                            let cast_details: Vec<CastMember> = details.credits.cast
-                                                .iter()
-                                                .take(5)
-                                                .map(|c| CastMember {
-                                                    name: c.name.clone(),
-                                                    character: c.character.clone(),
-                                                    profile_path: c.profile_path.as_ref()
-                                                        .map(|p| format!("https://image.tmdb.org/t/p/w185{}", p))
-                                                        .unwrap_or_default(),
-                                                })
-                                                .collect();
-                                            
-                                            
-                                            // Download cast photos
-                                            for cast_member in &cast_details {
-                                                if !cast_member.profile_path.is_empty() {
-                                                    let filename = cast_member.profile_path
-                                                        .trim_start_matches("https://image.tmdb.org/t/p/w185")
-                                                        .trim_start_matches('/')
-                                                        .replace('/', "_");
-                                                    let photo_path = cast_photos_dir.join(&filename);
-                                                    
-                                                    if !photo_path.exists() {
-                                                        if let Ok(response) = client.get(&cast_member.profile_path).send() {
-                                                            if let Ok(bytes) = response.bytes() {
-                                                                let _ = std::fs::write(&photo_path, bytes);
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                            let genres: Vec<String> = details.genres
-                                                .iter()
-                                                .map(|g| g.name.clone())
-                                                .collect();
-                                            
-                                            let poster_url = details.poster_path
-                                                .map(|p| format!("https://image.tmdb.org/t/p/original{}", p))
-                                                .unwrap_or_default();
-                                            
-                                            let poster_path = if !poster_url.is_empty() {
-                                                download_poster(&poster_url, tmdb_movie_id, &posters_dir).unwrap_or_default()
-                                            } else {
-                                                String::new()
-                                            };
-                                            
-                                            // Fetch IMDb ID
-                                            let external_ids_url = format!(
-                                                "https://api.themoviedb.org/3/movie/{}/external_ids?api_key={}",
-                                                tmdb_movie_id, api_key
-                                            );
-                                            
-                                            let imdb_id = if let Ok(response) = reqwest::blocking::get(&external_ids_url) {
-                                                if let Ok(external_ids) = response.json::<TMDBExternalIds>() {
-                                                    external_ids.imdb_id.unwrap_or_default()
-                                                } else {
-                                                    String::new()
-                                                }
-                                            } else {
-                                                String::new()
-                                            };
-                                            
-                                            let movie = Movie {
-                                                id: 0,
-                                                title: details.title,
-                                                year,
-                                                director,
-                                                genre: if genres.is_empty() { vec!["Unknown".to_string()] } else { genres },
-                                                rating: details.vote_average,
-                                                runtime: details.runtime.unwrap_or(0),
-                                                description: details.overview,
-                                                cast,
-                                                cast_details,
-                                                file_path: file_path.clone(),
-                                                poster_url,
-                                                tmdb_id: tmdb_movie_id,
-                                                imdb_id,
-                                                poster_path,
-        watch_log: Vec::new(),
-                                            };
-                                            
-                                            let _ = sender.send_blocking((String::new(), Some((*movie_id, movie))));
-                                            success_count += 1;
-                                            continue;
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        fail_count += 1;
-                    }
-                    
-                    let summary = format!("Refresh complete! {} succeeded, {} failed", success_count, fail_count);
-                    let _ = sender.send_blocking((summary, None));
-                });
-                
-                // Handle updates on main thread
-                glib::spawn_future_local(async move {
-                    let mut updated_ids = Vec::new();
-                    
-                    while let Ok((status, movie_opt)) = receiver.recv().await {
-                        if !status.is_empty() {
-                            status_bar_clone2.set_text(&status);
-                        }
-                        
-                        if let Some((old_id, new_movie)) = movie_opt {
-                            db_clone2.borrow_mut().delete_movie(old_id);
-                            db_clone2.borrow_mut().add_movie(new_movie);
-                            updated_ids.push(old_id);
-                        } else if status.contains("complete") {
-                            // Refresh UI
-                            let is_grid = *is_grid_view_clone2.borrow();
-                            
-                            if is_grid {
-                                while let Some(child) = grid_flow_clone2.first_child() {
-                                    grid_flow_clone2.remove(&child);
-                                }
-                                let movies = db_clone2.borrow().list_all();
-                                for movie in &movies {
-                                    let item = create_movie_grid_item(&movie, &poster_cache_clone2);
-                                    grid_flow_clone2.append(&item);
-                                }
-                            } else {
-                                while let Some(child) = list_box_clone2.first_child() {
-                                    list_box_clone2.remove(&child);
-                                }
-                                let movies = db_clone2.borrow().list_all();
-                                for movie in &movies {
-                                    let row = create_movie_row(&movie, &poster_cache_clone2);
-                                    list_box_clone2.append(&row);
-                                }
-                            }
-                            
-                            break;
-                        }
-                    }
-                });
+        let current_filters_clone2 = current_filters_clone.clone();
+        glib::spawn_future_local(async move {
+            if confirmed.await {
+                let removed = db_clone2.borrow_mut().delete_many(&ids);
+                let is_grid = *is_grid_view_clone2.borrow();
+                refresh_movie_list(&list_box_clone2, &grid_flow_clone2, is_grid, &db_clone2, &current_filters_clone2(), &poster_cache_clone2);
+                status_bar_clone2.set_text(&format!("Deleted {} movies", removed));
             }
         });
     });
 
+    let db_clone = db.clone();
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let status_bar_clone = status_bar.clone();
+    let poster_cache_clone = poster_cache.clone();
+    let is_grid_view_clone = is_grid_view.clone();
+    let current_filters_clone = current_filters.clone();
+    let bulk_tag_entry_clone = bulk_tag_entry.clone();
+    let bulk_tag_popover_clone = bulk_tag_popover.clone();
+    let add_bulk_tags: Rc<dyn Fn()> = Rc::new(move || {
+        let tags = parse_tags(&bulk_tag_entry_clone.text());
+        let ids = selected_row_ids(&list_box_clone);
+        if tags.is_empty() || ids.is_empty() {
+            return;
+        }
+        let tagged = db_clone.borrow_mut().add_tags(&ids, &tags);
+        bulk_tag_entry_clone.set_text("");
+        bulk_tag_popover_clone.popdown();
+        let is_grid = *is_grid_view_clone.borrow();
+        refresh_movie_list(&list_box_clone, &grid_flow_clone, is_grid, &db_clone, &current_filters_clone(), &poster_cache_clone);
+        status_bar_clone.set_text(&format!("Tagged {} movies with {}", tagged, tags.join(", ")));
+    });
+    let add_bulk_tags_clone = add_bulk_tags.clone();
+    bulk_tag_add_button.connect_clicked(move |_| add_bulk_tags_clone());
+    bulk_tag_entry.connect_activate(move |_| add_bulk_tags());
+
+    let list_box_clone = list_box.clone();
+    bulk_refresh_button.connect_clicked(move |_| {
+        let ids = selected_row_ids(&list_box_clone);
+        if !ids.is_empty() {
+            refresh_movies(ids);
+        }
+    });
+
+    let list_box_clone = list_box.clone();
+    bulk_clear_button.connect_clicked(move |_| list_box_clone.unselect_all());
+
     // Edit Metadata button
     let window_clone = window.clone();
     let db_clone = db.clone();
@@ -3350,9 +9378,8 @@ fn build_ui(app: &Application) {
             
             // Year
             grid.attach(&Label::new(Some("Year:")), 0, 1, 1, 1);
-            let year_entry = Entry::new();
-            year_entry.set_text(&movie.year.to_string());
-            grid.attach(&year_entry, 1, 1, 1, 1);
+            let (year_box, year_entry, year_error) = validated_entry(&movie.year.to_string());
+            grid.attach(&year_box, 1, 1, 1, 1);
             
             // Director
             grid.attach(&Label::new(Some("Director:")), 0, 2, 1, 1);
@@ -3361,27 +9388,88 @@ fn build_ui(app: &Application) {
             director_entry.set_hexpand(true);
             grid.attach(&director_entry, 1, 2, 1, 1);
             
-            // Genre
+            // Genre - checkboxes for known genres so spellings stay consistent
             grid.attach(&Label::new(Some("Genre:")), 0, 3, 1, 1);
-            let genre_entry = Entry::new();
-            genre_entry.set_text(&movie.genre.join(", "));
-            genre_entry.set_hexpand(true);
-            grid.attach(&genre_entry, 1, 3, 1, 1);
+            let genre_box = Box::new(Orientation::Vertical, 6);
+            let genre_flow = gtk::FlowBox::new();
+            genre_flow.set_selection_mode(gtk::SelectionMode::None);
+            genre_flow.set_max_children_per_line(4);
+            genre_flow.set_hexpand(true);
+            let genre_checks: Rc<RefCell<Vec<gtk::CheckButton>>> = Rc::new(RefCell::new(Vec::new()));
+            let library_genres = db_clone.borrow().all_genres();
+            let mut choices = genre_choices(&library_genres);
+            for genre in &movie.genre {
+                if genre != "Unknown" && !choices.iter().any(|g| g.eq_ignore_ascii_case(genre)) {
+                    choices.push(genre.clone());
+                }
+            }
+            for choice in &choices {
+                let check = gtk::CheckButton::with_label(choice);
+                check.set_active(movie.genre.iter().any(|g| g.eq_ignore_ascii_case(choice)));
+                genre_flow.insert(&check, -1);
+                genre_checks.borrow_mut().push(check);
+            }
+            genre_box.append(&genre_flow);
+            
+            // Escape hatch for genres not in the list
+            let custom_genre_box = Box::new(Orientation::Horizontal, 6);
+            let custom_genre_entry = Entry::new();
+            custom_genre_entry.set_placeholder_text(Some("Add custom genre"));
+            custom_genre_entry.set_hexpand(true);
+            let add_genre_button = Button::with_label("Add");
+            custom_genre_box.append(&custom_genre_entry);
+            custom_genre_box.append(&add_genre_button);
+            genre_box.append(&custom_genre_box);
+            grid.attach(&genre_box, 1, 3, 1, 1);
+            
+            let add_custom_genre = {
+                let genre_flow = genre_flow.clone();
+                let genre_checks = genre_checks.clone();
+                let custom_genre_entry = custom_genre_entry.clone();
+                move || {
+                    let name = custom_genre_entry.text().trim().to_string();
+                    if name.is_empty() {
+                        return;
+                    }
+                    let existing = genre_checks.borrow().iter()
+                        .find(|c| c.label().map(|l| l.eq_ignore_ascii_case(&name)).unwrap_or(false))
+                        .cloned();
+                    if let Some(check) = existing {
+                        check.set_active(true);
+                    } else {
+                        let check = gtk::CheckButton::with_label(&name);
+                        check.set_active(true);
+                        genre_flow.insert(&check, -1);
+                        genre_checks.borrow_mut().push(check);
+                    }
+                    custom_genre_entry.set_text("");
+                }
+            };
+            let add_custom_genre = Rc::new(add_custom_genre);
+            let add_custom_genre_clone = add_custom_genre.clone();
+            add_genre_button.connect_clicked(move |_| add_custom_genre_clone());
+            custom_genre_entry.connect_activate(move |_| add_custom_genre());
             
             // Rating
-            grid.attach(&Label::new(Some("Rating (0-10):")), 0, 4, 1, 1);
-            let rating_entry = Entry::new();
-            rating_entry.set_text(&format!("{:.1}", movie.rating));
-            grid.attach(&rating_entry, 1, 4, 1, 1);
+            grid.attach(&Label::new(Some("TMDB Rating (0-10):")), 0, 4, 1, 1);
+            let (rating_box, rating_entry, rating_error) = validated_entry(&format!("{:.1}", movie.rating));
+            grid.attach(&rating_box, 1, 4, 1, 1);
+            
+            // Personal rating
+            grid.attach(&Label::new(Some("My Rating (0-10):")), 0, 5, 1, 1);
+            let (my_rating_box, my_rating_entry, my_rating_error) = validated_entry(
+                &movie.my_rating.map(|r| format!("{:.1}", r)).unwrap_or_default()
+            );
+            my_rating_entry.set_placeholder_text(Some("Optional - leave blank if unrated"));
+            grid.attach(&my_rating_box, 1, 5, 1, 1);
             
             // Runtime
-            grid.attach(&Label::new(Some("Runtime (min):")), 0, 5, 1, 1);
-            let runtime_entry = Entry::new();
-            runtime_entry.set_text(&movie.runtime.to_string());
-            grid.attach(&runtime_entry, 1, 5, 1, 1);
+            grid.attach(&Label::new(Some("Runtime (min):")), 0, 6, 1, 1);
+            let (runtime_box, runtime_entry, runtime_error) = validated_entry(&movie.runtime.to_string());
+            grid.attach(&runtime_box, 1, 6, 1, 1);
             
             // Description
-            grid.attach(&Label::new(Some("Description:")), 0, 6, 1, 1);
+            grid.attach(&Label::new(Some("Description:")), 0, 7, 1, 1);
             let desc_text_view = gtk::TextView::new();
             desc_text_view.buffer().set_text(&movie.description);
             desc_text_view.set_wrap_mode(gtk::WrapMode::Word);
@@ -3389,14 +9477,40 @@ fn build_ui(app: &Application) {
             let desc_scroll = ScrolledWindow::new();
             desc_scroll.set_child(Some(&desc_text_view));
             desc_scroll.set_vexpand(true);
-            grid.attach(&desc_scroll, 1, 6, 1, 1);
+            grid.attach(&desc_scroll, 1, 7, 1, 1);
+            
+            // Cast - actor and character rows; movies saved before cast_details start from the names
+            grid.attach(&Label::new(Some("Cast:")), 0, 8, 1, 1);
+            let cast_box = Box::new(Orientation::Vertical, 6);
+            let cast_rows_box = Box::new(Orientation::Vertical, 6);
+            let cast_rows: Rc<RefCell<Vec<CastEditRow>>> = Rc::new(RefCell::new(Vec::new()));
+            let initial_cast: Vec<CastMember> = if movie.cast_details.is_empty() {
+                movie.cast.iter()
+                    .map(|name| CastMember { name: name.clone(), ..Default::default() })
+                    .collect()
+            } else {
+                movie.cast_details.clone()
+            };
+            for member in &initial_cast {
+                add_cast_edit_row(&cast_rows_box, &cast_rows, member);
+            }
+            let add_cast_button = Button::with_label("➕ Add Cast Member");
+            add_cast_button.set_halign(Align::Start);
+            let cast_rows_box_clone = cast_rows_box.clone();
+            let cast_rows_clone = cast_rows.clone();
+            add_cast_button.connect_clicked(move |_| {
+                add_cast_edit_row(&cast_rows_box_clone, &cast_rows_clone, &CastMember::default());
+            });
+            cast_box.append(&cast_rows_box);
+            cast_box.append(&add_cast_button);
+            grid.attach(&cast_box, 1, 8, 1, 1);
             
-            // Cast
-            grid.attach(&Label::new(Some("Cast (comma-separated):")), 0, 7, 1, 1);
-            let cast_entry = Entry::new();
-            cast_entry.set_text(&movie.cast.join(", "));
-            cast_entry.set_hexpand(true);
-            grid.attach(&cast_entry, 1, 7, 1, 1);
+            // Tags
+            grid.attach(&Label::new(Some("Tags:")), 0, 9, 1, 1);
+            let tags_entry = Entry::new();
+            tags_entry.set_text(&movie.tags.join(", "));
+            tags_entry.set_placeholder_text(Some("Comma-separated, e.g. rewatch, date-night, 4k"));
+            grid.attach(&tags_entry, 1, 9, 1, 1);
             
             scroll.set_child(Some(&grid));
             content.append(&scroll);
@@ -3423,95 +9537,75 @@ fn build_ui(app: &Application) {
             let status_bar_clone2 = status_bar_clone.clone();
             let poster_cache_clone2 = poster_cache_clone.clone();
             save_button.connect_clicked(move |_| {
-                // Parse and validate inputs
+                // Parse and validate inputs - nothing is saved until every field is valid
+                let year = parse_edit_year(&year_entry.text());
+                let rating = parse_edit_rating(&rating_entry.text());
+                let my_rating = parse_edit_my_rating(&my_rating_entry.text());
+                let runtime = parse_edit_runtime(&runtime_entry.text());
+                set_field_error(&year_entry, &year_error, year.as_ref().err().map(|e| e.as_str()));
+                set_field_error(&rating_entry, &rating_error, rating.as_ref().err().map(|e| e.as_str()));
+                set_field_error(&my_rating_entry, &my_rating_error, my_rating.as_ref().err().map(|e| e.as_str()));
+                set_field_error(&runtime_entry, &runtime_error, runtime.as_ref().err().map(|e| e.as_str()));
+                let (Ok(new_year), Ok(new_rating), Ok(new_my_rating), Ok(new_runtime)) = (year, rating, my_rating, runtime) else {
+                    status_bar_clone2.set_text("Fix the highlighted fields before saving");
+                    return;
+                };
+                
                 let new_title = title_entry.text().to_string();
-                let new_year: u16 = year_entry.text().parse().unwrap_or(movie.year);
                 let new_director = director_entry.text().to_string();
-                let new_genre: Vec<String> = genre_entry.text()
-                    .split(',')
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
+                let new_genre: Vec<String> = genre_checks.borrow().iter()
+                    .filter(|c| c.is_active())
+                    .filter_map(|c| c.label().map(|l| l.to_string()))
                     .collect();
-                let new_rating: f32 = rating_entry.text().parse().unwrap_or(movie.rating).clamp(0.0, 10.0);
-                let new_runtime: u16 = runtime_entry.text().parse().unwrap_or(movie.runtime);
-                
                 let buffer = desc_text_view.buffer();
                 let new_description = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false).to_string();
                 
-                let new_cast: Vec<String> = cast_entry.text()
-                    .split(',')
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect();
+                let new_cast_details = cast_from_edit_rows(&cast_rows.borrow());
+                let new_cast: Vec<String> = new_cast_details.iter().map(|member| member.name.clone()).collect();
                 
-                // Update movie
+                // Update movie, remembering which fields were changed so Refresh Metadata keeps them
                 let mut db = db_clone2.borrow_mut();
-                if let Some(existing_movie) = db.movies.get_mut(&movie_id) {
+                if let Some(existing_movie) = db.movie_mut(movie_id) {
+                    let new_genre = if new_genre.is_empty() { vec!["Unknown".to_string()] } else { new_genre };
+                    let changed = [
+                        ("title", existing_movie.title != new_title),
+                        ("year", existing_movie.year != new_year),
+                        ("director", existing_movie.director != new_director),
+                        ("genre", existing_movie.genre != new_genre),
+                        ("rating", format!("{:.1}", existing_movie.rating) != format!("{:.1}", new_rating)),
+                        ("runtime", existing_movie.runtime != new_runtime),
+                        ("description", existing_movie.description != new_description),
+                        ("cast", initial_cast != new_cast_details),
+                    ];
+                    for (field, _) in changed.iter().filter(|(_, changed)| *changed) {
+                        if !existing_movie.edited_fields.iter().any(|f| f == field) {
+                            existing_movie.edited_fields.push(field.to_string());
+                        }
+                    }
                     existing_movie.title = new_title;
                     existing_movie.year = new_year;
                     existing_movie.director = new_director;
-                    existing_movie.genre = if new_genre.is_empty() { vec!["Unknown".to_string()] } else { new_genre };
+                    existing_movie.genre = new_genre;
                     existing_movie.rating = new_rating;
+                    existing_movie.my_rating = new_my_rating;
                     existing_movie.runtime = new_runtime;
                     existing_movie.description = new_description;
                     existing_movie.cast = new_cast;
+                    existing_movie.cast_details = new_cast_details;
+                    existing_movie.tags = parse_tags(&tags_entry.text());
                 }
+                db.invalidate_result_cache();
                 drop(db);
                 
-                if let Err(e) = db_clone2.borrow_mut().save_to_file() {
-                    eprintln!("Warning: Failed to save edited metadata: {}", e);
+                let save_result = db_clone2.borrow_mut().save_to_file();
+                if let Err(e) = &save_result {
+                    log_warn!("Warning: Failed to save edited metadata: {}", e);
                 }
                 
                 // Refresh UI
                 let db = db_clone2.borrow();
                 if let Some(updated_movie) = db.movies.get(&movie_id) {
-                    let escaped_title = escape_markup(&updated_movie.title);
-                    let escaped_director = escape_markup(&updated_movie.director);
-                    let escaped_genre = escape_markup(&updated_movie.genre.join(", "));
-                    let escaped_description = escape_markup(&updated_movie.description);
-                    let escaped_file = escape_markup(&updated_movie.file_path);
-                    
-                    let cast_display = if !updated_movie.cast_details.is_empty() {
-                        let cast_list: Vec<String> = updated_movie.cast_details.iter()
-                            .map(|cm| {
-                                let name = escape_markup(&cm.name);
-                                let character = escape_markup(&cm.character);
-                                format!("{} ({})", name, character)
-                            })
-                            .collect();
-                        cast_list.join("\n    • ")
-                    } else if !updated_movie.cast.is_empty() {
-                        let cast_list: Vec<String> = updated_movie.cast.iter()
-                            .map(|name| escape_markup(name))
-                            .collect();
-                        cast_list.join("\n    • ")
-                    } else {
-                        String::from("Unknown")
-                    };
-                    
-                    let imdb_display = if !updated_movie.imdb_id.is_empty() {
-                        format!("{} (https://www.imdb.com/title/{})", updated_movie.imdb_id, updated_movie.imdb_id)
-                    } else {
-                        String::from("Not available")
-                    };
-                    
-                    let details = format!(
-                        "<b>{}</b> ({})\n\n\
-                        <b>Director:</b> {}\n\
-                        <b>Genre:</b> {}\n\
-                        <b>Rating:</b> ⭐ {:.1}/10\n\
-                        <b>Runtime:</b> {} minutes\n\n\
-                        <b>Starring:</b>\n    • {}\n\n\
-                        <b>Description:</b>\n{}\n\n\
-                        <b>File:</b> {}\n\
-                        <b>TMDB ID:</b> {}\n\
-                        <b>IMDb ID:</b> {}",
-                        escaped_title, updated_movie.year, escaped_director,
-                        escaped_genre, updated_movie.rating, updated_movie.runtime,
-                        cast_display, escaped_description, escaped_file,
-                        updated_movie.tmdb_id, imdb_display
-                    );
-                    details_label_clone2.set_markup(&details);
+                    details_label_clone2.set_markup(&details_pane_markup(updated_movie));
                 }
                 drop(db);
                 
@@ -3525,7 +9619,10 @@ fn build_ui(app: &Application) {
                     list_box_clone2.append(&row);
                 }
                 
-                status_bar_clone2.set_text("Movie metadata updated");
+                match save_result {
+                    Ok(()) => status_bar_clone2.set_text("Movie metadata updated"),
+                    Err(e) => status_bar_clone2.set_text(&format!("Metadata updated but not saved: {}", e)),
+                }
                 dialog_clone.close();
             });
             
@@ -3541,7 +9638,10 @@ fn build_ui(app: &Application) {
     let selected_movie_id_clone = selected_movie_id.clone();
     let poster_cache_clone_select = poster_cache.clone();
     let posters_dir_clone = db.borrow().posters_dir.clone();
-    select_version_button.connect_clicked(move |_| {
+    let busy_clone = busy.clone();
+    let set_busy_clone = set_busy.clone();
+    select_version_button.connect_clicked(move |button| {
+        let version_button = button.clone();
         let movie_id = *selected_movie_id_clone.borrow();
         if movie_id == 0 {
             status_bar_clone.set_text("Please select a movie first");
@@ -3553,7 +9653,7 @@ fn build_ui(app: &Application) {
             let movie_title = movie.title.clone();
             let movie_title_for_ui = movie_title.clone(); // Clone for UI updates
             let movie_year = movie.year; // Get the year for search
-            let file_path = movie.file_path.clone();
+            let file_path = movie.primary_file().to_string();
             let api_key = db.tmdb_api_key.clone();
             drop(db); // Release borrow
             
@@ -3622,7 +9722,7 @@ fn build_ui(app: &Application) {
             let status_bar_clone2 = status_bar_clone.clone();
             let selection_dialog_clone2 = selection_dialog.clone();
             
-            let (sender, receiver) = async_channel::unbounded::<Vec<(u32, String, String, f32)>>();
+            let (sender, receiver) = async_channel::unbounded::<Vec<TMDBSearchRow>>();
             
             // Check cache first - use title only for cache key to get more results
             let cache_key = movie_title.clone();
@@ -3640,10 +9740,11 @@ fn build_ui(app: &Application) {
                     // DON'T filter by year - we want ALL matches so user can find the right one
                     for page in 1..=5 {
                         let search_url = format!(
-                            "https://api.themoviedb.org/3/search/movie?api_key={}&query={}&page={}",
+                            "https://api.themoviedb.org/3/search/movie?api_key={}&query={}&page={}{}",
                             api_key,
                             urlencoding::encode(&movie_title),
-                            page
+                            page,
+                            tmdb_language_param()
                         );
                         
                         if let Ok(response) = reqwest::blocking::get(&search_url) {
@@ -3653,29 +9754,7 @@ fn build_ui(app: &Application) {
                                     break;
                                 }
                                 
-                                let page_results: Vec<(u32, String, String, f32)> = search_result.results.iter()
-                                    .map(|r| {
-                                        // Fetch basic details for each to get year
-                                        let details_url = format!(
-                                            "https://api.themoviedb.org/3/movie/{}?api_key={}",
-                                            r.id, api_key
-                                        );
-                                        
-                                        if let Ok(details_response) = reqwest::blocking::get(&details_url) {
-                                            if let Ok(details) = details_response.json::<TMDBMovieDetails>() {
-                                                let year = details.release_date
-                                                    .split('-')
-                                                    .next()
-                                                    .and_then(|y| y.parse().ok())
-                                                    .unwrap_or(0);
-                                                return (r.id, details.title, year.to_string(), details.vote_average);
-                                            }
-                                        }
-                                        (r.id, "Unknown".to_string(), "????".to_string(), 0.0)
-                                    })
-                                    .collect();
-                                
-                                all_results.extend(page_results);
+                                all_results.extend(search_result.results.iter().map(TMDBMovie::result_row));
                                 
                                 // If we got less than 20 results, we've reached the last page
                                 if search_result.results.len() < 20 {
@@ -3692,8 +9771,8 @@ fn build_ui(app: &Application) {
                     // Sort results: exact year matches first, then by popularity (rating)
                     if !all_results.is_empty() {
                         all_results.sort_by(|a, b| {
-                            let a_year: u16 = a.2.parse().unwrap_or(0);
-                            let b_year: u16 = b.2.parse().unwrap_or(0);
+                            let a_year: u16 = a.year.parse().unwrap_or(0);
+                            let b_year: u16 = b.year.parse().unwrap_or(0);
                             
                             // If we have a year to match, prioritize exact matches
                             if movie_year > 0 {
@@ -3708,7 +9787,7 @@ fn build_ui(app: &Application) {
                             }
                             
                             // Then sort by rating (higher is better)
-                            b.3.partial_cmp(&a.3).unwrap_or(std::cmp::Ordering::Equal)
+                            b.rating.partial_cmp(&a.rating).unwrap_or(std::cmp::Ordering::Equal)
                         });
                         
                         let _ = sender.send_blocking(all_results);
@@ -3721,6 +9800,8 @@ fn build_ui(app: &Application) {
             let cache_key_for_save = cache_key.clone();
             let poster_cache_clone_select2 = poster_cache_clone_select.clone();
             let posters_dir = posters_dir_clone.clone();
+            let busy_clone2 = busy_clone.clone();
+            let set_busy_clone2 = set_busy_clone.clone();
             glib::spawn_future_local(async move {
                 if let Ok(results) = receiver.recv().await {
                     // Cache the results if not from cache
@@ -3751,9 +9832,12 @@ fn build_ui(app: &Application) {
                     ));
                     
                     // Add result rows
-                    for (tmdb_id, title, year, rating) in &results {
+                    for TMDBSearchRow { tmdb_id, title, year, rating, overview } in &results {
                         let row = gtk::ListBoxRow::new();
                         row.set_widget_name(&tmdb_id.to_string());
+                        if !overview.is_empty() {
+                            row.set_tooltip_text(Some(overview));
+                        }
                         
                         let row_box = Box::new(Orientation::Vertical, 4);
                         row_box.set_margin_start(12);
@@ -3784,8 +9868,15 @@ fn build_ui(app: &Application) {
                         if let Some(selected_row) = list_box_results_clone.selected_row() {
                             let tmdb_id_str = selected_row.widget_name();
                             if let Ok(tmdb_id) = tmdb_id_str.as_str().parse::<u32>() {
+                                if busy_clone2.get() {
+                                    status_bar_clone2.set_text(BUSY_MESSAGE);
+                                    return;
+                                }
                                 status_bar_clone2.set_text(&format!("Fetching metadata for TMDB ID {}...", tmdb_id));
                                 selection_dialog_clone2.close();
+                                set_busy_clone2(true);
+                                let button_busy = ButtonBusy::start(&version_button, "Updating…");
+                                let set_busy = set_busy_clone2.clone();
                                 
                                 // Fetch full metadata for selected movie
                                 let db_clone3 = db_clone2.clone();
@@ -3800,106 +9891,21 @@ fn build_ui(app: &Application) {
                                 let (sender2, receiver2) = async_channel::unbounded::<Option<(u32, Movie)>>();
                                 
                                 std::thread::spawn(move || {
-                                    let details_url = format!(
-                                        "https://api.themoviedb.org/3/movie/{}?api_key={}&append_to_response=credits",
-                                        tmdb_id, api_key
-                                    );
-                                    
-                                    if let Ok(response) = reqwest::blocking::get(&details_url) {
-                                        if let Ok(details) = response.json::<TMDBMovieDetails>() {
-                                            // Build Movie struct (same as fetch_movie_metadata_async)
-                                            let year: u16 = details.release_date
-                                                .split('-')
-                                                .next()
-                                                .and_then(|y| y.parse().ok())
-                                                .unwrap_or(0);
-                                            
-                                            let director = details.credits.crew
-                                                .iter()
-                                                .find(|c| c.job == "Director")
-                                                .map(|c| c.name.clone())
-                                                .unwrap_or_else(|| "Unknown".to_string());
-                                            
-                                            let cast: Vec<String> = details.credits.cast
-                                                .iter()
-                                                .take(5)
-                                                .map(|c| c.name.clone())
-                                                .collect();
-                                            
-                                            let cast_details: Vec<CastMember> = details.credits.cast
-                                                .iter()
-                                                .take(5)
-                                                .map(|c| CastMember {
-                                                    name: c.name.clone(),
-                                                    character: c.character.clone(),
-                                                    profile_path: c.profile_path.as_ref()
-                                                        .map(|p| format!("https://image.tmdb.org/t/p/w185{}", p))
-                                                        .unwrap_or_default(),
-                                                })
-                                                .collect();
-                                            
-                                            let genres: Vec<String> = details.genres
-                                                .iter()
-                                                .map(|g| g.name.clone())
-                                                .collect();
-                                            
-                                            let poster_url = details.poster_path
-                                                .map(|p| format!("https://image.tmdb.org/t/p/original{}", p))
-                                                .unwrap_or_default();
-                                            
-                                            let poster_path = if !poster_url.is_empty() {
-                                                download_poster(&poster_url, tmdb_id, &posters_dir).unwrap_or_default()
-                                            } else {
-                                                String::new()
-                                            };
-                                            
-                                            // Fetch IMDb ID
-                                            let external_ids_url = format!(
-                                                "https://api.themoviedb.org/3/movie/{}/external_ids?api_key={}",
-                                                tmdb_id, api_key
-                                            );
-                                            
-                                            let imdb_id = if let Ok(response) = reqwest::blocking::get(&external_ids_url) {
-                                                if let Ok(external_ids) = response.json::<TMDBExternalIds>() {
-                                                    external_ids.imdb_id.unwrap_or_default()
-                                                } else {
-                                                    String::new()
-                                                }
-                                            } else {
-                                                String::new()
-                                            };
-                                            
-                                            let new_movie = Movie {
-                                                id: 0,
-                                                title: details.title,
-                                                year,
-                                                director,
-                                                genre: if genres.is_empty() { vec!["Unknown".to_string()] } else { genres },
-                                                rating: details.vote_average,
-                                                runtime: details.runtime.unwrap_or(0),
-                                                description: details.overview,
-                                                cast,
-                                                cast_details,
-                                                file_path: file_path_clone,
-                                                poster_url,
-                                                tmdb_id,
-                                                imdb_id,
-                                                poster_path,
-        watch_log: Vec::new(),
-                                            };
-                                            
-                                            let _ = sender2.send_blocking(Some((movie_id, new_movie)));
-                                            return;
-                                        }
+                                    let client = reqwest::blocking::Client::new();
+                                    if let Some(new_movie) = fetch_movie_by_tmdb_id(&client, tmdb_id, &api_key, file_path_clone, &posters_dir) {
+                                        let _ = sender2.send_blocking(Some((movie_id, new_movie)));
+                                        return;
                                     }
                                     let _ = sender2.send_blocking(None);
                                 });
                                 
                                 let poster_cache_clone_select3 = poster_cache_clone_select2.clone();
                                 glib::spawn_future_local(async move {
-                                    if let Ok(Some((old_id, new_movie))) = receiver2.recv().await {
-                                        db_clone3.borrow_mut().delete_movie(old_id);
-                                        db_clone3.borrow_mut().add_movie(new_movie);
+                                    let result = receiver2.recv().await;
+                                    drop(button_busy);
+                                    set_busy(false);
+                                    if let Ok(Some((old_id, new_movie))) = result {
+                                        db_clone3.borrow_mut().replace_with_refreshed(old_id, new_movie);
                                         
                                         // Refresh list
                                         while let Some(child) = list_box_clone3.first_child() {
@@ -3932,7 +9938,12 @@ fn build_ui(app: &Application) {
     let status_bar_clone = status_bar.clone();
     let poster_cache_clone_add = poster_cache.clone();
     let posters_dir_clone = db.borrow().posters_dir.clone();
-    add_button.connect_clicked(move |_| {
+    let busy_clone = busy.clone();
+    let set_busy_clone = set_busy.clone();
+    let add_button_clone = add_button.clone();
+    // `dropped` is a movie file dropped on the window; its title and path are filled in for the search
+    let open_add_dialog: Rc<dyn Fn(Option<ScanEntry>)> = Rc::new(move |dropped| {
+        let add_button = add_button_clone.clone();
         let dialog = Window::builder()
             .title("Add New Movie")
             .modal(true)
@@ -3972,6 +9983,11 @@ fn build_ui(app: &Application) {
         
         grid.attach(&file_label, 0, 1, 1, 1);
         grid.attach(&file_box, 1, 1, 1, 1);
+        
+        if let Some((title, _, file, _)) = dropped {
+            title_entry.set_text(&title);
+            file_entry.set_text(&file);
+        }
 
         content.append(&grid);
         
@@ -4016,6 +10032,8 @@ fn build_ui(app: &Application) {
         let status_bar_clone2 = status_bar_clone.clone();
         let poster_cache_clone_add2 = poster_cache_clone_add.clone();
         let posters_dir = posters_dir_clone.clone();
+        let busy_clone2 = busy_clone.clone();
+        let set_busy_clone2 = set_busy_clone.clone();
         search_btn.connect_clicked(move |_| {
             let search_title = title_entry.text().to_string();
             let selected_file_path = file_entry.text().to_string();
@@ -4092,7 +10110,7 @@ fn build_ui(app: &Application) {
                 
                 let api_key = db_clone3.borrow().tmdb_api_key.clone();
                 
-                let (sender, receiver) = async_channel::unbounded::<Vec<(u32, String, String, f32)>>();
+                let (sender, receiver) = async_channel::unbounded::<Vec<TMDBSearchRow>>();
                 
                 // Check cache first
                 let search_title_for_cache = search_title.clone();
@@ -4104,48 +10122,26 @@ fn build_ui(app: &Application) {
                 } else {
                     // Fetch from TMDB
                     std::thread::spawn(move || {
-                        // Search TMDB
-                        let search_url = format!(
-                            "https://api.themoviedb.org/3/search/movie?api_key={}&query={}",
-                            api_key,
-                            urlencoding::encode(&search_title)
-                        );
+                        // Search TMDB (a year typed after the title narrows the search)
+                        let search_results = search_tmdb_blocking(&api_key, &search_title);
+                        // Show ALL results (up to 20)
+                        let results: Vec<TMDBSearchRow> = search_results.iter()
+                            .map(TMDBMovie::result_row)
+                            .collect();
                         
-                        if let Ok(response) = reqwest::blocking::get(&search_url) {
-                            if let Ok(search_result) = response.json::<TMDBSearchResponse>() {
-                                let results: Vec<(u32, String, String, f32)> = search_result.results.iter()
-                                    // Show ALL results (up to 20)
-                                    .map(|r| {
-                                        let details_url = format!(
-                                            "https://api.themoviedb.org/3/movie/{}?api_key={}",
-                                            r.id, api_key
-                                        );
-                                        
-                                        if let Ok(details_response) = reqwest::blocking::get(&details_url) {
-                                            if let Ok(details) = details_response.json::<TMDBMovieDetails>() {
-                                                let year = details.release_date
-                                                    .split('-')
-                                                    .next()
-                                                    .and_then(|y| y.parse().ok())
-                                                    .unwrap_or(0);
-                                            return (r.id, details.title, year.to_string(), details.vote_average);
-                                        }
-                                    }
-                                    (r.id, "Unknown".to_string(), "????".to_string(), 0.0)
-                                })
-                                .collect();
-                            
-                            let _ = sender.send_blocking(results);
-                        }
-                    }
+                        let _ = sender.send_blocking(results);
                     });
                 }
                 
                 // Update UI with results
                 let db_clone_for_cache = db_clone3.clone();
                 let search_title_for_cache2 = search_title_for_ui.clone();
+                let selection_dialog_clone3 = selection_dialog.clone();
                 let poster_cache_clone_add3 = poster_cache_clone_add2.clone();
                 let posters_dir = posters_dir.clone();
+                let add_button = add_button.clone();
+                let busy_clone3 = busy_clone2.clone();
+                let set_busy_clone3 = set_busy_clone2.clone();
                 glib::spawn_future_local(async move {
                     if let Ok(results) = receiver.recv().await {
                         // Cache the results if not from cache
@@ -4175,10 +10171,14 @@ fn build_ui(app: &Application) {
                             search_title_for_ui, results.len()
                         ));
                         
-                        // Add result rows
-                        for (tmdb_id, title, year, rating) in &results {
+                        // Add result rows, marking movies already in the library
+                        let owned_ids = db_clone3.borrow().owned_tmdb_ids();
+                        for TMDBSearchRow { tmdb_id, title, year, rating, overview } in &results {
                             let row = gtk::ListBoxRow::new();
                             row.set_widget_name(&tmdb_id.to_string());
+                            if !overview.is_empty() {
+                                row.set_tooltip_text(Some(overview));
+                            }
                             
                             let row_box = Box::new(Orientation::Vertical, 4);
                             row_box.set_margin_start(12);
@@ -4195,6 +10195,12 @@ fn build_ui(app: &Application) {
                             
                             row_box.append(&title_label);
                             row_box.append(&rating_label);
+                            if owned_ids.contains(tmdb_id) {
+                                let owned_label = Label::new(None);
+                                owned_label.set_xalign(0.0);
+                                owned_label.set_markup("<span foreground='#e5a50a' weight='bold'>✓ Already in library</span>");
+                                row_box.append(&owned_label);
+                            }
                             row.set_child(Some(&row_box));
                             list_box_results_clone.append(&row);
                         }
@@ -4206,129 +10212,84 @@ fn build_ui(app: &Application) {
                         
                         // Handle add selected
                         let file_path_final = file_path_for_movie.clone();
+                        let start_add = Rc::new(move |tmdb_id: u32, allow_duplicate: bool| {
+                            if busy_clone3.get() {
+                                status_bar_clone3.set_text(BUSY_MESSAGE);
+                                return;
+                            }
+                            status_bar_clone3.set_text(&format!("Adding movie (TMDB ID: {})...", tmdb_id));
+                            selection_dialog_clone2.close();
+                            set_busy_clone3(true);
+                            let button_busy = ButtonBusy::start(&add_button, "Adding…");
+                            let set_busy = set_busy_clone3.clone();
+                            
+                            // Fetch full metadata
+                            let db_clone4 = db_clone3.clone();
+                            let list_box_clone4 = list_box_clone3.clone();
+                            let status_bar_clone4 = status_bar_clone3.clone();
+                            
+                            let api_key = db_clone4.borrow().tmdb_api_key.clone();
+                            let posters_dir = posters_dir.clone();
+                            let (sender2, receiver2) = async_channel::unbounded::<Option<(String, Movie)>>();
+                            
+                            let file_path_clone = file_path_final.clone();
+                            let use_ffprobe = load_config().unwrap_or_default().use_ffprobe;
+                            std::thread::spawn(move || {
+                                let client = reqwest::blocking::Client::new();
+                                if let Some(mut movie) = fetch_movie_by_tmdb_id(&client, tmdb_id, &api_key, file_path_clone, &posters_dir) {
+                                    probe_movie_file(&mut movie, use_ffprobe);
+                                    let _ = sender2.send_blocking(Some((movie.title.clone(), movie)));
+                                    return;
+                                }
+                                let _ = sender2.send_blocking(None);
+                            });
+                            
+                            let poster_cache_clone_add4 = poster_cache_clone_add3.clone();
+                            glib::spawn_future_local(async move {
+                                let result = receiver2.recv().await;
+                                drop(button_busy);
+                                set_busy(false);
+                                if let Ok(Some((title, movie))) = result {
+                                    let added = if allow_duplicate {
+                                        db_clone4.borrow_mut().add_movie_allowing_duplicate(movie.clone())
+                                    } else {
+                                        db_clone4.borrow_mut().add_movie(movie.clone())
+                                    };
+                                    if let Some(id) = added {
+                                        let row = create_movie_row(&Movie { id, ..movie }, &poster_cache_clone_add4);
+                                        list_box_clone4.append(&row);
+                                        
+                                        status_bar_clone4.set_text(&format!("Added: {}", title));
+                                    } else {
+                                        status_bar_clone4.set_text(&format!("Already in database: {}", title));
+                                    }
+                                } else {
+                                    status_bar_clone4.set_text("Failed to fetch movie metadata");
+                                }
+                            });
+                        });
+                        
                         add_selected_button.connect_clicked(move |_| {
                             if let Some(selected_row) = list_box_results_clone.selected_row() {
                                 let tmdb_id_str = selected_row.widget_name();
                                 if let Ok(tmdb_id) = tmdb_id_str.as_str().parse::<u32>() {
-                                    status_bar_clone3.set_text(&format!("Adding movie (TMDB ID: {})...", tmdb_id));
-                                    selection_dialog_clone2.close();
-                                    
-                                    // Fetch full metadata
-                                    let db_clone4 = db_clone3.clone();
-                                    let list_box_clone4 = list_box_clone3.clone();
-                                    let status_bar_clone4 = status_bar_clone3.clone();
-                                    
-                                    let api_key = db_clone4.borrow().tmdb_api_key.clone();
-                                    let posters_dir = posters_dir.clone();
-                                    let (sender2, receiver2) = async_channel::unbounded::<Option<(String, Movie)>>();
-                                    
-                                    let file_path_clone = file_path_final.clone();
-                                    std::thread::spawn(move || {
-                                        let details_url = format!(
-                                            "https://api.themoviedb.org/3/movie/{}?api_key={}&append_to_response=credits",
-                                            tmdb_id, api_key
-                                        );
-                                        
-                                        if let Ok(response) = reqwest::blocking::get(&details_url) {
-                                            if let Ok(details) = response.json::<TMDBMovieDetails>() {
-                                                let year: u16 = details.release_date
-                                                    .split('-')
-                                                    .next()
-                                                    .and_then(|y| y.parse().ok())
-                                                    .unwrap_or(0);
-                                                
-                                                let director = details.credits.crew
-                                                    .iter()
-                                                    .find(|c| c.job == "Director")
-                                                    .map(|c| c.name.clone())
-                                                    .unwrap_or_else(|| "Unknown".to_string());
-                                                
-                                                let cast: Vec<String> = details.credits.cast
-                                                    .iter()
-                                                    .take(5)
-                                                    .map(|c| c.name.clone())
-                                                    .collect();
-                                                
-                                                let cast_details: Vec<CastMember> = details.credits.cast
-                                                    .iter()
-                                                    .take(5)
-                                                    .map(|c| CastMember {
-                                                        name: c.name.clone(),
-                                                        character: c.character.clone(),
-                                                        profile_path: c.profile_path.as_ref()
-                                                            .map(|p| format!("https://image.tmdb.org/t/p/w185{}", p))
-                                                            .unwrap_or_default(),
-                                                    })
-                                                    .collect();
-                                                
-                                                let genres: Vec<String> = details.genres
-                                                    .iter()
-                                                    .map(|g| g.name.clone())
-                                                    .collect();
-                                                
-                                                let poster_url = details.poster_path
-                                                    .map(|p| format!("https://image.tmdb.org/t/p/original{}", p))
-                                                    .unwrap_or_default();
-                                                
-                                                let poster_path = if !poster_url.is_empty() {
-                                                    download_poster(&poster_url, tmdb_id, &posters_dir).unwrap_or_default()
-                                                } else {
-                                                    String::new()
-                                                };
-                                                
-                                                // Fetch IMDb ID
-                                                let external_ids_url = format!(
-                                                    "https://api.themoviedb.org/3/movie/{}/external_ids?api_key={}",
-                                                    tmdb_id, api_key
-                                                );
-                                                
-                                                let imdb_id = if let Ok(response) = reqwest::blocking::get(&external_ids_url) {
-                                                    if let Ok(external_ids) = response.json::<TMDBExternalIds>() {
-                                                        external_ids.imdb_id.unwrap_or_default()
-                                                    } else {
-                                                        String::new()
-                                                    }
-                                                } else {
-                                                    String::new()
-                                                };
-                                                
-                                                let movie = Movie {
-                                                    id: 0,
-                                                    title: details.title.clone(),
-                                                    year,
-                                                    director,
-                                                    genre: if genres.is_empty() { vec!["Unknown".to_string()] } else { genres },
-                                                    rating: details.vote_average,
-                                                    runtime: details.runtime.unwrap_or(0),
-                                                    description: details.overview,
-                                                    cast,
-                                                    cast_details,
-                                                    file_path: file_path_clone,
-                                                    poster_url,
-                                                    tmdb_id,
-                                                    imdb_id,
-                                                    poster_path,
-        watch_log: Vec::new(),
-                                                };
-                                                
-                                                let _ = sender2.send_blocking(Some((details.title, movie)));
-                                                return;
-                                            }
-                                        }
-                                        let _ = sender2.send_blocking(None);
-                                    });
+                                    if !owned_ids.contains(&tmdb_id) {
+                                        start_add(tmdb_id, false);
+                                        return;
+                                    }
                                     
-                                    let poster_cache_clone_add4 = poster_cache_clone_add3.clone();
+                                    // Already owned - confirm before creating a duplicate entry
+                                    let start_add = start_add.clone();
+                                    let selection_dialog = selection_dialog_clone3.clone();
                                     glib::spawn_future_local(async move {
-                                        if let Ok(Some((title, movie))) = receiver2.recv().await {
-                                            db_clone4.borrow_mut().add_movie(movie.clone());
-                                            
-                                            let row = create_movie_row(&movie, &poster_cache_clone_add4);
-                                            list_box_clone4.append(&row);
-                                            
-                                            status_bar_clone4.set_text(&format!("Added: {}", title));
-                                        } else {
-                                            status_bar_clone4.set_text("Failed to fetch movie metadata");
+                                        let add_anyway = confirm(
+                                            &selection_dialog,
+                                            "Already in library",
+                                            "This movie is already in your library. Add another entry for it anyway?",
+                                            "Add Anyway",
+                                        ).await;
+                                        if add_anyway {
+                                            start_add(tmdb_id, true);
                                         }
                                     });
                                 }
@@ -4341,11 +10302,75 @@ fn build_ui(app: &Application) {
 
         dialog.present();
     });
+    let open_add_dialog_clone = open_add_dialog.clone();
+    add_button.connect_clicked(move |_| open_add_dialog_clone(None));
+
+    // Drag and drop - dropped movie files open the Add Movie dialog, one each; directories
+    // are scanned, along with episode files, which the scan groups into their show
+    let drop_target = gtk::DropTarget::new(gtk::gdk::FileList::static_type(), gtk::gdk::DragAction::COPY);
+    let db_clone = db.clone();
+    let status_bar_clone = status_bar.clone();
+    let refresh_view_clone = refresh_view.clone();
+    let busy_clone = busy.clone();
+    let set_busy_clone = set_busy.clone();
+    drop_target.connect_drop(move |_, value, _, _| {
+        let Ok(file_list) = value.get::<gtk::gdk::FileList>() else {
+            return false;
+        };
+
+        let config = load_config().unwrap_or_default();
+        let mut directories = Vec::new();
+        let mut episodes = Vec::new();
+        let mut movies = Vec::new();
+        for path in file_list.files().iter().filter_map(|f| f.path()) {
+            if path.is_dir() {
+                directories.push(path.to_string_lossy().to_string());
+            } else if let Some(entry) = video_file_entry(&path, &config.video_extensions) {
+                match entry.3 {
+                    MediaType::Movie => movies.push(entry),
+                    MediaType::Tv => episodes.push(entry.2),
+                }
+            }
+        }
+
+        if directories.is_empty() && episodes.is_empty() && movies.is_empty() {
+            status_bar_clone.set_text("No video files or folders in drop");
+            return false;
+        }
+        let scanning = !directories.is_empty() || !episodes.is_empty();
+        if scanning && busy_clone.get() {
+            status_bar_clone.set_text(BUSY_MESSAGE);
+            return false;
+        }
+
+        for dropped in movies {
+            open_add_dialog(Some(dropped));
+        }
+        if scanning {
+            status_bar_clone.set_text(&format!(
+                "Scanning {} dropped folder(s) and {} episode file(s)...",
+                directories.len(),
+                episodes.len()
+            ));
+            set_busy_clone(true);
+            let scan = spawn_scan_worker(ScanJob::from_config(&config, &db_clone.borrow(), directories.clone(), episodes));
+            let set_busy = set_busy_clone.clone();
+            handle_scan_messages(scan, directories, db_clone.clone(), status_bar_clone.clone(), refresh_view_clone.clone(), move || set_busy(false));
+        }
+        true
+    });
+    window.add_controller(drop_target);
+
     // Settings button - change API key and manage scan directories
     let window_clone = window.clone();
     let db_clone = db.clone();
     let status_bar_clone = status_bar.clone();
-    settings_button.connect_clicked(move |_| {
+    let movie_list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let is_grid_view_clone = is_grid_view.clone();
+    let poster_cache_clone = poster_cache.clone();
+    let current_filters_clone = current_filters.clone();
+    settings_action.connect_activate(move |_, _| {
         let dialog = Window::builder()
             .title("Settings")
             .modal(true)
@@ -4371,10 +10396,42 @@ fn build_ui(app: &Application) {
 
         content.append(&api_label);
         content.append(&api_entry);
-        content.append(&Separator::new(Orientation::Horizontal));
+        
+        // A key from the command line or environment can't be edited here
+        let api_key_source = external_api_key_source();
+        if let Some(source) = api_key_source {
+            api_entry.set_sensitive(false);
+            let api_note = Label::new(Some(&format!("Using the key from {}. Change it there; edits here aren't saved.", source)));
+            api_note.set_xalign(0.0);
+            api_note.set_opacity(0.7);
+            api_note.set_wrap(true);
+            content.append(&api_note);
+        }
 
         // Load current config (need it for year_cutoff)
         let current_config = load_config().unwrap_or_default();
+
+        let omdb_label = Label::new(None);
+        omdb_label.set_xalign(0.0);
+        omdb_label.set_markup("<b>OMDb API Key (optional):</b>");
+        let omdb_entry = Entry::new();
+        omdb_entry.set_text(&current_config.omdb_api_key);
+        omdb_entry.set_visibility(false);
+        omdb_entry.set_placeholder_text(Some("Used by scans for titles TMDB can't find"));
+        content.append(&omdb_label);
+        content.append(&omdb_entry);
+
+        let language_box = Box::new(Orientation::Horizontal, 8);
+        let language_label = Label::new(None);
+        language_label.set_markup("<b>Metadata Language:</b>");
+        let language_labels: Vec<&str> = TMDB_LANGUAGES.iter().map(|(_, label)| *label).collect();
+        let language_dropdown = DropDown::new(Some(StringList::new(&language_labels)), None::<gtk::Expression>);
+        let language_idx = TMDB_LANGUAGES.iter().position(|(code, _)| *code == current_config.tmdb_language).unwrap_or(0);
+        language_dropdown.set_selected(language_idx as u32);
+        language_box.append(&language_label);
+        language_box.append(&language_dropdown);
+        content.append(&language_box);
+        content.append(&Separator::new(Orientation::Horizontal));
         
         // Year Cutoff section
         let year_label = Label::new(Some("Year Cutoff for Auto-Scan:"));
@@ -4394,6 +10451,148 @@ fn build_ui(app: &Application) {
         content.append(&year_label);
         content.append(&year_help);
         content.append(&year_entry);
+        
+        // Minimum vote count for auto-matching
+        let votes_label = Label::new(Some("Minimum TMDB Votes for Auto-Match:"));
+        votes_label.set_xalign(0.0);
+        votes_label.set_markup("<b>Minimum TMDB Votes for Auto-Match:</b>");
+        
+        let votes_help = Label::new(Some("Scans prefer the most popular match with at least this many votes; use \"Wrong Movie?\" to override"));
+        votes_help.set_xalign(0.0);
+        votes_help.set_opacity(0.7);
+        votes_help.set_wrap(true);
+        
+        let votes_entry = Entry::new();
+        votes_entry.set_text(&current_config.min_vote_count.to_string());
+        votes_entry.set_width_chars(6);
+        
+        content.append(&votes_label);
+        content.append(&votes_help);
+        content.append(&votes_entry);
+        content.append(&Separator::new(Orientation::Horizontal));
+
+        // Scan request concurrency
+        let requests_label = Label::new(Some("Simultaneous TMDB Requests:"));
+        requests_label.set_xalign(0.0);
+        requests_label.set_markup("<b>Simultaneous TMDB Requests:</b>");
+
+        let requests_help = Label::new(Some("Lower this if scans add many movies without metadata; TMDB rejects requests that come too fast. Shared by all scans and refreshes; a change applies after a restart"));
+        requests_help.set_xalign(0.0);
+        requests_help.set_opacity(0.7);
+        requests_help.set_wrap(true);
+
+        let requests_entry = Entry::new();
+        requests_entry.set_text(&current_config.max_concurrent_requests.to_string());
+        requests_entry.set_width_chars(6);
+
+        content.append(&requests_label);
+        content.append(&requests_help);
+        content.append(&requests_entry);
+        content.append(&Separator::new(Orientation::Horizontal));
+
+        // Cast size
+        let cast_size_label = Label::new(Some("Cast Members to Store:"));
+        cast_size_label.set_xalign(0.0);
+        cast_size_label.set_markup("<b>Cast Members to Store:</b>");
+
+        let cast_size_help = Label::new(Some("How many actors Show Cast lists for movies added or refreshed from now on; 0 keeps the whole cast"));
+        cast_size_help.set_xalign(0.0);
+        cast_size_help.set_opacity(0.7);
+        cast_size_help.set_wrap(true);
+
+        let cast_size_entry = Entry::new();
+        cast_size_entry.set_text(&current_config.max_cast_members.to_string());
+        cast_size_entry.set_width_chars(6);
+
+        content.append(&cast_size_label);
+        content.append(&cast_size_help);
+        content.append(&cast_size_entry);
+        content.append(&Separator::new(Orientation::Horizontal));
+
+        // Media player
+        let player_label = Label::new(Some("Media Player Command:"));
+        player_label.set_xalign(0.0);
+        player_label.set_markup("<b>Media Player Command:</b>");
+        
+        let player_help = Label::new(Some("e.g. \"vlc\", \"mpv --fs\" or \"mplayer %f -loop 0\". %f is replaced by the file path; without it the path is added at the end"));
+        player_help.set_xalign(0.0);
+        player_help.set_opacity(0.7);
+        player_help.set_wrap(true);
+        
+        let player_entry = Entry::new();
+        player_entry.set_text(&current_config.player_command);
+        
+        content.append(&player_label);
+        content.append(&player_help);
+        content.append(&player_entry);
+        content.append(&Separator::new(Orientation::Horizontal));
+
+        // Scan skip rules
+        let skip_label = Label::new(Some("Skip During Scans:"));
+        skip_label.set_xalign(0.0);
+        skip_label.set_markup("<b>Skip During Scans:</b>");
+        
+        let skip_help = Label::new(Some("Video files smaller than this size (MB, 0 to keep all) or with any of these words in the name are left out of directory scans. Only files with the listed extensions count as video, and folders named like a skipped folder (* and ? as wildcards) aren't searched."));
+        skip_help.set_xalign(0.0);
+        skip_help.set_opacity(0.7);
+        skip_help.set_wrap(true);
+        
+        let skip_row = Box::new(Orientation::Horizontal, 8);
+        let min_size_entry = Entry::new();
+        min_size_entry.set_text(&current_config.min_file_size_mb.to_string());
+        min_size_entry.set_width_chars(6);
+        let ignore_entry = Entry::new();
+        ignore_entry.set_text(&current_config.scan_ignore_patterns.join(", "));
+        ignore_entry.set_placeholder_text(Some("sample, trailer"));
+        ignore_entry.set_hexpand(true);
+        skip_row.append(&Label::new(Some("Min size (MB):")));
+        skip_row.append(&min_size_entry);
+        skip_row.append(&Label::new(Some("Ignore names:")));
+        skip_row.append(&ignore_entry);
+        
+        let extensions_row = Box::new(Orientation::Horizontal, 8);
+        let extensions_entry = Entry::new();
+        extensions_entry.set_text(&current_config.video_extensions.join(", "));
+        extensions_entry.set_placeholder_text(Some("mp4, mkv, avi"));
+        extensions_entry.set_hexpand(true);
+        extensions_row.append(&Label::new(Some("Video extensions:")));
+        extensions_row.append(&extensions_entry);
+        
+        content.append(&skip_label);
+        content.append(&skip_help);
+        content.append(&skip_row);
+        content.append(&extensions_row);
+        
+        let exclude_row = Box::new(Orientation::Horizontal, 8);
+        let exclude_entry = Entry::new();
+        exclude_entry.set_text(&current_config.scan_exclude_patterns.join(", "));
+        exclude_entry.set_placeholder_text(Some("sample, extras, .*"));
+        exclude_entry.set_hexpand(true);
+        exclude_row.append(&Label::new(Some("Skip folders:")));
+        exclude_row.append(&exclude_entry);
+        content.append(&exclude_row);
+        content.append(&Separator::new(Orientation::Horizontal));
+
+        // External link templates
+        let links_label = Label::new(Some("External Links:"));
+        links_label.set_xalign(0.0);
+        links_label.set_markup("<b>External Links:</b>");
+        
+        let links_help = Label::new(Some("One \"Name = URL\" per line. URLs can use {tmdb_id}, {imdb_id} and {title}"));
+        links_help.set_xalign(0.0);
+        links_help.set_opacity(0.7);
+        links_help.set_wrap(true);
+        
+        let links_view = gtk::TextView::new();
+        links_view.set_monospace(true);
+        links_view.buffer().set_text(&format_link_templates(&current_config.link_templates));
+        let links_scroll = ScrolledWindow::new();
+        links_scroll.set_min_content_height(80);
+        links_scroll.set_child(Some(&links_view));
+        
+        content.append(&links_label);
+        content.append(&links_help);
+        content.append(&links_scroll);
         content.append(&Separator::new(Orientation::Horizontal));
 
         // Scan directories section
@@ -4425,9 +10624,13 @@ fn build_ui(app: &Application) {
             dir_label.set_xalign(0.0);
             dir_label.set_hexpand(true);
             
+            let scanned_label = Label::new(Some(&last_scanned_text(&current_config, dir)));
+            scanned_label.set_opacity(0.7);
+            
             let remove_btn = Button::with_label("Remove");
             
             hbox.append(&dir_label);
+            hbox.append(&scanned_label);
             hbox.append(&remove_btn);
             row.set_child(Some(&hbox));
             list_box.append(&row);
@@ -4453,282 +10656,1528 @@ fn build_ui(app: &Application) {
         add_dir_box.append(&add_dir_btn);
         dirs_box.append(&add_dir_box);
         
-        content.append(&dirs_box);
+        content.append(&dirs_box);
+        
+        // Add directory handler
+        let window_clone2 = window_clone.clone();
+        let dirs_list_clone = dirs_list.clone();
+        let list_box_clone = list_box.clone();
+        add_dir_btn.connect_clicked(move |_| {
+            let file_dialog = gtk::FileDialog::new();
+            file_dialog.set_title("Select Directory to Scan");
+            
+            let dirs_list_clone2 = dirs_list_clone.clone();
+            let list_box_clone2 = list_box_clone.clone();
+            file_dialog.select_folder(Some(&window_clone2), None::<&gtk::gio::Cancellable>, move |result| {
+                if let Ok(folder) = result {
+                    if let Some(path) = folder.path() {
+                        let path_str = path.to_string_lossy().to_string();
+                        
+                        // Add to list
+                        dirs_list_clone2.borrow_mut().push(path_str.clone());
+                        
+                        // Add to UI
+                        let row = gtk::ListBoxRow::new();
+                        let hbox = Box::new(Orientation::Horizontal, 8);
+                        hbox.set_margin_start(8);
+                        hbox.set_margin_end(8);
+                        hbox.set_margin_top(4);
+                        hbox.set_margin_bottom(4);
+                        
+                        let dir_label = Label::new(Some(&path_str));
+                        dir_label.set_xalign(0.0);
+                        dir_label.set_hexpand(true);
+                        
+                        let scanned_label = Label::new(Some(&last_scanned_text(&load_config().unwrap_or_default(), &path_str)));
+                        scanned_label.set_opacity(0.7);
+                        
+                        let remove_btn = Button::with_label("Remove");
+                        
+                        hbox.append(&dir_label);
+                        hbox.append(&scanned_label);
+                        hbox.append(&remove_btn);
+                        row.set_child(Some(&hbox));
+                        list_box_clone2.append(&row);
+                        
+                        // Remove button handler
+                        let dirs_list_clone3 = dirs_list_clone2.clone();
+                        let path_str_clone = path_str.clone();
+                        let list_box_clone3 = list_box_clone2.clone();
+                        remove_btn.connect_clicked(move |btn| {
+                            dirs_list_clone3.borrow_mut().retain(|d| d != &path_str_clone);
+                            if let Some(row) = btn.parent().and_then(|p| p.parent()) {
+                                list_box_clone3.remove(&row);
+                            }
+                        });
+                    }
+                }
+            });
+        });
+        
+        content.append(&Separator::new(Orientation::Horizontal));
+        
+        // Auto-scan checkbox
+        let auto_scan_check = gtk::CheckButton::with_label("Automatically scan directories on startup");
+        auto_scan_check.set_active(current_config.auto_scan_on_startup);
+        content.append(&auto_scan_check);
+        let ffprobe_check = gtk::CheckButton::with_label("Read resolution, codecs and duration with ffprobe (needs FFmpeg installed)");
+        ffprobe_check.set_active(current_config.use_ffprobe);
+        content.append(&ffprobe_check);
+        content.append(&Separator::new(Orientation::Horizontal));
+
+        // Theme selection
+        let theme_box = Box::new(Orientation::Horizontal, 8);
+        let theme_label = Label::new(Some("Theme:"));
+        theme_label.set_markup("<b>Theme:</b>");
+        let theme_dropdown = DropDown::new(Some(StringList::new(&["System", "Light", "Dark"])), None::<gtk::Expression>);
+        let theme_idx = THEME_OPTIONS.iter().position(|t| *t == current_config.theme).unwrap_or(0);
+        theme_dropdown.set_selected(theme_idx as u32);
+        theme_dropdown.connect_selected_notify(|dropdown| {
+            // Preview immediately; persisted on Save
+            apply_theme(THEME_OPTIONS.get(dropdown.selected() as usize).unwrap_or(&"system"));
+        });
+        theme_box.append(&theme_label);
+        theme_box.append(&theme_dropdown);
+        content.append(&theme_box);
+
+        // List thumbnail size
+        let thumb_box = Box::new(Orientation::Horizontal, 8);
+        let thumb_label = Label::new(Some("List Poster Size:"));
+        thumb_label.set_markup("<b>List Poster Size:</b>");
+        let thumb_labels: Vec<&str> = LIST_THUMB_PRESETS.iter().map(|(label, _)| *label).collect();
+        let thumb_dropdown = DropDown::new(Some(StringList::new(&thumb_labels)), None::<gtk::Expression>);
+        let (current_thumb_width, _) = current_config.list_thumb_size();
+        let thumb_idx = LIST_THUMB_PRESETS.iter()
+            .position(|(_, (width, _))| *width >= current_thumb_width)
+            .unwrap_or(LIST_THUMB_PRESETS.len() - 1);
+        thumb_dropdown.set_selected(thumb_idx as u32);
+        thumb_box.append(&thumb_label);
+        thumb_box.append(&thumb_dropdown);
+        content.append(&thumb_box);
+
+        // Storage format for movies.db
+        let format_box = Box::new(Orientation::Horizontal, 8);
+        let format_label = Label::new(Some("Storage Format:"));
+        format_label.set_markup("<b>Storage Format:</b>");
+        let format_labels: Vec<&str> = STORAGE_FORMATS.iter().map(|(_, label)| *label).collect();
+        let format_dropdown = DropDown::new(Some(StringList::new(&format_labels)), None::<gtk::Expression>);
+        let format_idx = STORAGE_FORMATS.iter().position(|(f, _)| *f == current_config.storage_format)
+            .or_else(|| STORAGE_FORMATS.iter().position(|(f, _)| *f == DEFAULT_STORAGE_FORMAT))
+            .unwrap_or(0);
+        format_dropdown.set_selected(format_idx as u32);
+        format_box.append(&format_label);
+        format_box.append(&format_dropdown);
+        content.append(&format_box);
+
+        // Buttons
+        let button_box = Box::new(Orientation::Horizontal, 8);
+        button_box.set_halign(gtk::Align::End);
+        let cancel_btn = Button::with_label("Cancel");
+        let save_btn = Button::with_label("Save");
+        button_box.append(&cancel_btn);
+        button_box.append(&save_btn);
+        content.append(&button_box);
+
+        dialog.set_child(Some(&content));
+
+        // Drop any unsaved theme preview however the dialog is closed
+        dialog.connect_close_request(|_| {
+            apply_theme(&load_config().unwrap_or_default().theme);
+            glib::Propagation::Proceed
+        });
+
+        let dialog_clone = dialog.clone();
+        cancel_btn.connect_clicked(move |_| {
+            dialog_clone.close();
+        });
+
+        let dialog_clone = dialog.clone();
+        let db_clone2 = db_clone.clone();
+        let status_bar_clone2 = status_bar_clone.clone();
+        let movie_list_box_clone2 = movie_list_box_clone.clone();
+        let grid_flow_clone2 = grid_flow_clone.clone();
+        let is_grid_view_clone2 = is_grid_view_clone.clone();
+        let poster_cache_clone2 = poster_cache_clone.clone();
+        let current_filters_clone2 = current_filters_clone.clone();
+        save_btn.connect_clicked(move |_| {
+            let new_key = api_entry.text().to_string();
+            if !new_key.is_empty() {
+                // Update database API key
+                db_clone2.borrow_mut().tmdb_api_key = new_key.clone();
+                
+                // Parse year cutoff
+                let year_cutoff = year_entry.text()
+                    .to_string()
+                    .parse::<i32>()
+                    .unwrap_or(1966);
+                
+                // Save to config, preserving settings not shown in this dialog
+                let mut config = load_config().unwrap_or_default();
+                if api_key_source.is_none() {
+                    config.tmdb_api_key = new_key;
+                }
+                config.scan_directories = dirs_list.borrow().clone();
+                let scan_directories = config.scan_directories.clone();
+                config.last_scanned.retain(|dir, _| scan_directories.contains(dir));
+                config.auto_scan_on_startup = auto_scan_check.is_active();
+                config.use_ffprobe = ffprobe_check.is_active();
+                config.omdb_api_key = omdb_entry.text().trim().to_string();
+                let tmdb_language = TMDB_LANGUAGES.get(language_dropdown.selected() as usize).map(|(code, _)| *code).unwrap_or("en-US");
+                if config.tmdb_language != tmdb_language {
+                    // Cached search results hold titles in the old language
+                    config.tmdb_language = tmdb_language.to_string();
+                    set_tmdb_language(tmdb_language);
+                    db_clone2.borrow_mut().tmdb_cache.clear();
+                }
+                config.year_cutoff = year_cutoff;
+                config.min_vote_count = votes_entry.text().trim().parse().unwrap_or(config.min_vote_count);
+                config.max_concurrent_requests = requests_entry.text().trim().parse::<usize>()
+                    .map(|n| n.max(1))
+                    .unwrap_or(config.max_concurrent_requests);
+                config.max_cast_members = cast_size_entry.text().trim().parse().unwrap_or(config.max_cast_members);
+                let player_command = player_entry.text().trim().to_string();
+                config.player_command = if player_command.is_empty() { default_player_command() } else { player_command };
+                config.min_file_size_mb = min_size_entry.text().trim().parse().unwrap_or(config.min_file_size_mb);
+                let video_extensions = parse_video_extensions(&extensions_entry.text());
+                config.video_extensions = if video_extensions.is_empty() { default_video_extensions() } else { video_extensions };
+                config.scan_exclude_patterns = exclude_entry.text()
+                    .split(',')
+                    .map(|p| p.trim().to_string())
+                    .filter(|p| !p.is_empty())
+                    .collect();
+                config.scan_ignore_patterns = ignore_entry.text()
+                    .split(',')
+                    .map(|p| p.trim().to_string())
+                    .filter(|p| !p.is_empty())
+                    .collect();
+                let links_buffer = links_view.buffer();
+                config.link_templates = parse_link_templates(&links_buffer.text(&links_buffer.start_iter(), &links_buffer.end_iter(), false));
+                config.theme = THEME_OPTIONS.get(theme_dropdown.selected() as usize).unwrap_or(&"system").to_string();
+                let old_thumb_size = config.list_thumb_size();
+                // Keep a hand-edited size unless a different preset was picked
+                let thumb_preset = LIST_THUMB_PRESETS.get(thumb_dropdown.selected() as usize)
+                    .filter(|_| thumb_dropdown.selected() != thumb_idx as u32);
+                if let Some((_, (width, height))) = thumb_preset {
+                    config.list_thumb_width = *width;
+                    config.list_thumb_height = *height;
+                }
+                let storage_format = STORAGE_FORMATS.get(format_dropdown.selected() as usize).map(|(f, _)| *f).unwrap_or(DEFAULT_STORAGE_FORMAT);
+                if config.storage_format != storage_format {
+                    // Rewrite the database right away in the new format
+                    config.storage_format = storage_format.to_string();
+                    db_clone2.borrow_mut().storage_format = config.storage_format.clone();
+                    if let Err(e) = db_clone2.borrow().save_to_file() {
+                        log_warn!("Warning: Failed to save database in new format: {}", e);
+                    }
+                }
+                let new_thumb_size = config.list_thumb_size();
+                if new_thumb_size != old_thumb_size {
+                    // Cached thumbnails are at the old size - regenerate the list
+                    LIST_THUMB_SIZE.with(|size| size.set(new_thumb_size));
+                    poster_cache_clone2.borrow_mut().clear();
+                    let is_grid = *is_grid_view_clone2.borrow();
+                    refresh_movie_list(&movie_list_box_clone2, &grid_flow_clone2, is_grid, &db_clone2, &current_filters_clone2(), &poster_cache_clone2);
+                }
+                if let Err(e) = save_config(&config) {
+                    status_bar_clone2.set_text(&format!("Error saving config: {}", e));
+                } else {
+                    status_bar_clone2.set_text("Settings saved successfully");
+                }
+            }
+            dialog_clone.close();
+        });
+
+        dialog.present();
+    });
+
+    
+    // Statistics button
+    let db_clone = db.clone();
+    let window_clone = window.clone();
+    let status_bar_clone = status_bar.clone();
+    stats_action.connect_activate(move |_, _| {
+        let db = db_clone.borrow();
+        let movies = db.list_all();
+        
+        if movies.is_empty() {
+            drop(db);
+            let dialog = gtk::AlertDialog::builder()
+                .message("No Statistics Available")
+                .detail("Add some movies to your database first!")
+                .buttons(vec!["OK"])
+                .build();
+            dialog.show(Some(&window_clone));
+            return;
+        }
+        
+        let stats = db.compute_statistics();
+        
+        // (title, files) for the disk usage section, measured off the main thread
+        let movie_files: Vec<(String, Vec<String>)> = movies.iter()
+            .map(|m| (format!("{} ({})", m.title, m.year), m.file_paths.clone()))
+            .collect();
+        
+        drop(db);
+        
+        // Create statistics dialog
+        let stats_dialog = Window::builder()
+            .title("📊 Database Statistics")
+            .modal(true)
+            .transient_for(&window_clone)
+            .default_width(600)
+            .default_height(500)
+            .build();
+        
+        let scroll = ScrolledWindow::new();
+        scroll.set_vexpand(true);
+        
+        let stats_box = Box::new(Orientation::Vertical, 12);
+        stats_box.set_margin_start(20);
+        stats_box.set_margin_end(20);
+        stats_box.set_margin_top(20);
+        stats_box.set_margin_bottom(20);
+        
+        // Overview section
+        let overview_label = Label::new(None);
+        overview_label.set_xalign(0.0);
+        overview_label.set_markup(&format!(
+            "<span size='large' weight='bold'>📊 Overview</span>\n\n\
+            <b>Total Movies:</b> {}\n\
+            <b>Average Rating:</b> ⭐ {:.2}/10\n\
+            <b>Total Runtime:</b> {} hours ({} minutes)\n\
+            <b>Average Runtime:</b> {} minutes\n\
+            <b>Year Range:</b> {} - {}",
+            stats.total_movies,
+            stats.avg_rating,
+            stats.total_runtime / 60,
+            stats.total_runtime,
+            stats.avg_runtime,
+            stats.oldest_year,
+            stats.newest_year
+        ));
+        stats_box.append(&overview_label);
+        stats_box.append(&Separator::new(Orientation::Horizontal));
+        
+        // Disk usage section, filled in once the files have been measured
+        let disk_label = Label::new(None);
+        disk_label.set_xalign(0.0);
+        disk_label.set_markup("<span size='large' weight='bold'>💽 Disk Usage</span>\n\nMeasuring files...");
+        stats_box.append(&disk_label);
+        stats_box.append(&Separator::new(Orientation::Horizontal));
+        
+        let (sender, receiver) = async_channel::bounded::<DiskUsage>(1);
+        std::thread::spawn(move || {
+            let _ = sender.send_blocking(measure_disk_usage(movie_files));
+        });
+        glib::spawn_future_local(async move {
+            let Ok(usage) = receiver.recv().await else {
+                return;
+            };
+            let average = usage.total_bytes.checked_div(usage.movies_measured as u64).unwrap_or(0);
+            let largest = usage.largest.iter()
+                .map(|(title, bytes)| format!("{} - {}", glib::markup_escape_text(title), format_disk_size(*bytes)))
+                .collect::<Vec<String>>()
+                .join("\n");
+            disk_label.set_markup(&format!(
+                "<span size='large' weight='bold'>💽 Disk Usage</span>\n\n\
+                <b>Total Size:</b> {}\n\
+                <b>Average per Movie:</b> {}\n\
+                <b>Missing Files:</b> {}\n\
+                <b>No File Associated:</b> {}\n\n\
+                <b>Largest Files:</b>\n{}",
+                format_disk_size(usage.total_bytes),
+                format_disk_size(average),
+                usage.missing,
+                usage.unassociated,
+                if largest.is_empty() { String::from("None") } else { largest }
+            ));
+        });
+        
+        // Top rated section
+        let top_rated_label = Label::new(None);
+        top_rated_label.set_xalign(0.0);
+        let top_rated_text = stats.top_rated.iter()
+            .map(|(title, year, rating)| format!("{} ({}) - ⭐ {:.1}", glib::markup_escape_text(title), year, rating))
+            .collect::<Vec<String>>()
+            .join("\n");
+        top_rated_label.set_markup(&format!(
+            "<span size='large' weight='bold'>🏆 Top 100 Rated Movies</span>\n\n{}",
+            top_rated_text
+        ));
+        stats_box.append(&top_rated_label);
+        stats_box.append(&Separator::new(Orientation::Horizontal));
+        
+        // Genre breakdown
+        let genre_text = stats.genres.iter()
+            .take(10)
+            .map(|(genre, count)| format!("<b>{}:</b> {} movies", glib::markup_escape_text(genre), count))
+            .collect::<Vec<String>>()
+            .join("\n");
+        
+        let genre_label = Label::new(None);
+        genre_label.set_xalign(0.0);
+        genre_label.set_markup(&format!(
+            "<span size='large' weight='bold'>🎭 Top Genres</span>\n\n{}",
+            genre_text
+        ));
+        stats_box.append(&genre_label);
+        stats_box.append(&Separator::new(Orientation::Horizontal));
+        
+        // People breakdown
+        for (heading, people) in [("🌟 Top 10 Actors", &stats.top_actors), ("🎬 Top 10 Directors", &stats.top_directors)] {
+            let people_text = people.iter()
+                .map(|(name, count)| format!("<b>{}:</b> {} movies", glib::markup_escape_text(name), count))
+                .collect::<Vec<String>>()
+                .join("\n");
+            let people_label = Label::new(None);
+            people_label.set_xalign(0.0);
+            people_label.set_markup(&format!(
+                "<span size='large' weight='bold'>{}</span>\n\n{}",
+                heading,
+                if people_text.is_empty() { String::from("None yet") } else { people_text }
+            ));
+            stats_box.append(&people_label);
+            stats_box.append(&Separator::new(Orientation::Horizontal));
+        }
+        
+        // Decade breakdown
+        let decade_text = stats.decades.iter()
+            .map(|(decade, count)| format!("<b>{}s:</b> {} movies", decade, count))
+            .collect::<Vec<String>>()
+            .join("\n");
+        
+        let decade_label = Label::new(None);
+        decade_label.set_xalign(0.0);
+        decade_label.set_markup(&format!(
+            "<span size='large' weight='bold'>📅 By Decade</span>\n\n{}",
+            decade_text
+        ));
+        stats_box.append(&decade_label);
         
-        // Add directory handler
-        let window_clone2 = window_clone.clone();
-        let dirs_list_clone = dirs_list.clone();
-        let list_box_clone = list_box.clone();
-        add_dir_btn.connect_clicked(move |_| {
-            let file_dialog = gtk::FileDialog::new();
-            file_dialog.set_title("Select Directory to Scan");
+        // Export and close buttons
+        let button_box = Box::new(Orientation::Horizontal, 8);
+        button_box.set_halign(Align::End);
+        let export_button = Button::with_label("Export Report...");
+        export_button.set_tooltip_text(Some("Save these statistics as an HTML page, or as plain text with a .txt name"));
+        let close_button = Button::with_label("Close");
+        button_box.append(&export_button);
+        button_box.append(&close_button);
+        stats_box.append(&button_box);
+        
+        let stats_dialog_clone = stats_dialog.clone();
+        let status_bar_clone2 = status_bar_clone.clone();
+        export_button.connect_clicked(move |_| {
+            let file_dialog = gtk::FileDialog::builder()
+                .title("Export Statistics Report")
+                .initial_name(format!("movie_statistics_{}.html", chrono::Local::now().format("%Y-%m-%d")))
+                .modal(true)
+                .build();
             
-            let dirs_list_clone2 = dirs_list_clone.clone();
-            let list_box_clone2 = list_box_clone.clone();
-            file_dialog.select_folder(Some(&window_clone2), None::<&gtk::gio::Cancellable>, move |result| {
-                if let Ok(folder) = result {
-                    if let Some(path) = folder.path() {
-                        let path_str = path.to_string_lossy().to_string();
-                        
-                        // Add to list
-                        dirs_list_clone2.borrow_mut().push(path_str.clone());
-                        
-                        // Add to UI
-                        let row = gtk::ListBoxRow::new();
-                        let hbox = Box::new(Orientation::Horizontal, 8);
-                        hbox.set_margin_start(8);
-                        hbox.set_margin_end(8);
-                        hbox.set_margin_top(4);
-                        hbox.set_margin_bottom(4);
-                        
-                        let dir_label = Label::new(Some(&path_str));
-                        dir_label.set_xalign(0.0);
-                        dir_label.set_hexpand(true);
-                        
-                        let remove_btn = Button::with_label("Remove");
-                        
-                        hbox.append(&dir_label);
-                        hbox.append(&remove_btn);
-                        row.set_child(Some(&hbox));
-                        list_box_clone2.append(&row);
-                        
-                        // Remove button handler
-                        let dirs_list_clone3 = dirs_list_clone2.clone();
-                        let path_str_clone = path_str.clone();
-                        let list_box_clone3 = list_box_clone2.clone();
-                        remove_btn.connect_clicked(move |btn| {
-                            dirs_list_clone3.borrow_mut().retain(|d| d != &path_str_clone);
-                            if let Some(row) = btn.parent().and_then(|p| p.parent()) {
-                                list_box_clone3.remove(&row);
-                            }
-                        });
+            let report = stats.clone();
+            let status_bar_clone3 = status_bar_clone2.clone();
+            file_dialog.save(Some(&stats_dialog_clone), gtk::gio::Cancellable::NONE, move |result| {
+                let Some(target) = result.ok().and_then(|file| file.path()) else {
+                    return;
+                };
+                let is_text = target.extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("txt"));
+                let contents = if is_text { report.to_text() } else { report.to_html() };
+                match std::fs::write(&target, contents) {
+                    Ok(()) => status_bar_clone3.set_text(&format!("Statistics report saved to {}", target.display())),
+                    Err(e) => {
+                        log_warn!("Warning: Statistics export failed: {}", e);
+                        status_bar_clone3.set_text(&format!("Statistics export failed: {}", e));
                     }
                 }
             });
         });
         
+        let stats_dialog_clone = stats_dialog.clone();
+        close_button.connect_clicked(move |_| {
+            stats_dialog_clone.close();
+        });
+        
+        scroll.set_child(Some(&stats_box));
+        stats_dialog.set_child(Some(&scroll));
+        stats_dialog.present();
+    });
+
+    // Diagnostics button - log tail and environment info for bug reports
+    let window_clone = window.clone();
+    let db_clone = db.clone();
+    diagnostics_action.connect_activate(move |_, _| {
+        let db = db_clone.borrow();
+        let (poster_files, poster_bytes) = dir_usage(Path::new(&db.posters_dir));
+        let player_command = load_config().map(|c| c.player_command).unwrap_or_else(default_player_command);
+        let player_program = split_command(&player_command).into_iter().next().unwrap_or_default();
+        let player_display = match find_in_path(&player_program) {
+            Some(path) => format!("{} ({})", player_command, path.display()),
+            None => format!("{} (not found on PATH)", player_command),
+        };
+        let environment = format!(
+            "<b>Config file:</b> {}\n\
+            <b>Data file:</b> {}\n\
+            <b>Posters directory:</b> {}\n\
+            <b>Poster cache:</b> {} files on disk ({:.1} MB), {} thumbnails in memory\n\
+            <b>Movies:</b> {}\n\
+            <b>Player:</b> {}\n\
+            <b>Log file:</b> {}",
+            escape_markup(&get_config_file().display().to_string()),
+            escape_markup(&db.data_file),
+            escape_markup(&db.posters_dir),
+            poster_files,
+            poster_bytes as f64 / (1024.0 * 1024.0),
+            db.poster_cache.borrow().len(),
+            db.movies.len(),
+            escape_markup(&player_display),
+            escape_markup(&get_log_file().display().to_string()),
+        );
+        drop(db);
+        
+        let dialog = Window::builder()
+            .title("🩺 Diagnostics")
+            .modal(true)
+            .transient_for(&window_clone)
+            .default_width(700)
+            .default_height(550)
+            .build();
+        
+        let content = Box::new(Orientation::Vertical, 12);
+        content.set_margin_start(20);
+        content.set_margin_end(20);
+        content.set_margin_top(20);
+        content.set_margin_bottom(20);
+        
+        let env_label = Label::new(None);
+        env_label.set_xalign(0.0);
+        env_label.set_selectable(true);
+        env_label.set_wrap(true);
+        env_label.set_markup(&environment);
+        content.append(&env_label);
         content.append(&Separator::new(Orientation::Horizontal));
         
-        // Auto-scan checkbox
-        let auto_scan_check = gtk::CheckButton::with_label("Automatically scan directories on startup");
-        auto_scan_check.set_active(current_config.auto_scan_on_startup);
-        content.append(&auto_scan_check);
+        let log_label = Label::new(None);
+        log_label.set_xalign(0.0);
+        log_label.set_markup("<b>Recent log messages:</b>");
+        content.append(&log_label);
+        
+        let log_text = read_log_tail(200);
+        let log_view = gtk::TextView::new();
+        log_view.set_editable(false);
+        log_view.set_cursor_visible(false);
+        log_view.set_monospace(true);
+        log_view.buffer().set_text(if log_text.is_empty() { "No log messages yet." } else { &log_text });
+        let log_scroll = ScrolledWindow::new();
+        log_scroll.set_vexpand(true);
+        log_scroll.set_child(Some(&log_view));
+        content.append(&log_scroll);
+        
+        let close_button = Button::with_label("Close");
+        close_button.set_halign(Align::End);
+        content.append(&close_button);
+        
+        let dialog_clone = dialog.clone();
+        close_button.connect_clicked(move |_| {
+            dialog_clone.close();
+        });
+        
+        dialog.set_child(Some(&content));
+        dialog.present();
+        
+        // Scroll to the newest messages
+        let mut end = log_view.buffer().end_iter();
+        log_view.scroll_to_iter(&mut end, 0.0, false, 0.0, 0.0);
+    });
 
-        // Buttons
-        let button_box = Box::new(Orientation::Horizontal, 8);
-        button_box.set_halign(gtk::Align::End);
-        let cancel_btn = Button::with_label("Cancel");
-        let save_btn = Button::with_label("Save");
-        button_box.append(&cancel_btn);
-        button_box.append(&save_btn);
-        content.append(&button_box);
+    // Rescan Local Files - refresh file sizes and subtitle sidecars without TMDB calls
+    let db_clone = db.clone();
+    let status_bar_clone = status_bar.clone();
+    rescan_local_action.connect_activate(move |_, _| {
+        let (checked, updated) = db_clone.borrow_mut().refresh_local_file_info();
+        status_bar_clone.set_text(&format!("Checked {} files, updated {} movies", checked, updated));
+    });
 
-        dialog.set_child(Some(&content));
+    // Mark Shown as Watched - bulk-mark every movie in the current filter/search
+    let window_clone = window.clone();
+    let db_clone = db.clone();
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let status_bar_clone = status_bar.clone();
+    let poster_cache_clone = poster_cache.clone();
+    let is_grid_view_clone = is_grid_view.clone();
+    let current_filters_clone = current_filters.clone();
+    mark_shown_watched_action.connect_activate(move |_, _| {
+        let shown: Vec<u32> = db_clone.borrow()
+            .filtered_movies(&current_filters_clone())
+            .iter()
+            .filter(|m| !m.watched)
+            .map(|m| m.id)
+            .collect();
+        if shown.is_empty() {
+            status_bar_clone.set_text("Every movie shown is already watched");
+            return;
+        }
+        
+        let confirmed = confirm(
+            &window_clone,
+            "Mark Shown as Watched",
+            &format!("Mark {} unwatched movies in the current list as watched?", shown.len()),
+            "Mark Watched",
+        );
+        
+        let db_clone2 = db_clone.clone();
+        let list_box_clone2 = list_box_clone.clone();
+        let grid_flow_clone2 = grid_flow_clone.clone();
+        let status_bar_clone2 = status_bar_clone.clone();
+        let poster_cache_clone2 = poster_cache_clone.clone();
+        let is_grid_view_clone2 = is_grid_view_clone.clone();
+        let current_filters_clone2 = current_filters_clone.clone();
+        glib::spawn_future_local(async move {
+            if confirmed.await {
+                let marked = db_clone2.borrow_mut().mark_watched(&shown);
+                let is_grid = *is_grid_view_clone2.borrow();
+                refresh_movie_list(&list_box_clone2, &grid_flow_clone2, is_grid, &db_clone2, &current_filters_clone2(), &poster_cache_clone2);
+                status_bar_clone2.set_text(&format!("Marked {} movies as watched", marked));
+            }
+        });
+    });
 
-        let dialog_clone = dialog.clone();
-        cancel_btn.connect_clicked(move |_| {
-            dialog_clone.close();
+    // Prune Missing button - bulk-remove entries whose files were deleted from disk
+    let window_clone = window.clone();
+    let db_clone = db.clone();
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let status_bar_clone = status_bar.clone();
+    let poster_cache_clone = poster_cache.clone();
+    let is_grid_view_clone = is_grid_view.clone();
+    let current_filters_clone = current_filters.clone();
+    prune_missing_action.connect_activate(move |_, _| {
+        let missing_ids = db_clone.borrow().missing_file_ids();
+        if missing_ids.is_empty() {
+            status_bar_clone.set_text("No movies with missing files");
+            return;
+        }
+        
+        let confirmed = confirm(
+            &window_clone,
+            "Prune Missing Files",
+            &format!(
+                "{} movies point to files that no longer exist.\n\nRemove them from the database? Movies without a linked file are kept.",
+                missing_ids.len()
+            ),
+            "Remove All",
+        );
+        
+        let db_clone2 = db_clone.clone();
+        let list_box_clone2 = list_box_clone.clone();
+        let grid_flow_clone2 = grid_flow_clone.clone();
+        let status_bar_clone2 = status_bar_clone.clone();
+        let poster_cache_clone2 = poster_cache_clone.clone();
+        let is_grid_view_clone2 = is_grid_view_clone.clone();
+        let current_filters_clone2 = current_filters_clone.clone();
+        glib::spawn_future_local(async move {
+            if confirmed.await {
+                let removed = db_clone2.borrow_mut().delete_many(&missing_ids);
+                for id in &missing_ids {
+                    poster_cache_clone2.borrow_mut().remove(id);
+                }
+                let is_grid = *is_grid_view_clone2.borrow();
+                refresh_movie_list(&list_box_clone2, &grid_flow_clone2, is_grid, &db_clone2, &current_filters_clone2(), &poster_cache_clone2);
+                status_bar_clone2.set_text(&format!("Removed {} movies with missing files", removed));
+            }
         });
+    });
 
-        let dialog_clone = dialog.clone();
+    // Rescan Library - walk the scan directories, offer to prune movies whose files are
+    // gone, then fetch metadata for the new files
+    let window_clone = window.clone();
+    let db_clone = db.clone();
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let status_bar_clone = status_bar.clone();
+    let poster_cache_clone = poster_cache.clone();
+    let is_grid_view_clone = is_grid_view.clone();
+    let current_filters_clone = current_filters.clone();
+    let refresh_view_clone = refresh_view.clone();
+    let busy_clone = busy.clone();
+    let set_busy_clone = set_busy.clone();
+    rescan_library_action.connect_activate(move |_, _| {
+        let config = load_config().unwrap_or_default();
+        if config.scan_directories.is_empty() {
+            status_bar_clone.set_text("No scan directories configured - add some in Settings");
+            return;
+        }
+        if busy_clone.get() {
+            status_bar_clone.set_text(BUSY_MESSAGE);
+            return;
+        }
+        let (available, unavailable): (Vec<String>, Vec<String>) = config.scan_directories.iter()
+            .cloned()
+            .partition(|dir| Path::new(dir).is_dir());
+        status_bar_clone.set_text("Rescanning library...");
+        set_busy_clone(true);
+        
+        let (sender, receiver) = async_channel::bounded::<HashSet<String>>(1);
+        let directories = available.clone();
+        let video_extensions = config.video_extensions.clone();
+        let skip_rules = ScanSkipRules::from_config(&config);
+        std::thread::spawn(move || {
+            let mut entries = Vec::new();
+            for dir in &directories {
+                scan_directory_recursive(Path::new(dir), &video_extensions, &skip_rules, &mut entries);
+            }
+            let _ = sender.send_blocking(entries.into_iter().map(|(_, _, path, _)| path).collect());
+        });
+        
+        let window_clone2 = window_clone.clone();
         let db_clone2 = db_clone.clone();
+        let list_box_clone2 = list_box_clone.clone();
+        let grid_flow_clone2 = grid_flow_clone.clone();
         let status_bar_clone2 = status_bar_clone.clone();
-        save_btn.connect_clicked(move |_| {
-            let new_key = api_entry.text().to_string();
-            if !new_key.is_empty() {
-                // Update database API key
-                db_clone2.borrow_mut().tmdb_api_key = new_key.clone();
-                
-                // Parse year cutoff
-                let year_cutoff = year_entry.text()
-                    .to_string()
-                    .parse::<i32>()
-                    .unwrap_or(1966);
-                
-                // Save to config
-                let config = Config {
-                    tmdb_api_key: new_key,
-                    scan_directories: dirs_list.borrow().clone(),
-                    auto_scan_on_startup: auto_scan_check.is_active(),
-                    year_cutoff,
+        let poster_cache_clone2 = poster_cache_clone.clone();
+        let is_grid_view_clone2 = is_grid_view_clone.clone();
+        let current_filters_clone2 = current_filters_clone.clone();
+        let refresh_view_clone2 = refresh_view_clone.clone();
+        let set_busy = set_busy_clone.clone();
+        glib::spawn_future_local(async move {
+            let Ok(found_paths) = receiver.recv().await else {
+                set_busy(false);
+                return;
+            };
+            let report = db_clone2.borrow().reconcile(&found_paths);
+            
+            let mut dropped_copies = 0;
+            if !report.missing_copies.is_empty() {
+                let listed: Vec<String> = {
+                    let db = db_clone2.borrow();
+                    report.missing_copies.iter()
+                        .map(|(id, path)| format!("{}: {}", db.movies.get(id).map_or("", |m| m.title.as_str()), path))
+                        .collect()
                 };
-                if let Err(e) = save_config(&config) {
-                    status_bar_clone2.set_text(&format!("Error saving config: {}", e));
-                } else {
-                    status_bar_clone2.set_text("Settings saved successfully");
+                let mut detail = format!(
+                    "{} extra copies of movies still in the library are no longer on disk:\n\n{}",
+                    listed.len(),
+                    listed.iter().take(10).cloned().collect::<Vec<_>>().join("\n")
+                );
+                if listed.len() > 10 {
+                    detail.push_str(&format!("\n…and {} more", listed.len() - 10));
+                }
+                detail.push_str("\n\nRemove them from their movies?");
+                if !unavailable.is_empty() {
+                    detail.push_str(&format!(
+                        "\n\nThese scan directories are unavailable, so copies stored there may only look missing:\n{}",
+                        unavailable.join("\n")
+                    ));
+                }
+                if confirm(&window_clone2, "Rescan Library", &detail, "Remove Copies").await {
+                    dropped_copies = db_clone2.borrow_mut().remove_copies(&report.missing_copies);
                 }
             }
-            dialog_clone.close();
+            
+            let mut removed = 0;
+            if !report.missing_ids.is_empty() {
+                let mut detail = format!(
+                    "{} movies point to files that no longer exist.\n\nRemove them from the database?",
+                    report.missing_ids.len()
+                );
+                if !unavailable.is_empty() {
+                    detail.push_str(&format!(
+                        "\n\nThese scan directories are unavailable, so movies stored there may only look missing:\n{}",
+                        unavailable.join("\n")
+                    ));
+                }
+                if confirm(&window_clone2, "Rescan Library", &detail, "Remove").await {
+                    removed = db_clone2.borrow_mut().delete_many(&report.missing_ids);
+                    for id in &report.missing_ids {
+                        poster_cache_clone2.borrow_mut().remove(id);
+                    }
+                }
+            }
+            let is_grid = *is_grid_view_clone2.borrow();
+            refresh_movie_list(&list_box_clone2, &grid_flow_clone2, is_grid, &db_clone2, &current_filters_clone2(), &poster_cache_clone2);
+            
+            let summary = format!(
+                "Rescan: {} unchanged, {} new files, {} movies removed, {} missing copies dropped",
+                report.unchanged, report.new_files.len(), removed, dropped_copies
+            );
+            if report.new_files.is_empty() {
+                status_bar_clone2.set_text(&summary);
+                set_busy(false);
+                return;
+            }
+            
+            status_bar_clone2.set_text(&format!("{} - fetching metadata for the new files...", summary));
+            let config = load_config().unwrap_or_default();
+            let scan = spawn_scan_worker(ScanJob::from_config(&config, &db_clone2.borrow(), Vec::new(), report.new_files));
+            handle_scan_messages(scan, available, db_clone2.clone(), status_bar_clone2, refresh_view_clone2, move || set_busy(false));
         });
-
-        dialog.present();
     });
 
-    
-    // Statistics button
-    let db_clone = db.clone();
+    // Find Duplicates - pick one movie to keep from each group of duplicates
     let window_clone = window.clone();
-    stats_button.connect_clicked(move |_| {
-        let db = db_clone.borrow();
-        let movies = db.list_all();
-        
-        if movies.is_empty() {
-            drop(db);
-            let dialog = gtk::AlertDialog::builder()
-                .message("No Statistics Available")
-                .detail("Add some movies to your database first!")
-                .buttons(vec!["OK"])
-                .build();
-            dialog.show(Some(&window_clone));
+    let db_clone = db.clone();
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let status_bar_clone = status_bar.clone();
+    let poster_cache_clone = poster_cache.clone();
+    let is_grid_view_clone = is_grid_view.clone();
+    let current_filters_clone = current_filters.clone();
+    find_duplicates_action.connect_activate(move |_, _| {
+        if db_clone.borrow().find_duplicates().is_empty() {
+            status_bar_clone.set_text("No duplicate movies found");
+            return;
+        }
+        let list_box_clone2 = list_box_clone.clone();
+        let grid_flow_clone2 = grid_flow_clone.clone();
+        let status_bar_clone2 = status_bar_clone.clone();
+        let poster_cache_clone2 = poster_cache_clone.clone();
+        let is_grid_view_clone2 = is_grid_view_clone.clone();
+        let current_filters_clone2 = current_filters_clone.clone();
+        let db_clone2 = db_clone.clone();
+        show_duplicates_dialog(window_clone.upcast_ref(), &db_clone, move |removed| {
+            let is_grid = *is_grid_view_clone2.borrow();
+            refresh_movie_list(&list_box_clone2, &grid_flow_clone2, is_grid, &db_clone2, &current_filters_clone2(), &poster_cache_clone2);
+            status_bar_clone2.set_text(&format!("Removed {} duplicate movies", removed));
+        });
+    });
+
+    // Clean Poster Cache - delete poster files no movie uses any more (left by deletes and
+    // rematches). Disabled while scans run, as their new posters aren't referenced yet.
+    let window_clone = window.clone();
+    let db_clone = db.clone();
+    let status_bar_clone = status_bar.clone();
+    clean_posters_action.connect_activate(move |_, _| {
+        let orphans = db_clone.borrow().orphaned_posters();
+        if orphans.is_empty() {
+            status_bar_clone.set_text("Poster cache is clean - every poster file is in use");
             return;
         }
+        let total_bytes: u64 = orphans.iter().map(|(_, bytes)| bytes).sum();
+        let confirmed = confirm(
+            &window_clone,
+            "Clean Poster Cache",
+            &format!(
+                "Delete {} poster files ({:.1} MB) that no movie in the library uses?",
+                orphans.len(),
+                total_bytes as f64 / (1024.0 * 1024.0)
+            ),
+            "Delete Files",
+        );
         
-        // Calculate statistics
-        let total_movies = movies.len();
-        let total_runtime: u32 = movies.iter().map(|m| m.runtime as u32).sum();
-        let avg_runtime = if total_movies > 0 { total_runtime / total_movies as u32 } else { 0 };
-        
-        let avg_rating: f32 = if total_movies > 0 {
-            movies.iter().map(|m| m.rating).sum::<f32>() / total_movies as f32
-        } else {
-            0.0
-        };
-        
-        let oldest_year = movies.iter().filter(|m| m.year > 0).map(|m| m.year).min().unwrap_or(0);
-        let newest_year = movies.iter().map(|m| m.year).max().unwrap_or(0);
-        
-        // Genre breakdown
-        let mut genre_counts: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
-        for movie in &movies {
-            for genre in &movie.genre {
-                *genre_counts.entry(genre.clone()).or_insert(0) += 1;
+        let status_bar_clone2 = status_bar_clone.clone();
+        glib::spawn_future_local(async move {
+            if !confirmed.await {
+                return;
             }
-        }
-        let mut genre_list: Vec<(String, usize)> = genre_counts.into_iter().collect();
-        genre_list.sort_by(|a, b| b.1.cmp(&a.1));
-        
-        // Decade breakdown
-        let mut decade_counts: std::collections::HashMap<u16, usize> = std::collections::HashMap::new();
-        for movie in &movies {
-            if movie.year > 0 {
-                let decade = (movie.year / 10) * 10;
-                *decade_counts.entry(decade).or_insert(0) += 1;
+            let mut removed = 0;
+            let mut freed: u64 = 0;
+            for (path, bytes) in &orphans {
+                match std::fs::remove_file(path) {
+                    Ok(()) => {
+                        removed += 1;
+                        freed += bytes;
+                    }
+                    Err(e) => log_warn!("Warning: Failed to delete {}: {}", path.display(), e),
+                }
             }
-        }
-        let mut decade_list: Vec<(u16, usize)> = decade_counts.into_iter().collect();
-        decade_list.sort_by(|a, b| a.0.cmp(&b.0));
-        
-        // Top rated movies
-        let mut top_rated = movies.clone();
-        top_rated.sort_by(|a, b| b.rating.partial_cmp(&a.rating).unwrap_or(std::cmp::Ordering::Equal));
-        let top_100: Vec<String> = top_rated.iter()
-            .take(100)
-            .map(|m| format!("{} ({}) - ⭐ {:.1}", m.title, m.year, m.rating))
+            status_bar_clone2.set_text(&format!(
+                "Deleted {} unused posters, freed {:.1} MB",
+                removed,
+                freed as f64 / (1024.0 * 1024.0)
+            ));
+        });
+    });
+
+    // Fix Missing Posters - report movies without a usable poster and offer to fetch them
+    let window_clone = window.clone();
+    let db_clone = db.clone();
+    let list_box_clone = list_box.clone();
+    let grid_flow_clone = grid_flow.clone();
+    let status_bar_clone = status_bar.clone();
+    let poster_cache_clone = poster_cache.clone();
+    missing_posters_action.connect_activate(move |_, _| {
+        let mut missing: Vec<Movie> = db_clone.borrow().movies.values()
+            .filter(|m| !has_usable_poster(m))
+            .cloned()
             .collect();
+        missing.sort_by(|a, b| a.title.cmp(&b.title));
         
-        drop(db);
-        
-        // Create statistics dialog
-        let stats_dialog = Window::builder()
-            .title("📊 Database Statistics")
+        let dialog = Window::builder()
+            .title("🖼️ Fix Missing Posters")
             .modal(true)
             .transient_for(&window_clone)
-            .default_width(600)
+            .default_width(500)
             .default_height(500)
             .build();
         
-        let scroll = ScrolledWindow::new();
-        scroll.set_vexpand(true);
-        
-        let stats_box = Box::new(Orientation::Vertical, 12);
-        stats_box.set_margin_start(20);
-        stats_box.set_margin_end(20);
-        stats_box.set_margin_top(20);
-        stats_box.set_margin_bottom(20);
-        
-        // Overview section
-        let overview_label = Label::new(None);
-        overview_label.set_xalign(0.0);
-        overview_label.set_markup(&format!(
-            "<span size='large' weight='bold'>📊 Overview</span>\n\n\
-            <b>Total Movies:</b> {}\n\
-            <b>Average Rating:</b> ⭐ {:.2}/10\n\
-            <b>Total Runtime:</b> {} hours ({} minutes)\n\
-            <b>Average Runtime:</b> {} minutes\n\
-            <b>Year Range:</b> {} - {}",
-            total_movies,
-            avg_rating,
-            total_runtime / 60,
-            total_runtime,
-            avg_runtime,
-            oldest_year,
-            newest_year
-        ));
-        stats_box.append(&overview_label);
-        stats_box.append(&Separator::new(Orientation::Horizontal));
-        
-        // Top rated section
-        let top_rated_label = Label::new(None);
-        top_rated_label.set_xalign(0.0);
-        top_rated_label.set_markup(&format!(
-            "<span size='large' weight='bold'>🏆 Top 100 Rated Movies</span>\n\n{}",
-            top_100.join("\n")
-        ));
-        stats_box.append(&top_rated_label);
-        stats_box.append(&Separator::new(Orientation::Horizontal));
-        
-        // Genre breakdown
-        let genre_text = genre_list.iter()
-            .take(10)
-            .map(|(genre, count)| format!("<b>{}:</b> {} movies", genre, count))
-            .collect::<Vec<String>>()
-            .join("\n");
+        let content = Box::new(Orientation::Vertical, 12);
+        content.set_margin_start(20);
+        content.set_margin_end(20);
+        content.set_margin_top(20);
+        content.set_margin_bottom(20);
         
-        let genre_label = Label::new(None);
-        genre_label.set_xalign(0.0);
-        genre_label.set_markup(&format!(
-            "<span size='large' weight='bold'>🎭 Top Genres</span>\n\n{}",
-            genre_text
-        ));
-        stats_box.append(&genre_label);
-        stats_box.append(&Separator::new(Orientation::Horizontal));
+        let summary_label = Label::new(None);
+        summary_label.set_xalign(0.0);
+        summary_label.set_markup(&format!("<b>{} movies without a usable poster</b>", missing.len()));
+        content.append(&summary_label);
         
-        // Decade breakdown
-        let decade_text = decade_list.iter()
-            .map(|(decade, count)| format!("<b>{}s:</b> {} movies", decade, count))
-            .collect::<Vec<String>>()
-            .join("\n");
+        let missing_list = ListBox::new();
+        missing_list.set_selection_mode(gtk::SelectionMode::None);
+        for movie in &missing {
+            let source = if !movie.poster_url.is_empty() {
+                "stored poster URL"
+            } else if movie.tmdb_id != 0 {
+                "look up from TMDB"
+            } else {
+                "no TMDB match"
+            };
+            let label = Label::new(Some(&format!("{} ({}) - {}", movie.title, movie.year, source)));
+            label.set_xalign(0.0);
+            label.set_margin_start(6);
+            label.set_margin_top(4);
+            label.set_margin_bottom(4);
+            missing_list.append(&label);
+        }
+        let scroll = ScrolledWindow::new();
+        scroll.set_vexpand(true);
+        scroll.set_child(Some(&missing_list));
+        content.append(&scroll);
         
-        let decade_label = Label::new(None);
-        decade_label.set_xalign(0.0);
-        decade_label.set_markup(&format!(
-            "<span size='large' weight='bold'>📅 By Decade</span>\n\n{}",
-            decade_text
-        ));
-        stats_box.append(&decade_label);
+        let progress_bar = gtk::ProgressBar::new();
+        progress_bar.set_show_text(true);
+        progress_bar.set_visible(false);
+        content.append(&progress_bar);
         
-        // Close button
+        let button_box = Box::new(Orientation::Horizontal, 8);
+        button_box.set_halign(Align::End);
         let close_button = Button::with_label("Close");
-        close_button.set_halign(Align::End);
-        stats_box.append(&close_button);
+        let download_button = Button::with_label("Download Posters");
+        download_button.set_sensitive(missing.iter().any(|m| !m.poster_url.is_empty() || m.tmdb_id != 0));
+        button_box.append(&close_button);
+        button_box.append(&download_button);
+        content.append(&button_box);
         
-        let stats_dialog_clone = stats_dialog.clone();
+        dialog.set_child(Some(&content));
+        
+        let dialog_clone = dialog.clone();
         close_button.connect_clicked(move |_| {
-            stats_dialog_clone.close();
+            dialog_clone.close();
         });
         
-        scroll.set_child(Some(&stats_box));
-        stats_dialog.set_child(Some(&scroll));
-        stats_dialog.present();
+        let db_clone2 = db_clone.clone();
+        let list_box_clone2 = list_box_clone.clone();
+        let grid_flow_clone2 = grid_flow_clone.clone();
+        let status_bar_clone2 = status_bar_clone.clone();
+        let poster_cache_clone2 = poster_cache_clone.clone();
+        download_button.connect_clicked(move |button| {
+            button.set_sensitive(false);
+            progress_bar.set_visible(true);
+            progress_bar.set_fraction(0.0);
+            
+            let candidates: Vec<Movie> = missing.iter()
+                .filter(|m| !m.poster_url.is_empty() || m.tmdb_id != 0)
+                .cloned()
+                .collect();
+            let total_count = candidates.len();
+            let api_key = db_clone2.borrow().tmdb_api_key.clone();
+            let posters_dir = db_clone2.borrow().posters_dir.clone();
+            let max_concurrent_requests = load_config().unwrap_or_default().max_concurrent_requests;
+            
+            let (sender, receiver) = async_channel::unbounded::<PosterProgress>();
+            
+            std::thread::spawn(move || {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .unwrap();
+                
+                runtime.block_on(async {
+                    let client = reqwest::Client::new();
+                    let limiter = tmdb_limiter(max_concurrent_requests);
+                    let downloads = candidates.iter().map(|movie| {
+                        let client = &client;
+                        let limiter = &limiter;
+                        let api_key = &api_key;
+                        let posters_dir = &posters_dir;
+                        let sender = sender.clone();
+                        async move {
+                            let poster = fetch_missing_poster_async(client, limiter, movie, api_key, posters_dir).await;
+                            let _ = sender.send_blocking(PosterProgress::Checked(movie.id, poster));
+                        }
+                    });
+                    futures::future::join_all(downloads).await;
+                    let _ = sender.send_blocking(PosterProgress::Done);
+                });
+            });
+            
+            // Each poster is shown in its row as it arrives; the database is saved once at the end
+            let progress_bar = progress_bar.clone();
+            let summary_label = summary_label.clone();
+            let db_clone3 = db_clone2.clone();
+            let list_box_clone3 = list_box_clone2.clone();
+            let grid_flow_clone3 = grid_flow_clone2.clone();
+            let status_bar_clone3 = status_bar_clone2.clone();
+            let poster_cache_clone3 = poster_cache_clone2.clone();
+            glib::spawn_future_local(async move {
+                let mut checked = 0;
+                let mut downloaded = 0;
+                let mut finished = false;
+                while let Ok(message) = receiver.recv().await {
+                    let (movie_id, poster) = match message {
+                        PosterProgress::Checked(movie_id, poster) => (movie_id, poster),
+                        PosterProgress::Done => {
+                            finished = true;
+                            break;
+                        }
+                    };
+                    checked += 1;
+                    if let Some((poster_url, poster_path)) = poster {
+                        downloaded += 1;
+                        if let Some(movie) = db_clone3.borrow_mut().movie_mut(movie_id) {
+                            movie.poster_url = poster_url;
+                            movie.poster_path = poster_path;
+                        }
+                        poster_cache_clone3.borrow_mut().remove(&movie_id);
+                        refresh_movie_in_views(&list_box_clone3, &grid_flow_clone3, &db_clone3, movie_id, &poster_cache_clone3);
+                    }
+                    let status = format!("Downloaded {} posters ({}/{} checked)", downloaded, checked, total_count);
+                    progress_bar.set_fraction(checked as f64 / total_count.max(1) as f64);
+                    progress_bar.set_text(Some(&status));
+                    status_bar_clone3.set_text(&status);
+                }
+                
+                // Without Done the worker stopped before checking every movie
+                let summary = if finished {
+                    format!("Poster download complete! {} of {} downloaded", downloaded, total_count)
+                } else {
+                    format!("Poster download stopped early - {} of {} downloaded", downloaded, total_count)
+                };
+                summary_label.set_markup(&format!("<b>{}</b>", escape_markup(&summary)));
+                status_bar_clone3.set_text(&summary);
+                if downloaded > 0 {
+                    let db = db_clone3.borrow();
+                    db.invalidate_result_cache();
+                    if let Err(e) = db.save_to_file() {
+                        log_warn!("Warning: Failed to save downloaded posters: {}", e);
+                    }
+                }
+            });
+        });
+        
+        dialog.present();
     });
 }
 
+// `--scan <dir>`: scan and fetch metadata on this thread, save, print a summary and
+// return the process exit code. No GTK UI is built.
+fn run_headless_scan(scan_dir: &str, overrides: &CliOverrides) -> i32 {
+    let config = load_config().unwrap_or_default();
+    set_tmdb_language(&config.tmdb_language);
+    let api_key = overrides.api_key.clone().or_else(env_api_key).unwrap_or(config.tmdb_api_key.clone());
+    if api_key.is_empty() {
+        eprintln!("No API key available. Set TMDB_API_KEY, pass --api-key, or save one in Settings.");
+        return 1;
+    }
+    
+    let scan_path = match std::fs::canonicalize(scan_dir) {
+        Ok(path) if path.is_dir() => path.to_string_lossy().to_string(),
+        _ => {
+            eprintln!("Not a directory: {}", scan_dir);
+            return 1;
+        }
+    };
+    
+    let data_dir = get_data_dir();
+    std::fs::create_dir_all(&data_dir).expect("Could not create data directory");
+    if overrides.data_file.is_none() {
+        migrate_cwd_data(&data_dir);
+    }
+    let db_path = resolve_data_file(overrides, &data_dir);
+    let posters_dir = data_dir.join("posters").to_string_lossy().to_string();
+    std::fs::create_dir_all(&posters_dir).expect("Could not create posters directory");
+    
+    let mut db = MovieDatabase::new(&db_path, &posters_dir, &api_key);
+    let job = ScanJob::from_config(&config, &db, vec![scan_path.clone()], Vec::new());
+    
+    let (sender, receiver) = async_channel::unbounded::<(String, String, Option<Movie>)>();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    runtime.block_on(run_scan_job(job, &AtomicBool::new(false), sender));
+    
+    let mut added = 0;
+    let mut without_metadata = 0;
+    while let Ok((msg_type, status, movie_opt)) = receiver.try_recv() {
+        match msg_type.as_str() {
+            "status" => println!("{}", status),
+            "add" => {
+                println!("{}", status);
+                if let Some(movie) = movie_opt {
+                    let has_metadata = movie.tmdb_id != 0;
+                    if db.add_movie(movie).is_some() {
+                        added += 1;
+                        if !has_metadata {
+                            without_metadata += 1;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    record_scan_completed(&[scan_path]);
+    println!(
+        "Scan complete: added {} movies ({} without metadata), {} total in {}",
+        added, without_metadata, db.movies.len(), db_path
+    );
+    0
+}
+
 fn main() {
+    let (overrides, gtk_args) = parse_cli_overrides(std::env::args().collect());
+    if let Some(scan_dir) = overrides.scan_dir.clone() {
+        std::process::exit(run_headless_scan(&scan_dir, &overrides));
+    }
+    let _ = CLI_OVERRIDES.set(overrides);
+
     let app = Application::builder()
         .application_id("com.example.moviedb")
         .build();
 
     app.connect_activate(build_ui);
 
-    app.run();
+    app.run_with_args(&gtk_args);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An empty library in its own temp directory, saved as a JSON database object
+    fn test_db(name: &str) -> MovieDatabase {
+        let dir = std::env::temp_dir().join(format!("movie_db_test_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        MovieDatabase::open(
+            dir.join("movies.json").to_str().unwrap(),
+            dir.join("posters").to_str().unwrap(),
+            "",
+            String::from("database"),
+        )
+    }
+
+    fn test_movie(title: &str, tmdb_id: u32, path: &str) -> Movie {
+        Movie {
+            title: title.to_string(),
+            tmdb_id,
+            file_paths: vec![path.to_string()],
+            ..Default::default()
+        }
+    }
+
+    // Two scans of overlapping folders feeding one library, then list and save timings for
+    // the result. Slow, so run it with `cargo test -- --ignored`.
+    #[test]
+    #[ignore]
+    fn overlapping_scans_of_a_large_library() {
+        let mut db = test_db("stress");
+        db.storage_format = String::from("sqlite");
+        let scan_a = (0..5000).map(|i| test_movie(&format!("Movie {}", i), i + 1, &format!("/films/{}.mkv", i)));
+        let scan_b = (2500..7500).map(|i| test_movie(&format!("Movie {}", i), i + 1, &format!("/films/{}.mkv", i)));
+        let started = std::time::Instant::now();
+        for (a, b) in scan_a.zip(scan_b) {
+            db.add_movie(a);
+            db.add_movie(b);
+        }
+        eprintln!("added {} movies in {:?}", db.movies.len(), started.elapsed());
+        assert_eq!(db.movies.len(), 7500);
+        assert_eq!(db.existing_file_paths().len(), 7500);
+
+        let filters = ListFilters {
+            search_query: String::from("movie 1"),
+            sort_by: String::from("Year (Newest)"),
+            ..Default::default()
+        };
+        let started = std::time::Instant::now();
+        let shown = db.filtered_movies(&filters);
+        eprintln!("filtered to {} movies in {:?}", shown.len(), started.elapsed());
+        assert!(!shown.is_empty());
+
+        db.changed_ids.borrow_mut().extend(db.movies.keys());
+        let started = std::time::Instant::now();
+        db.save_to_file().unwrap();
+        eprintln!("saved every movie in {:?}", started.elapsed());
+        assert_eq!(MovieDatabase::open_as_stored(&db.data_file, &db.posters_dir, "").movies.len(), 7500);
+    }
+
+    #[test]
+    fn clean_movie_title_strips_release_names() {
+        let cases = [
+            // Release tags, resolutions and "-GROUP" suffixes
+            ("The.Matrix.1999.1080p.BluRay.x264-GROUP", "The Matrix", Some(1999)),
+            ("Mad.Max.Fury.Road.2015.WEB-DL.x265-RARBG", "Mad Max Fury Road", Some(2015)),
+            ("Her.720p.WEBRip", "Her", None),
+            ("[YTS] Inception 2010 BRRip", "Inception", Some(2010)),
+            ("2001 A Space Odyssey (1968) [1080p]", "2001 A Space Odyssey", Some(1968)),
+            // Years: only a plausible one after some title text is split off
+            ("Dune (2021)", "Dune", Some(2021)),
+            ("1917.2019.720p", "1917", Some(2019)),
+            ("Blade_Runner_2049_2160p_UHD", "Blade Runner 2049", None),
+            ("Heat 1995 - Director's Cut", "Heat", Some(1995)),
+            // Separators and names with nothing to strip
+            ("Alien", "Alien", None),
+            ("The_Thing", "The Thing", None),
+        ];
+        for (raw, title, year) in cases {
+            assert_eq!(clean_movie_title(raw), (title.to_string(), year), "{}", raw);
+        }
+    }
+
+    #[test]
+    fn merge_refreshed_movie_keeps_user_data_and_takes_tmdb_fields() {
+        let old = Movie {
+            id: 7,
+            title: String::from("Alien (Director's Cut)"),
+            description: String::from("Old plot"),
+            rating: 7.9,
+            runtime: 117,
+            edited_fields: vec![String::from("title")],
+            favorite: true,
+            watched: true,
+            my_rating: Some(9.5),
+            tags: vec![String::from("rewatch")],
+            date_added: 1_600_000_000,
+            ..test_movie("Alien", 348, "/films/alien.mkv")
+        };
+        let refreshed = Movie {
+            description: String::from("New plot"),
+            rating: 8.2,
+            runtime: 0,
+            ..test_movie("Alien", 348, "/films/alien.mkv")
+        };
+        let merged = merge_refreshed_movie(&old, refreshed);
+
+        // Edited in Edit Metadata, or the user's own
+        assert_eq!(merged.id, 7);
+        assert_eq!(merged.title, "Alien (Director's Cut)");
+        assert!(merged.favorite && merged.watched);
+        assert_eq!(merged.my_rating, Some(9.5));
+        assert_eq!(merged.tags, vec!["rewatch"]);
+        assert_eq!(merged.date_added, 1_600_000_000);
+        // Owned by TMDB, except where it has nothing to offer
+        assert_eq!(merged.description, "New plot");
+        assert_eq!(merged.rating, 8.2);
+        assert_eq!(merged.runtime, 117);
+    }
+
+    #[test]
+    fn merge_refreshed_movie_drops_edits_for_a_different_match() {
+        let old = Movie {
+            edited_fields: vec![String::from("title")],
+            ..test_movie("Alien (edited)", 348, "/films/alien.mkv")
+        };
+        let merged = merge_refreshed_movie(&old, test_movie("Aliens", 679, "/films/alien.mkv"));
+        assert_eq!(merged.title, "Aliens");
+        assert!(merged.edited_fields.is_empty());
+    }
+
+    #[test]
+    fn wildcard_matches_whole_names() {
+        assert!(wildcard_matches("sample", "sample"));
+        assert!(!wildcard_matches("sample", "samples"));
+        assert!(wildcard_matches("extras*", "extras (bonus)"));
+        assert!(wildcard_matches(".*", ".hidden"));
+        assert!(!wildcard_matches(".*", "visible"));
+        assert!(wildcard_matches("*feature*", "behind the featurettes"));
+        assert!(wildcard_matches("cd?", "cd1"));
+        assert!(!wildcard_matches("cd?", "cd10"));
+        assert!(wildcard_matches("*", ""));
+        assert!(!wildcard_matches("?", ""));
+    }
+
+    #[test]
+    fn add_movie_rejects_a_file_already_in_the_library() {
+        let mut db = test_db("add_same_path");
+        assert!(db.add_movie(test_movie("Alien", 348, "/films/alien.mkv")).is_some());
+        assert!(db.add_movie(test_movie("Aliens", 679, "/films/alien.mkv")).is_none());
+        assert!(db.add_movie_allowing_duplicate(test_movie("Alien", 348, "/films/alien.mkv")).is_none());
+        assert_eq!(db.movies.len(), 1);
+    }
+
+    #[test]
+    fn add_movie_attaches_a_second_copy_of_the_same_tmdb_movie() {
+        let mut db = test_db("add_same_tmdb_id");
+        let id = db.add_movie(test_movie("Alien", 348, "/films/alien.mkv")).unwrap();
+        assert!(db.add_movie(test_movie("Alien", 348, "/backup/alien.mkv")).is_none());
+        assert_eq!(db.movies.len(), 1);
+        assert_eq!(db.movies[&id].file_paths, vec!["/films/alien.mkv", "/backup/alien.mkv"]);
+    }
+
+    #[test]
+    fn add_movie_allowing_duplicate_adds_a_second_entry() {
+        let mut db = test_db("add_anyway");
+        db.add_movie(test_movie("Alien", 348, "/films/alien.mkv")).unwrap();
+        assert!(db.add_movie_allowing_duplicate(test_movie("Alien", 348, "/films/alien-directors-cut.mkv")).is_some());
+        assert_eq!(db.movies.len(), 2);
+    }
+
+    #[test]
+    fn allocate_id_never_reuses_a_deleted_id() {
+        let mut db = test_db("allocate_id");
+        let first = db.add_movie(test_movie("Alien", 348, "/films/alien.mkv")).unwrap();
+        let second = db.add_movie(test_movie("Aliens", 679, "/films/aliens.mkv")).unwrap();
+        assert!(db.delete_movie(second));
+        let third = db.add_movie(test_movie("Alien 3", 8077, "/films/alien3.mkv")).unwrap();
+        assert!(third > second && second > first);
+    }
+
+    #[test]
+    fn allocate_id_survives_delete_and_reload() {
+        for format in ["sqlite", "database", "array", "lines"] {
+            let dir = std::env::temp_dir().join(format!("movie_db_test_reload_{}_{}", format, std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            create_dir_all(&dir).unwrap();
+            let data_file = dir.join("movies.db").to_string_lossy().to_string();
+            let posters_dir = dir.join("posters").to_string_lossy().to_string();
+
+            let mut db = MovieDatabase::open(&data_file, &posters_dir, "", format.to_string());
+            let oldest = db.add_movie(test_movie("Alien", 348, "/films/alien.mkv")).unwrap();
+            let kept = db.add_movie(test_movie("Aliens", 679, "/films/aliens.mkv")).unwrap();
+            let newest = db.add_movie(test_movie("Alien 3", 8077, "/films/alien3.mkv")).unwrap();
+            assert!(db.delete_movie(oldest));
+            assert!(db.delete_movie(newest));
+            db.save_to_file().unwrap();
+            drop(db);
+
+            let mut reloaded = MovieDatabase::open_as_stored(&data_file, &posters_dir, "");
+            assert_eq!(reloaded.storage_format, format);
+            assert_eq!(reloaded.movies.keys().copied().collect::<Vec<_>>(), vec![kept], "{}", format);
+            let added = reloaded.add_movie(test_movie("Prometheus", 70981, "/films/prometheus.mkv")).unwrap();
+            assert!(added != kept && added != oldest, "{}: got id {}", format, added);
+            // Only the database object and SQLite keep next_id; the movie-only formats
+            // can't know about an id deleted from the end
+            if matches!(format, "sqlite" | "database") {
+                assert!(added > newest, "{}: got id {}", format, added);
+            }
+        }
+    }
+
+    #[test]
+    fn undo_delete_restores_the_movie_under_its_old_id() {
+        let mut db = test_db("undo_delete");
+        let id = db.add_movie(test_movie("Alien", 348, "/films/alien.mkv")).unwrap();
+        assert!(db.delete_movie(id));
+        assert!(!db.movies.contains_key(&id));
+
+        assert_eq!(db.undo_delete(), Some((id, String::from("Alien"))));
+        assert_eq!(db.movies[&id].title, "Alien");
+        assert_eq!(db.undo_delete(), None);
+    }
+
+    #[test]
+    fn undo_delete_is_dropped_by_a_later_add() {
+        let mut db = test_db("undo_after_add");
+        let id = db.add_movie(test_movie("Alien", 348, "/films/alien.mkv")).unwrap();
+        db.delete_movie(id);
+        db.add_movie(test_movie("Aliens", 679, "/films/aliens.mkv")).unwrap();
+        assert_eq!(db.undo_delete(), None);
+    }
+
+    #[test]
+    fn undo_delete_is_dropped_by_a_later_edit() {
+        let mut db = test_db("undo_after_edit");
+        let kept = db.add_movie(test_movie("Alien", 348, "/films/alien.mkv")).unwrap();
+        let deleted = db.add_movie(test_movie("Aliens", 679, "/films/aliens.mkv")).unwrap();
+        db.delete_movie(deleted);
+        db.toggle_favorite(kept);
+        assert_eq!(db.undo_delete(), None);
+    }
+
+    #[test]
+    fn a_movie_with_one_copy_left_is_not_missing() {
+        let mut db = test_db("missing_copies");
+        let kept = Path::new(&db.data_file).with_file_name("alien.mkv").to_string_lossy().to_string();
+        std::fs::write(&kept, b"").unwrap();
+        let playable = db.add_movie(Movie { file_paths: vec![kept.clone(), String::from("/gone/alien.mkv")], ..test_movie("Alien", 348, "") }).unwrap();
+        let gone = db.add_movie(test_movie("Aliens", 679, "/gone/aliens.mkv")).unwrap();
+        db.add_movie(Movie { file_paths: Vec::new(), ..test_movie("Alien 3", 8077, "") }).unwrap();  // Watchlist entry
+
+        assert!(!no_file_left(&db.movies[&playable].file_paths));
+        assert_eq!(db.missing_file_ids(), vec![gone]);
+    }
+
+    #[test]
+    fn reconcile_asks_before_removing_missing_copies() {
+        let mut db = test_db("reconcile_copies");
+        let dir = Path::new(&db.data_file).parent().unwrap().to_path_buf();
+        let kept = dir.join("alien.mkv").to_string_lossy().to_string();
+        let gone = dir.join("alien-copy.mkv").to_string_lossy().to_string();
+        std::fs::write(&kept, b"").unwrap();
+        let id = db.add_movie(Movie { file_paths: vec![kept.clone(), gone.clone()], ..test_movie("Alien", 348, "") }).unwrap();
+
+        let report = db.reconcile(&HashSet::new());
+        assert_eq!(report.missing_copies, vec![(id, gone)]);
+        assert!(report.missing_ids.is_empty());
+        assert_eq!(db.movies[&id].file_paths.len(), 2);
+
+        assert_eq!(db.remove_copies(&report.missing_copies), 1);
+        assert_eq!(db.movies[&id].file_paths, vec![kept]);
+    }
+
+    #[test]
+    fn backup_archive_round_trip() {
+        let mut db = test_db("backup_source");
+        create_dir_all(&db.posters_dir).unwrap();
+        let poster = Path::new(&db.posters_dir).join("348.jpg");
+        std::fs::write(&poster, b"poster").unwrap();
+        let id = db.add_movie(Movie {
+            poster_path: poster.to_string_lossy().to_string(),
+            ..test_movie("Alien", 348, "/films/alien.mkv")
+        }).unwrap();
+        let archive = Path::new(&db.data_file).with_file_name("backup.zip");
+        let config = Config { tmdb_api_key: String::from("secret"), ..Default::default() };
+        assert_eq!(db.export_archive(&archive, Some(&config)).unwrap(), 1);
+
+        let mut restored = test_db("backup_target");
+        let (count, restored_config) = restored.import_archive(&archive).unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(restored.movies[&id].title, "Alien");
+        let restored_poster = Path::new(&restored.posters_dir).join("348.jpg");
+        assert_eq!(restored.movies[&id].poster_path, restored_poster.to_string_lossy());
+        assert_eq!(std::fs::read(&restored_poster).unwrap(), b"poster");
+        assert_eq!(restored_config.unwrap().tmdb_api_key, "");
+    }
+
+    #[test]
+    fn import_archive_rejects_a_file_that_is_not_a_backup() {
+        let mut db = test_db("backup_invalid");
+        let not_a_zip = Path::new(&db.data_file).with_file_name("notes.txt");
+        std::fs::write(&not_a_zip, b"not a zip").unwrap();
+        let result = db.import_archive(&not_a_zip);
+        assert!(result.is_err_and(|e| e.kind() == std::io::ErrorKind::InvalidData));
+    }
+
+    #[test]
+    fn copy_movie_to_keeps_the_target_library_format() {
+        let mut db = test_db("copy_source");
+        let id = db.add_movie(test_movie("Alien", 348, "/films/alien.mkv")).unwrap();
+        let mut target = test_db("copy_target");
+        target.storage_format = String::from("array");
+        target.add_movie(test_movie("Aliens", 679, "/films/aliens.mkv")).unwrap();
+
+        db.copy_movie_to(id, Path::new(&target.data_file)).unwrap();
+        assert_eq!(stored_format(&target.data_file), Some("array"));
+        assert_eq!(MovieDatabase::open_as_stored(&target.data_file, &target.posters_dir, "").movies.len(), 2);
+        assert!(db.copy_movie_to(id, Path::new(&db.data_file)).is_err());
+    }
+
+    #[test]
+    fn collapse_episodes_keeps_every_episode_file() {
+        let entries = vec![
+            (String::from("Dark"), None, String::from("/tv/Dark.S01E02.mkv"), MediaType::Tv),
+            (String::from("Alien"), Some(1979), String::from("/films/Alien.1979.mkv"), MediaType::Movie),
+            (String::from("Dark"), None, String::from("/tv/Dark.S01E01.mkv"), MediaType::Tv),
+        ];
+        let groups = collapse_episodes(entries);
+        assert_eq!(groups.len(), 2);
+        let show = groups.iter().find(|g| g.3 == MediaType::Tv).unwrap();
+        assert_eq!(show.2, vec!["/tv/Dark.S01E01.mkv", "/tv/Dark.S01E02.mkv"]);
+    }
+
+    #[test]
+    fn add_movie_adds_a_distinct_movie() {
+        let mut db = test_db("add_distinct");
+        let first = db.add_movie(test_movie("Alien", 348, "/films/alien.mkv")).unwrap();
+        let second = db.add_movie(test_movie("Aliens", 679, "/films/aliens.mkv")).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(db.movies.len(), 2);
+    }
+
+    #[test]
+    fn list_thumb_size_is_clamped_once_and_keeps_both_dimensions() {
+        let mut config = Config { list_thumb_width: 80, list_thumb_height: 100, ..Config::default() };
+        config.validate_list_thumb_size();
+        assert_eq!(config.list_thumb_size(), (80, 100));
+
+        let mut config = Config { list_thumb_width: 5, list_thumb_height: 5000, ..Config::default() };
+        config.validate_list_thumb_size();
+        assert_eq!(config.list_thumb_size(), (MIN_LIST_THUMB_WIDTH, MAX_LIST_THUMB_WIDTH * 3 / 2));
+    }
+
+    #[test]
+    fn only_movie_databases_are_migrated() {
+        let mut db = test_db("migrate_check");
+        db.add_movie(test_movie("Alien", 348, "/films/alien.mkv")).unwrap();
+        db.save_to_file().unwrap();
+        assert!(is_movie_database(Path::new(&db.data_file)));
+
+        let other = Path::new(&db.posters_dir).with_file_name("notes.db");
+        std::fs::write(&other, "shopping list").unwrap();
+        assert!(!is_movie_database(&other));
+        std::fs::write(&other, "{\"users\": []}").unwrap();
+        assert!(!is_movie_database(&other));
+
+        assert!(is_poster_image(Path::new("posters/348.JPG")));
+        assert!(!is_poster_image(Path::new("posters/readme.txt")));
+    }
+
+    #[test]
+    fn skipped_preview_posters_are_removed_unless_in_use() {
+        let mut db = test_db("unused_posters");
+        create_dir_all(&db.posters_dir).unwrap();
+        let kept = Path::new(&db.posters_dir).join("poster_348.jpg").to_string_lossy().to_string();
+        let skipped = Path::new(&db.posters_dir).join("poster_679.jpg").to_string_lossy().to_string();
+        std::fs::write(&kept, "jpg").unwrap();
+        std::fs::write(&skipped, "jpg").unwrap();
+        db.add_movie(Movie { poster_path: kept.clone(), ..test_movie("Alien", 348, "/films/alien.mkv") }).unwrap();
+
+        assert_eq!(db.remove_unused_posters(&[kept.clone(), skipped.clone()]), 1);
+        assert!(Path::new(&kept).exists());
+        assert!(!Path::new(&skipped).exists());
+    }
+
+    #[test]
+    fn year_range_filter_orders_and_clears_the_range() {
+        assert_eq!(year_range_filter(2000, 1990), Some((1990, 2000)));
+        assert_eq!(year_range_filter(1990, 2000), Some((1990, 2000)));
+        assert_eq!(year_range_filter(MIN_FILTER_YEAR, max_filter_year()), None);
+        assert_eq!(year_range_filter(max_filter_year(), MIN_FILTER_YEAR), None);
+    }
+
+    #[test]
+    fn filtered_movies_applies_year_range_and_min_rating() {
+        let mut db = test_db("year_rating_filter");
+        for (title, tmdb_id, year, rating) in [("Heat", 949, 1995, 8.0), ("Ronin", 8195, 1998, 7.2), ("Collateral", 1538, 2004, 7.5), ("Unknown", 1, 0, 9.0)] {
+            let mut movie = test_movie(title, tmdb_id, &format!("/films/{}.mkv", title));
+            movie.year = year;
+            movie.rating = rating;
+            db.add_movie(movie).unwrap();
+        }
+        let titles = |filters: &ListFilters| {
+            let mut titles: Vec<String> = db.filtered_movies(filters).into_iter().map(|m| m.title).collect();
+            titles.sort();
+            titles
+        };
+
+        let nineties = ListFilters { year_range: year_range_filter(1999, 1990), ..Default::default() };
+        assert_eq!(titles(&nineties), ["Heat", "Ronin"]);
+        let one_year = ListFilters { year_range: Some((2004, 2004)), ..Default::default() };
+        assert_eq!(titles(&one_year), ["Collateral"]);
+        // An unknown year is never inside a range, however wide
+        let any_known_year = ListFilters { year_range: Some((1, u16::MAX)), ..Default::default() };
+        assert_eq!(titles(&any_known_year), ["Collateral", "Heat", "Ronin"]);
+        assert_eq!(titles(&ListFilters::default()).len(), 4);
+
+        let well_rated = ListFilters { min_rating: 7.5, ..Default::default() };
+        assert_eq!(titles(&well_rated), ["Collateral", "Heat", "Unknown"]);
+        let both = ListFilters { year_range: Some((1990, 1999)), min_rating: 7.5, ..Default::default() };
+        assert_eq!(titles(&both), ["Heat"]);
+
+        assert_eq!(db.filter_by_year_range(2000, 1990).len(), 2);
+        assert!(db.filter_by_year_range(0, 0).is_empty());
+        assert_eq!(db.filter_by_min_rating(7.5).len(), 3);
+        assert_eq!(db.filter_by_min_rating(0.0).len(), 4);
+    }
 }